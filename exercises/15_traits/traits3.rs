@@ -14,13 +14,16 @@ struct OtherSoftware {
     version_number: String,
 }
 
-impl Licensed for SomeSoftware {} // Don't edit this line.
-impl Licensed for OtherSoftware {} // Don't edit this line.
+// rustlings:readonly-begin
+impl Licensed for SomeSoftware {}
+impl Licensed for OtherSoftware {}
+// rustlings:readonly-end
 
 fn main() {
     // You can optionally experiment here.
 }
 
+// rustlings:readonly-begin
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,3 +39,4 @@ mod tests {
         assert_eq!(other_software.licensing_info(), licensing_info);
     }
 }
+// rustlings:readonly-end
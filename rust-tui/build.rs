@@ -1,5 +1,13 @@
 fn main() {
     // Fix building from source on Windows because it can't handle file links.
     #[cfg(windows)]
-    let _ = std::fs::copy("dev/Cargo.toml", "dev-Cargo.toml");
+    {
+        let _ = std::fs::copy("dev/Cargo.toml", "dev-Cargo.toml");
+        println!("cargo:rerun-if-changed=dev/Cargo.toml");
+    }
+
+    // `include_files!` only embeds the Frog lessons that exist when it expands.
+    // Rebuild when one is added, changes to embedded files are already tracked.
+    println!("cargo:rerun-if-changed=frog");
+    println!("cargo:rerun-if-changed=build.rs");
 }
@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use serde::Deserialize;
+use std::{env, path::Path};
 
 #[derive(Deserialize)]
 struct ExerciseInfo {
@@ -26,6 +27,18 @@ pub fn include_files(_: TokenStream) -> TokenStream {
     let solution_files = exercises
         .iter()
         .map(|exercise| format!("../../solutions/{}/{}.rs", exercise.dir, exercise.name));
+    // Not every exercise has a Frog lesson.
+    // The build script of `rustlings` reruns the expansion when a lesson is added.
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("`CARGO_MANIFEST_DIR` isn't set");
+    let lesson_files = exercises.iter().map(|exercise| {
+        let path = format!("frog/{}/{}.md", exercise.dir, exercise.name);
+        if Path::new(&manifest_dir).join(&path).exists() {
+            let path = format!("../{path}");
+            quote! { Some(include_str!(#path)) }
+        } else {
+            quote! { None }
+        }
+    });
 
     let mut dirs = Vec::with_capacity(32);
     let mut dir_inds = vec![0; exercises.len()];
@@ -48,7 +61,7 @@ pub fn include_files(_: TokenStream) -> TokenStream {
     quote! {
         EmbeddedFiles {
             info_file: #info_file,
-            exercise_files: &[#(ExerciseFiles { exercise: include_bytes!(#exercise_files), solution: include_bytes!(#solution_files), dir_ind: #dir_inds }),*],
+            exercise_files: &[#(ExerciseFiles { exercise: include_bytes!(#exercise_files), solution: include_bytes!(#solution_files), lesson: #lesson_files, dir_ind: #dir_inds }),*],
             exercise_dirs: &[#(ExerciseDir { name: #dirs, readme: include_bytes!(#readmes) }),*]
         }
     }
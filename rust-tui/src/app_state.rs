@@ -3,7 +3,7 @@ use crossterm::{QueueableCommand, cursor, terminal};
 use std::{
     collections::HashSet,
     env,
-    fs::{File, OpenOptions},
+    fs::{self, File, OpenOptions},
     io::{Read, Seek, StdoutLock, Write},
     path::{MAIN_SEPARATOR_STR, Path},
     process::{Command, Stdio},
@@ -19,7 +19,7 @@ use crate::{
     cmd::CmdRunner,
     embedded::EMBEDDED_FILES,
     exercise::{Exercise, RunnableExercise},
-    info_file::ExerciseInfo,
+    info_file::{ExerciseInfo, LESSON_DIRS},
     term::{self, CheckProgressVisualizer},
 };
 
@@ -39,6 +39,15 @@ pub enum StateFileStatus {
     NotRead,
 }
 
+/// The answer to a quiz slide of a Frog lesson
+#[derive(Clone, Copy)]
+pub struct QuizAnswer {
+    pub exercise_ind: usize,
+    pub slide: usize,
+    pub choice: usize,
+    pub correct: bool,
+}
+
 #[derive(Clone, Copy)]
 pub enum CheckProgress {
     None,
@@ -52,6 +61,8 @@ pub struct AppState {
     exercises: Vec<Exercise>,
     // Caches the number of done exercises to avoid iterating over all exercises every time.
     n_done: u16,
+    // First answers to the quiz slides, stored after the done exercises in the state file.
+    quiz_answers: Vec<QuizAnswer>,
     final_message: String,
     state_file: File,
     // Preallocated buffer for reading and writing the state file.
@@ -86,11 +97,35 @@ fn build_canonical_path(
     })
 }
 
+// Parse a quiz answer line `<exercise> <slide> <choice> <correct|wrong>`
+fn parse_quiz_answer(line: &[u8], exercises: &[Exercise]) -> Option<QuizAnswer> {
+    let mut fields = str::from_utf8(line).ok()?.split(' ');
+    let name = fields.next()?;
+    let exercise_ind = exercises
+        .iter()
+        .position(|exercise| exercise.name == name)?;
+    let slide = fields.next()?.parse::<usize>().ok()?.checked_sub(1)?;
+    let choice = fields.next()?.parse::<usize>().ok()?.checked_sub(1)?;
+    let correct = match fields.next()? {
+        "correct" => true,
+        "wrong" => false,
+        _ => return None,
+    };
+
+    Some(QuizAnswer {
+        exercise_ind,
+        slide,
+        choice,
+        correct,
+    })
+}
+
 // Parse the state file and update exercises' done status
 fn parse_state_file(
     state_file: &mut File,
     file_buf: &mut Vec<u8>,
     exercises: &mut [Exercise],
+    quiz_answers: &mut Vec<QuizAnswer>,
 ) -> (usize, u16, StateFileStatus) {
     let mut current_exercise_ind = 0;
     let mut n_done: u16 = 0;
@@ -109,7 +144,9 @@ fn parse_state_file(
         return (0, 0, StateFileStatus::NotRead);
     }
 
-    let done_exercises: HashSet<_> = lines.take_while(|name| !name.is_empty()).collect();
+    let done_exercises: HashSet<_> = lines.by_ref().take_while(|name| !name.is_empty()).collect();
+    // Quiz answers follow the empty line after the done exercises
+    quiz_answers.extend(lines.filter_map(|line| parse_quiz_answer(line, exercises)));
 
     for (ind, exercise) in exercises.iter_mut().enumerate() {
         if done_exercises.contains(exercise.name.as_bytes()) {
@@ -149,6 +186,17 @@ impl AppState {
                 let name = exercise_info.name.leak();
                 let dir = exercise_info.dir.map(|dir| &*dir.leak());
                 let hint = exercise_info.hint.leak().trim_ascii();
+                let lesson = exercise_info.lesson.map(|lesson| &*lesson.leak());
+                let concepts = exercise_info
+                    .concepts
+                    .into_iter()
+                    .map(|concept| &*concept.leak())
+                    .collect();
+                let book = exercise_info
+                    .book
+                    .into_iter()
+                    .map(|page| &*page.leak())
+                    .collect();
                 let canonical_path = build_canonical_path(dir_canonical_path.as_deref(), dir, name);
 
                 Exercise {
@@ -159,14 +207,22 @@ impl AppState {
                     test: exercise_info.test,
                     strict_clippy: exercise_info.strict_clippy,
                     hint,
+                    lesson,
+                    concepts,
+                    book,
                     done: false,
                 }
             })
             .collect::<Vec<_>>();
 
         let mut file_buf = Vec::with_capacity(2048);
-        let (current_exercise_ind, n_done, state_file_status) =
-            parse_state_file(&mut state_file, &mut file_buf, &mut exercises);
+        let mut quiz_answers = Vec::new();
+        let (current_exercise_ind, n_done, state_file_status) = parse_state_file(
+            &mut state_file,
+            &mut file_buf,
+            &mut exercises,
+            &mut quiz_answers,
+        );
 
         file_buf.clear();
         file_buf.extend_from_slice(STATE_FILE_HEADER);
@@ -175,6 +231,7 @@ impl AppState {
             current_exercise_ind,
             exercises,
             n_done,
+            quiz_answers,
             final_message,
             state_file,
             file_buf,
@@ -236,6 +293,19 @@ impl AppState {
             }
         }
 
+        if !self.quiz_answers.is_empty() {
+            self.file_buf.push(b'\n');
+            for answer in &self.quiz_answers {
+                let result = if answer.correct { "correct" } else { "wrong" };
+                self.file_buf.push(b'\n');
+                self.file_buf
+                    .extend_from_slice(self.exercises[answer.exercise_ind].name.as_bytes());
+                self.file_buf.extend_from_slice(
+                    format!(" {} {} {result}", answer.slide + 1, answer.choice + 1).as_bytes(),
+                );
+            }
+        }
+
         self.state_file
             .rewind()
             .with_context(|| format!("Failed to rewind the state file {STATE_FILE_NAME}"))?;
@@ -249,6 +319,35 @@ impl AppState {
         Ok(())
     }
 
+    /// The first answers to the quiz slides of all lessons
+    #[inline]
+    pub fn quiz_answers(&self) -> &[QuizAnswer] {
+        &self.quiz_answers
+    }
+
+    /// The answer to a quiz slide in the lesson of the current exercise
+    pub fn quiz_answer(&self, slide: usize) -> Option<&QuizAnswer> {
+        self.quiz_answers.iter().find(|answer| {
+            answer.exercise_ind == self.current_exercise_ind && answer.slide == slide
+        })
+    }
+
+    /// Record the answer to a quiz slide in the lesson of the current exercise.
+    /// Only the first answer counts.
+    pub fn answer_quiz(&mut self, slide: usize, choice: usize, correct: bool) -> Result<()> {
+        if self.quiz_answer(slide).is_some() {
+            return Ok(());
+        }
+
+        self.quiz_answers.push(QuizAnswer {
+            exercise_ind: self.current_exercise_ind,
+            slide,
+            choice,
+            correct,
+        });
+        self.write()
+    }
+
     pub fn set_current_exercise_ind(&mut self, exercise_ind: usize) -> Result<()> {
         if exercise_ind == self.current_exercise_ind {
             return Ok(());
@@ -385,6 +484,39 @@ impl AppState {
         }
     }
 
+    /// Markdown of the current exercise's Frog lesson, see `lesson`.
+    pub fn current_lesson(&self) -> Result<Option<String>> {
+        self.lesson(self.current_exercise_ind)
+    }
+
+    /// Markdown of an exercise's Frog lesson.
+    /// Community exercises: The file declared as `lesson` in `info.toml`.
+    /// Official exercises: The lesson embedded in the binary.
+    /// A `frog/` directory next to the exercises is read first so that lesson authors
+    /// see their changes without rebuilding.
+    pub fn lesson(&self, exercise_ind: usize) -> Result<Option<String>> {
+        let exercise = &self.exercises[exercise_ind];
+        if let Some(lesson) = exercise.lesson {
+            return fs::read_to_string(lesson)
+                .with_context(|| format!("Failed to read the lesson file {lesson}"))
+                .map(Some);
+        }
+
+        let dir = exercise.dir.unwrap_or_default();
+        for frog_dir in LESSON_DIRS {
+            if let Ok(content) =
+                fs::read_to_string(format!("{frog_dir}/{dir}/{}.md", exercise.name))
+            {
+                return Ok(Some(content));
+            }
+        }
+
+        if self.official_exercises {
+            return Ok(EMBEDDED_FILES.lesson(exercise_ind).map(str::to_string));
+        }
+        Ok(None)
+    }
+
     fn check_all_exercises_impl(&mut self, stdout: &mut StdoutLock) -> Result<Option<usize>> {
         let term_width = terminal::size()
             .context("Failed to get the terminal size")?
@@ -595,6 +727,9 @@ mod tests {
             test: false,
             strict_clippy: false,
             hint: "",
+            lesson: None,
+            concepts: Vec::new(),
+            book: Vec::new(),
             done: false,
         }
     }
@@ -605,6 +740,7 @@ mod tests {
             current_exercise_ind: 0,
             exercises: vec![dummy_exercise(), dummy_exercise(), dummy_exercise()],
             n_done: 0,
+            quiz_answers: Vec::new(),
             final_message: String::new(),
             state_file: tempfile::tempfile().unwrap(),
             file_buf: Vec::new(),
@@ -636,4 +772,47 @@ mod tests {
         assert([false, true, false], [Some(2), Some(2), Some(0)]);
         assert([false, false, true], [Some(1), Some(0), Some(0)]);
     }
+
+    #[test]
+    fn quiz_answers_in_state_file() {
+        let exercises = || {
+            let mut exercises = vec![dummy_exercise(), dummy_exercise()];
+            exercises[1].name = "1";
+            exercises[1].done = true;
+            exercises
+        };
+        let mut app_state = AppState {
+            current_exercise_ind: 1,
+            exercises: exercises(),
+            n_done: 1,
+            quiz_answers: Vec::new(),
+            final_message: String::new(),
+            state_file: tempfile::tempfile().unwrap(),
+            file_buf: STATE_FILE_HEADER.to_vec(),
+            official_exercises: true,
+            cmd_runner: CmdRunner::build().unwrap(),
+            emit_file_links: true,
+        };
+        app_state.answer_quiz(2, 0, false).unwrap();
+        // only the first answer counts
+        app_state.answer_quiz(2, 1, true).unwrap();
+
+        let mut state_file = app_state.state_file;
+        state_file.rewind().unwrap();
+        let mut quiz_answers = Vec::new();
+        let (current_exercise_ind, n_done, _) = parse_state_file(
+            &mut state_file,
+            &mut Vec::new(),
+            &mut exercises(),
+            &mut quiz_answers,
+        );
+        assert_eq!((current_exercise_ind, n_done), (1, 1));
+        assert_eq!(quiz_answers.len(), 1);
+        let answer = quiz_answers[0];
+        assert_eq!(
+            (answer.exercise_ind, answer.slide, answer.choice),
+            (1, 2, 0)
+        );
+        assert!(!answer.correct);
+    }
 }
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use crate::exercise::Exercise;
+
+/// The Book on the web, used if the offline docs aren't installed.
+const ONLINE_URL: &str = "https://doc.rust-lang.org/book";
+
+/// Section number of a page of the Book like `ch03-01-variables-and-mutability` (§3.1)
+/// or `appendix-04-useful-development-tools` (Appendix D).
+/// `None` if the name doesn't have this form.
+pub fn section(page: &str) -> Option<String> {
+    let mut parts = page.splitn(3, '-');
+    let (chapter, section) = (parts.next()?, parts.next()?);
+    parts.next().filter(|slug| !slug.is_empty())?;
+    let section: u8 = section.parse().ok()?;
+
+    if let Some(chapter) = chapter.strip_prefix("ch") {
+        let chapter: u8 = chapter.parse().ok()?;
+        return Some(if section == 0 {
+            format!("§{chapter}")
+        } else {
+            format!("§{chapter}.{section}")
+        });
+    }
+    if chapter == "appendix" && (1..=26).contains(&section) {
+        return Some(format!("Appendix {}", char::from(b'A' + section - 1)));
+    }
+    None
+}
+
+/// Section number and title of a page of the Book, like `§3.1 Variables and mutability`
+pub fn label(page: &str) -> String {
+    let Some(section) = section(page) else {
+        return page.to_string();
+    };
+    let slug = page.splitn(3, '-').nth(2).unwrap_or_default();
+    let mut title = slug.replace('-', " ");
+    if let Some(first) = title.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    format!("{section} {title}")
+}
+
+/// The directory of offline docs installed by rustup, `topic` is a flag of `rustup doc`
+/// like `--book` or `--std`
+pub fn local_dir(topic: &str) -> Option<PathBuf> {
+    let output = Command::new("rustup")
+        .args(["doc", "--path", topic])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let index = PathBuf::from(String::from_utf8(output.stdout).ok()?.trim());
+    let dir = index.parent()?;
+    dir.is_dir().then(|| dir.to_path_buf())
+}
+
+/// `rustlings docs`: print the offline paths of the Book chapters of an exercise
+pub fn docs(exercise: &Exercise) -> Result<()> {
+    if exercise.book.is_empty() {
+        bail!(
+            "The exercise {} has no Book chapters.\n\
+             Try `rustlings hint {}` instead",
+            exercise.name,
+            exercise.name,
+        );
+    }
+
+    let local_dir = local_dir("--book");
+    let mut stdout = io::stdout().lock();
+    for page in &exercise.book {
+        let file_name = format!("{page}.html");
+        let link = match &local_dir {
+            Some(dir) if dir.join(&file_name).is_file() => {
+                dir.join(&file_name).display().to_string()
+            }
+            _ => format!("{ONLINE_URL}/{file_name}"),
+        };
+        writeln!(stdout, "{}\n  {link}", label(page)).context("Failed to write to stdout")?;
+    }
+    if local_dir.is_none() {
+        stdout.write_all(
+            b"\nThe offline Book isn't installed. Install it with `rustup component add rust-docs`\n",
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections() {
+        assert_eq!(
+            label("ch03-01-variables-and-mutability"),
+            "§3.1 Variables and mutability"
+        );
+        assert_eq!(section("ch06-00-enums").as_deref(), Some("§6"));
+        assert_eq!(
+            section("appendix-04-useful-development-tools").as_deref(),
+            Some("Appendix D")
+        );
+        assert_eq!(section("ch03-variables"), None);
+        assert_eq!(section("ch03-01-"), None);
+        assert_eq!(section("chapter-01-intro"), None);
+    }
+}
@@ -112,6 +112,9 @@ mod tests {
                 strict_clippy: true,
                 hint: String::new(),
                 skip_check_unsolved: false,
+                lesson: None,
+                concepts: Vec::new(),
+                book: Vec::new(),
             },
             ExerciseInfo {
                 name: String::from("2"),
@@ -120,6 +123,9 @@ mod tests {
                 strict_clippy: false,
                 hint: String::new(),
                 skip_check_unsolved: false,
+                lesson: None,
+                concepts: Vec::new(),
+                book: Vec::new(),
             },
         ];
 
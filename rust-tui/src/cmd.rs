@@ -1,11 +1,23 @@
 use anyhow::{Context, Result, bail};
 use serde::Deserialize;
 use std::{
-    io::{Read, pipe},
+    env, fs,
+    io::{Read, Write, pipe},
     path::PathBuf,
     process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
 };
 
+use crate::EDITION;
+
+/// Compiling a scratch program is stopped after this time.
+const SCRATCH_COMPILE_TIMEOUT: Duration = Duration::from_secs(60);
+/// A scratch program is killed after this time, e.g. if it loops forever or waits for input.
+const SCRATCH_RUN_TIMEOUT: Duration = Duration::from_secs(10);
+// how often to check if a command with a timeout exited
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
 /// Run a command with a description for a possible error and append the merged stdout and stderr.
 /// The boolean in the returned `Result` is true if the command's exit status is success.
 fn run_cmd(mut cmd: Command, description: &str, output: Option<&mut Vec<u8>>) -> Result<bool> {
@@ -46,12 +58,76 @@ fn run_cmd(mut cmd: Command, description: &str, output: Option<&mut Vec<u8>>) ->
         .map(|status| status.success())
 }
 
+/// Like `run_cmd` with output, but the command is killed if it runs longer than `timeout`.
+/// A note about it is appended to the output then, and the returned boolean is false.
+fn run_cmd_with_timeout(
+    mut cmd: Command,
+    description: &str,
+    output: &mut Vec<u8>,
+    timeout: Duration,
+) -> Result<bool> {
+    let (mut reader, writer) = pipe()
+        .with_context(|| format!("Failed to create a pipe to run the command `{description}`"))?;
+    let writer_clone = writer.try_clone().with_context(|| {
+        format!("Failed to clone the pipe writer for the command `{description}`")
+    })?;
+    cmd.stdin(Stdio::null()).stdout(writer_clone).stderr(writer);
+    let mut handle = cmd
+        .spawn()
+        .with_context(|| format!("Failed to run the command `{description}`"))?;
+    // NOTE: Dropping `cmd` closes our ends of the pipe, so reading stops when the command exits.
+    drop(cmd);
+
+    // read on another thread to be able to kill the command while it's still writing
+    let reading = thread::spawn(move || {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).map(|_| buf)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        let status = handle
+            .try_wait()
+            .with_context(|| format!("Failed to wait on the command `{description}` to exit"))?;
+        if status.is_some() {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            // fails if the command exited in the meantime
+            let _ = handle.kill();
+            handle.wait().with_context(|| {
+                format!("Failed to wait on the command `{description}` to exit")
+            })?;
+            break None;
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    };
+
+    let Ok(read) = reading.join() else {
+        bail!("Panic while reading the output of the command `{description}`");
+    };
+    output
+        .extend_from_slice(&read.with_context(|| {
+            format!("Failed to read the output of the command `{description}`")
+        })?);
+    output.push(b'\n');
+
+    match status {
+        Some(status) => Ok(status.success()),
+        None => {
+            writeln!(output, "Stopped after {} seconds", timeout.as_secs())?;
+            Ok(false)
+        }
+    }
+}
+
 // Parses parts of the output of `cargo metadata`.
 #[derive(Deserialize)]
 struct CargoMetadata {
     target_directory: PathBuf,
 }
 
+#[derive(Clone)]
 pub struct CmdRunner {
     target_dir: PathBuf,
 }
@@ -118,6 +194,78 @@ impl CmdRunner {
 
         run_cmd(Command::new(&bin_path), &bin_path.to_string_lossy(), output)
     }
+
+    /// Compile a standalone program with `rustc` into the scratch binary `name` in the target directory,
+    /// e.g. a code block of a Frog slide. Code without a line starting with `fn main` is wrapped in one.
+    /// The compiler output is appended to `output`, `rustc` is stopped if it takes too long.
+    /// Returns the path of the binary if the program compiled.
+    pub fn compile_scratch(
+        &self,
+        name: &str,
+        code: &str,
+        output: Option<&mut Vec<u8>>,
+    ) -> Result<Option<PathBuf>> {
+        let scratch_dir = self.target_dir.join("rustlings-scratch").join(name);
+        fs::create_dir_all(&scratch_dir).with_context(|| {
+            format!(
+                "Failed to create the scratch directory {}",
+                scratch_dir.display()
+            )
+        })?;
+
+        let has_main = code
+            .lines()
+            .any(|line| line.trim_start().starts_with("fn main"));
+        let source = if has_main {
+            code.to_string()
+        } else {
+            format!("fn main() {{\n{code}}}\n")
+        };
+        fs::write(scratch_dir.join("main.rs"), source)
+            .context("Failed to write the scratch program")?;
+
+        let bin_name = format!("scratch{}", env::consts::EXE_SUFFIX);
+        let mut rustc = Command::new("rustc");
+        rustc
+            .args([
+                "--edition",
+                EDITION,
+                "--color",
+                "never",
+                "-A",
+                "unused",
+                "-o",
+            ])
+            .arg(&bin_name)
+            .arg("main.rs")
+            .current_dir(&scratch_dir);
+        let mut discarded = Vec::new();
+        let compiled = run_cmd_with_timeout(
+            rustc,
+            "rustc main.rs",
+            output.unwrap_or(&mut discarded),
+            SCRATCH_COMPILE_TIMEOUT,
+        )?;
+
+        Ok(compiled.then(|| scratch_dir.join(bin_name)))
+    }
+
+    /// Compile and run a scratch program, see `compile_scratch`.
+    /// The compiler and program output are appended to `output`.
+    /// The program is killed if it runs too long, e.g. in an endless loop.
+    /// The boolean in the returned `Result` is true if the program compiled and exited successfully.
+    pub fn run_scratch(&self, code: &str, output: &mut Vec<u8>) -> Result<bool> {
+        let Some(bin_path) = self.compile_scratch("frog", code, Some(&mut *output))? else {
+            return Ok(false);
+        };
+
+        run_cmd_with_timeout(
+            Command::new(bin_path),
+            "scratch program",
+            output,
+            SCRATCH_RUN_TIMEOUT,
+        )
+    }
 }
 
 pub struct CargoSubcommand<'out> {
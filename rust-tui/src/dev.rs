@@ -21,6 +21,13 @@ pub enum DevCommands {
         /// Require that every exercise has a solution
         #[arg(short, long)]
         require_solutions: bool,
+        /// Require that every exercise has a Frog lesson
+        #[arg(long)]
+        require_lessons: bool,
+        /// The maximum width of the code blocks in Frog lessons.
+        /// Wider diagrams get mangled in the Frog panel.
+        #[arg(long, default_value_t = 80)]
+        max_lesson_width: usize,
     },
     /// Update the `Cargo.toml` file for the exercises
     Update,
@@ -36,7 +43,11 @@ impl DevCommands {
 
                 new::new(&path, no_git).context(INIT_ERR)
             }
-            Self::Check { require_solutions } => check::check(require_solutions),
+            Self::Check {
+                require_solutions,
+                require_lessons,
+                max_lesson_width,
+            } => check::check(require_solutions, require_lessons, max_lesson_width),
             Self::Update => update::update(),
         }
     }
@@ -10,12 +10,18 @@ use std::{
 };
 
 use crate::{
-    CURRENT_FORMAT_VERSION,
+    CURRENT_FORMAT_VERSION, EDITION, book,
     cargo_toml::{BINS_BUFFER_CAPACITY, append_bins, bins_start_end_ind},
     cmd::CmdRunner,
     exercise::{OUTPUT_CAPACITY, RunnableExercise},
+    glossary::Glossary,
     info_file::{ExerciseInfo, InfoFile},
+    readonly,
     term::ProgressCounter,
+    ui::{
+        buffer::display_width,
+        lesson::{CompileCheck, SLIDE_SEPARATOR, code_blocks, parse_slide},
+    },
 };
 
 const MAX_N_EXERCISES: usize = 999;
@@ -95,6 +101,22 @@ fn check_info_file_exercises(info_file: &InfoFile) -> Result<HashSet<PathBuf>> {
             bail!("The exercise name `{name}` is duplicated. Exercise names must all be unique");
         }
 
+        if let Some(lesson) = &exercise_info.lesson
+            && !Path::new(lesson).is_file()
+        {
+            bail!("The lesson file `{lesson}` of the exercise `{name}` doesn't exist");
+        }
+
+        if let Some(page) = exercise_info
+            .book
+            .iter()
+            .find(|page| book::section(page).is_none())
+        {
+            bail!(
+                "The Book page `{page}` of the exercise `{name}` isn't a page name like `ch03-01-variables-and-mutability`"
+            );
+        }
+
         let path = exercise_info.path();
 
         OpenOptions::new()
@@ -118,6 +140,9 @@ fn check_info_file_exercises(info_file: &InfoFile) -> Result<HashSet<PathBuf>> {
             );
         }
 
+        readonly::regions(file_buf.lines())
+            .with_context(|| format!("Invalid read-only markers in the file `{path}`"))?;
+
         let contains_tests = file_buf.contains("#[test]\n");
         if exercise_info.test {
             if !contains_tests {
@@ -267,6 +292,25 @@ fn check_exercises(info_file: &'static InfoFile, cmd_runner: &'static CmdRunner)
     handle.join().unwrap()
 }
 
+// Check that the solution keeps the read-only regions of the exercise byte-identical.
+fn check_readonly_regions(exercise_info: &ExerciseInfo, sol_path: &str) -> Result<()> {
+    let path = exercise_info.path();
+    let exercise =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read the file {path}"))?;
+    let solution = fs::read_to_string(sol_path)
+        .with_context(|| format!("Failed to read the file {sol_path}"))?;
+
+    let solution_regions = readonly::region_texts(&solution)
+        .with_context(|| format!("Invalid read-only markers in the file `{sol_path}`"))?;
+    if readonly::region_texts(&exercise)? != solution_regions {
+        bail!(
+            "The solution `{sol_path}` doesn't contain the read-only regions of the exercise `{path}` unchanged"
+        );
+    }
+
+    Ok(())
+}
+
 enum SolutionCheck {
     Success { sol_path: String },
     MissingOptional,
@@ -299,6 +343,10 @@ fn check_solutions(
                     return SolutionCheck::MissingOptional;
                 }
 
+                if let Err(e) = check_readonly_regions(exercise_info, &sol_path) {
+                    return SolutionCheck::Err(e);
+                }
+
                 let mut output = Vec::with_capacity(OUTPUT_CAPACITY);
                 match exercise_info.run_solution(Some(&mut output), cmd_runner) {
                     Ok(true) => SolutionCheck::Success { sol_path },
@@ -315,7 +363,7 @@ fn check_solutions(
     fmt_cmd
         .arg("--check")
         .arg("--edition")
-        .arg("2024")
+        .arg(EDITION)
         .arg("--color")
         .arg("always")
         .stdin(Stdio::null());
@@ -369,7 +417,188 @@ fn check_solutions(
     handle.join().unwrap()
 }
 
-pub fn check(require_solutions: bool) -> Result<()> {
+// Check that the concepts of all exercises are defined in the glossary.
+fn check_concepts(info_file: &InfoFile) -> Result<()> {
+    let glossary = Glossary::parse()?;
+    for exercise_info in &info_file.exercises {
+        for concept in &exercise_info.concepts {
+            if glossary.definition(concept).is_none() {
+                bail!(
+                    "The concept `{concept}` of the exercise `{}` isn't defined in `glossary.toml`",
+                    exercise_info.name,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// A code block of a lesson that `dev check` compiles.
+struct LessonBlock {
+    exercise_name: &'static str,
+    lesson_path: String,
+    slide: usize,
+    code: String,
+    compile_check: CompileCheck,
+}
+
+// Check the slide separators, the quizzes and the width of the code blocks of a lesson.
+// Returns the code blocks that have to be compiled.
+fn check_lesson(
+    exercise_info: &'static ExerciseInfo,
+    lesson_path: String,
+    max_width: usize,
+) -> Result<Vec<LessonBlock>> {
+    let lesson = fs::read_to_string(&lesson_path)
+        .with_context(|| format!("Failed to read the lesson file {lesson_path}"))?;
+
+    for (ind, line) in lesson.lines().enumerate() {
+        if line == SLIDE_SEPARATOR {
+            continue;
+        }
+        if line.contains(SLIDE_SEPARATOR) {
+            bail!(
+                "{lesson_path}:{}: The slide separator `{SLIDE_SEPARATOR}` must be alone on its line",
+                ind + 1,
+            );
+        }
+        if line.trim_start().starts_with("--") && line.to_lowercase().contains("slide") {
+            bail!(
+                "{lesson_path}:{}: Malformed slide separator `{line}`, expected `{SLIDE_SEPARATOR}`",
+                ind + 1,
+            );
+        }
+    }
+
+    let mut blocks = Vec::new();
+    for (slide_ind, text) in lesson.split(SLIDE_SEPARATOR).enumerate() {
+        let slide_number = slide_ind + 1;
+        if text.trim().is_empty() {
+            bail!("The slide {slide_number} of the lesson {lesson_path} is empty");
+        }
+        let slide = parse_slide(text).with_context(|| {
+            format!("Invalid quiz on the slide {slide_number} of the lesson {lesson_path}")
+        })?;
+
+        for block in code_blocks(&slide.body) {
+            // the panel strips the common indentation of code blocks
+            let indent = block
+                .code
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start_matches(' ').len())
+                .min()
+                .unwrap_or(0);
+            let too_wide = block
+                .code
+                .lines()
+                .any(|line| display_width(line.get(indent..).unwrap_or("").trim_end()) > max_width);
+            if too_wide {
+                bail!(
+                    "A code block on the slide {slide_number} of the lesson {lesson_path} is wider than {max_width} columns.\n\
+                     It would be mangled in the Frog panel"
+                );
+            }
+
+            if block.compile_check == CompileCheck::None {
+                continue;
+            }
+            if !block.is_rust() {
+                bail!(
+                    "A `{}` code block on the slide {slide_number} of the lesson {lesson_path} is marked to be compiled.\n\
+                     Only Rust code blocks can be checked",
+                    block.lang,
+                );
+            }
+            blocks.push(LessonBlock {
+                exercise_name: &exercise_info.name,
+                lesson_path: lesson_path.clone(),
+                slide: slide_number,
+                code: block.code,
+                compile_check: block.compile_check,
+            });
+        }
+    }
+
+    Ok(blocks)
+}
+
+fn check_lessons(
+    require_lessons: bool,
+    max_width: usize,
+    info_file: &'static InfoFile,
+    cmd_runner: &'static CmdRunner,
+) -> Result<()> {
+    let mut blocks = Vec::new();
+    for exercise_info in &info_file.exercises {
+        match exercise_info.lesson_path() {
+            Some(lesson_path) => {
+                blocks.extend(check_lesson(exercise_info, lesson_path, max_width)?)
+            }
+            None if require_lessons => {
+                bail!("The exercise {} has no Frog lesson", exercise_info.name)
+            }
+            None => (),
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(b"Compiling the code blocks of all lessons...\n")?;
+
+    let handles = blocks
+        .into_iter()
+        .enumerate()
+        .map(|(ind, block)| {
+            thread::Builder::new().spawn(move || {
+                let mut output = Vec::with_capacity(OUTPUT_CAPACITY);
+                let scratch_name = format!("lesson-{}-{ind}", block.exercise_name);
+                let compiled = cmd_runner
+                    .compile_scratch(&scratch_name, &block.code, Some(&mut output))
+                    .map(|bin_path| bin_path.is_some());
+                (block, compiled, output)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to spawn a thread to compile a code block of a lesson")?;
+
+    let mut progress_counter = ProgressCounter::new(&mut stdout, handles.len())?;
+
+    for handle in handles {
+        let Ok((block, compiled, output)) = handle.join() else {
+            bail!("Panic while trying to compile a code block of a lesson");
+        };
+
+        match (block.compile_check, compiled?) {
+            (CompileCheck::Compiles, false) => {
+                drop(progress_counter);
+                stdout.write_all(&output)?;
+                bail!(
+                    "A code block on the slide {} of the lesson {} failed to compile with the error above.\n\
+                     Mark it with `compile_fail` if it shouldn't compile",
+                    block.slide,
+                    block.lesson_path,
+                );
+            }
+            (CompileCheck::Fails, true) => bail!(
+                "A code block on the slide {} of the lesson {} is marked with `compile_fail` but compiles",
+                block.slide,
+                block.lesson_path,
+            ),
+            _ => (),
+        }
+
+        progress_counter.increment()?;
+    }
+
+    Ok(())
+}
+
+pub fn check(
+    require_solutions: bool,
+    require_lessons: bool,
+    max_lesson_width: usize,
+) -> Result<()> {
     let info_file = InfoFile::parse()?;
 
     if info_file.exercises.len() > MAX_N_EXERCISES {
@@ -388,6 +617,8 @@ pub fn check(require_solutions: bool) -> Result<()> {
     let info_file = Box::leak(Box::new(info_file));
 
     check_exercises(info_file, cmd_runner)?;
+    check_concepts(info_file)?;
+    check_lessons(require_lessons, max_lesson_width, info_file, cmd_runner)?;
     check_solutions(require_solutions, info_file, cmd_runner)?;
 
     println!("Everything looks fine!");
@@ -15,6 +15,8 @@ struct ExerciseFiles {
     exercise: &'static [u8],
     // The content of the solution file.
     solution: &'static [u8],
+    // The Frog lesson from `frog/<dir>/<name>.md` if the exercise has one.
+    lesson: Option<&'static str>,
     // Index of the related `ExerciseDir` in `EmbeddedFiles::exercise_dirs`.
     dir_ind: usize,
 }
@@ -104,6 +106,11 @@ impl EmbeddedFiles {
             .with_context(|| format!("Failed to write the exercise file {path}"))
     }
 
+    /// The embedded Frog lesson of an exercise.
+    pub fn lesson(&self, exercise_ind: usize) -> Option<&'static str> {
+        self.exercise_files[exercise_ind].lesson
+    }
+
     /// Write the solution file to disk and return its path.
     pub fn write_solution_to_disk(
         &self,
@@ -165,4 +172,12 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn lessons() {
+        let intro = EMBEDDED_FILES
+            .lesson(0)
+            .expect("`intro1` has a Frog lesson");
+        assert!(intro.contains("--- slide ---"));
+    }
 }
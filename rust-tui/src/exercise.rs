@@ -74,6 +74,12 @@ pub struct Exercise {
     pub test: bool,
     pub strict_clippy: bool,
     pub hint: &'static str,
+    /// Path of the Frog lesson declared in `info.toml` by community exercises.
+    pub lesson: Option<&'static str>,
+    /// Names of the concepts in the glossary
+    pub concepts: Vec<&'static str>,
+    /// Pages of the Rust Book
+    pub book: Vec<&'static str>,
     pub done: bool,
 }
 
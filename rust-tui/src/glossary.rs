@@ -0,0 +1,59 @@
+use anyhow::{Context, Error, Result};
+use std::{collections::BTreeMap, fs, io::ErrorKind};
+
+/// The glossary of the official exercises.
+const EMBEDDED_GLOSSARY: &str = include_str!("../frog/glossary.toml");
+
+/// Short definitions of the concepts that exercises declare in `info.toml`.
+/// Deserialized from the `glossary.toml` file, a table of concept names and definitions.
+pub struct Glossary {
+    definitions: BTreeMap<String, String>,
+}
+
+impl Glossary {
+    /// Official exercises: The glossary embedded in the binary.
+    /// Community exercises: The `glossary.toml` file next to `info.toml`.
+    pub fn parse() -> Result<Self> {
+        let definitions = match fs::read_to_string("glossary.toml") {
+            Ok(file_content) => toml::de::from_str(&file_content)
+                .context("Failed to parse the `glossary.toml` file")?,
+            Err(e) => {
+                if e.kind() == ErrorKind::NotFound {
+                    toml::de::from_str(EMBEDDED_GLOSSARY)
+                        .context("Failed to parse the embedded `glossary.toml` file")?
+                } else {
+                    return Err(Error::from(e).context("Failed to read the `glossary.toml` file"));
+                }
+            }
+        };
+
+        Ok(Self { definitions })
+    }
+
+    pub fn definition(&self, concept: &str) -> Option<&str> {
+        self.definitions.get(concept).map(String::as_str)
+    }
+
+    /// Concept names in alphabetical order
+    pub fn concepts(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{embedded::EMBEDDED_FILES, info_file::InfoFile};
+
+    #[test]
+    fn official_concepts_are_defined() {
+        let info_file: InfoFile = toml::de::from_str(EMBEDDED_FILES.info_file).unwrap();
+        let definitions: BTreeMap<String, String> = toml::de::from_str(EMBEDDED_GLOSSARY).unwrap();
+        for exercise in info_file.exercises {
+            assert!(!exercise.concepts.is_empty(), "{}", exercise.name);
+            for concept in exercise.concepts {
+                assert!(definitions.contains_key(&concept), "{concept}");
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{Context, Error, Result, bail};
 use serde::Deserialize;
-use std::{fs, io::ErrorKind};
+use std::{fs, io::ErrorKind, path::Path};
 
 use crate::{embedded::EMBEDDED_FILES, exercise::RunnableExercise};
 
@@ -22,7 +22,21 @@ pub struct ExerciseInfo {
     /// The exercise is already solved. Ignore it when checking that all exercises are unsolved.
     #[serde(default)]
     pub skip_check_unsolved: bool,
+    /// Markdown file shown in the Frog panel, relative to the directory of `info.toml`.
+    /// Slides are separated by `--- slide ---` lines.
+    pub lesson: Option<String>,
+    /// Rust concepts practiced by the exercise, defined in the glossary.
+    #[serde(default)]
+    pub concepts: Vec<String>,
+    /// Pages of the Rust Book explaining the exercise, like `ch03-01-variables-and-mutability`.
+    #[serde(default)]
+    pub book: Vec<String>,
 }
+
+/// Directories with the lessons of exercises that don't declare a `lesson` file,
+/// `frog/<dir>/<name>.md` in one of them
+pub const LESSON_DIRS: [&str; 2] = ["rust-tui/frog", "frog"];
+
 #[inline(always)]
 const fn default_true() -> bool {
     true
@@ -52,6 +66,20 @@ impl ExerciseInfo {
 
         path
     }
+
+    /// Path to the exercise's Frog lesson: The declared `lesson` file,
+    /// otherwise the lesson in one of the `LESSON_DIRS` if it exists.
+    pub fn lesson_path(&self) -> Option<String> {
+        if let Some(lesson) = &self.lesson {
+            return Some(lesson.clone());
+        }
+
+        let dir = self.dir.as_deref().unwrap_or_default();
+        LESSON_DIRS
+            .iter()
+            .map(|lesson_dir| format!("{lesson_dir}/{dir}/{}.md", self.name))
+            .find(|path| Path::new(path).exists())
+    }
 }
 
 impl RunnableExercise for ExerciseInfo {
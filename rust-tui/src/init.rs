@@ -204,6 +204,8 @@ cargo.targetDir = true
 const GITIGNORE: &[u8] = b"Cargo.lock
 target/
 .vscode/
+.rustlings-undo/
+.rustlings-tui.toml
 ";
 
 pub const VS_CODE_EXTENSIONS_JSON: &[u8] = br#"{"recommendations":["rust-lang.rust-analyzer"]}"#;
@@ -11,18 +11,25 @@ use term::{clear_terminal, press_enter_prompt};
 use self::{app_state::AppState, dev::DevCommands, info_file::InfoFile};
 
 mod app_state;
+mod book;
 mod cargo_toml;
 mod cmd;
 mod dev;
 mod embedded;
 mod exercise;
+mod glossary;
 mod info_file;
 mod init;
+mod readonly;
 mod run;
+mod stats;
 mod term;
 mod ui;
 
 const CURRENT_FORMAT_VERSION: u8 = 1;
+/// The edition of the exercises, passed to `rustfmt` (`dev check` and `:fmt`) and to `rustc`
+/// for the scratch code of Frog lessons.
+const EDITION: &str = "2024";
 
 /// Rustlings is a collection of small exercises to get you used to writing and reading Rust code
 #[derive(Parser)]
@@ -57,6 +64,14 @@ enum Subcommands {
         /// The name of the exercise
         name: Option<String>,
     },
+    /// Print the paths of the offline Rust Book chapters explaining an exercise.
+    /// Uses the current exercise if the exercise name is not specified
+    Docs {
+        /// The name of the exercise
+        name: Option<String>,
+    },
+    /// Show the progress and the mastery of the concepts practiced by the exercises
+    Stats,
     /// Commands for developing (community) Rustlings exercises
     #[command(subcommand)]
     Dev(DevCommands),
@@ -171,6 +186,13 @@ fn main() -> Result<ExitCode> {
             }
             println!("{}", app_state.current_exercise().hint);
         }
+        Some(Subcommands::Docs { name }) => {
+            if let Some(name) = name {
+                app_state.set_current_exercise_by_name(&name)?;
+            }
+            book::docs(app_state.current_exercise())?;
+        }
+        Some(Subcommands::Stats) => stats::stats(&app_state)?,
         // Handled in an earlier match.
         Some(Subcommands::Init | Subcommands::Dev(_)) => (),
     }
@@ -0,0 +1,79 @@
+use anyhow::{Result, bail};
+use std::ops::RangeInclusive;
+
+/// Comment line starting a region of an exercise that learners must not change.
+pub const BEGIN_MARKER: &str = "// rustlings:readonly-begin";
+/// Comment line ending a read-only region.
+pub const END_MARKER: &str = "// rustlings:readonly-end";
+
+/// Return the line ranges of the read-only regions including their marker lines.
+/// Fails if the markers aren't balanced.
+pub fn regions<S: AsRef<str>>(
+    lines: impl IntoIterator<Item = S>,
+) -> Result<Vec<RangeInclusive<usize>>> {
+    let mut regions = Vec::new();
+    let mut begin = None;
+
+    for (ind, line) in lines.into_iter().enumerate() {
+        match line.as_ref().trim() {
+            BEGIN_MARKER => {
+                if let Some(begin) = begin {
+                    bail!(
+                        "`{BEGIN_MARKER}` on line {} is inside the read-only region starting on line {}",
+                        ind + 1,
+                        begin + 1,
+                    );
+                }
+                begin = Some(ind);
+            }
+            END_MARKER => {
+                let Some(begin) = begin.take() else {
+                    bail!(
+                        "`{END_MARKER}` on line {} without a `{BEGIN_MARKER}` before it",
+                        ind + 1,
+                    );
+                };
+                regions.push(begin..=ind);
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(begin) = begin {
+        bail!(
+            "The read-only region starting on line {} isn't closed by `{END_MARKER}`",
+            begin + 1,
+        );
+    }
+
+    Ok(regions)
+}
+
+/// Return the text of every read-only region in `content`.
+pub fn region_texts(content: &str) -> Result<Vec<String>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let regions = regions(&lines)?;
+    Ok(regions
+        .into_iter()
+        .map(|region| lines[region].join("\n"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balanced_markers() {
+        let content = format!("fn main() {{}}\n{BEGIN_MARKER}\nmod tests {{}}\n{END_MARKER}\n");
+        assert_eq!(regions(content.lines()).unwrap(), [1..=3]);
+        assert_eq!(
+            region_texts(&content).unwrap(),
+            [format!("{BEGIN_MARKER}\nmod tests {{}}\n{END_MARKER}")],
+        );
+
+        assert!(regions([BEGIN_MARKER, BEGIN_MARKER]).is_err());
+        assert!(regions([END_MARKER]).is_err());
+        assert!(regions([BEGIN_MARKER]).is_err());
+    }
+}
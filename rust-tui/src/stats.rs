@@ -0,0 +1,185 @@
+use anyhow::Result;
+use crossterm::{
+    QueueableCommand,
+    style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor},
+};
+use std::io::{self, Write};
+
+use crate::{
+    app_state::{AppState, QuizAnswer},
+    exercise::Exercise,
+};
+
+// Width of the mastery bar of a concept
+const BAR_WIDTH: usize = 20;
+
+/// Progress on the exercises and quizzes tagged with a concept
+#[derive(PartialEq, Eq, Debug)]
+pub struct ConceptStats<'a> {
+    pub concept: &'a str,
+    pub n_exercises: usize,
+    pub n_done: usize,
+    pub n_quizzes: usize,
+    pub n_correct: usize,
+}
+
+impl ConceptStats<'_> {
+    /// Share of the done exercises and right quiz answers in percent
+    pub fn mastery(&self) -> usize {
+        let total = self.n_exercises + self.n_quizzes;
+        (self.n_done + self.n_correct) * 100 / total.max(1)
+    }
+}
+
+/// Stats of every concept that an exercise declares, in alphabetical order
+pub fn concept_stats<'a>(
+    exercises: &'a [Exercise],
+    quiz_answers: &[QuizAnswer],
+) -> Vec<ConceptStats<'a>> {
+    let mut concepts: Vec<&str> = exercises
+        .iter()
+        .flat_map(|exercise| exercise.concepts.iter().copied())
+        .collect();
+    concepts.sort_unstable();
+    concepts.dedup();
+
+    concepts
+        .into_iter()
+        .map(|concept| {
+            let tagged = |ind: usize| exercises[ind].concepts.contains(&concept);
+            let tagged_exercises = exercises
+                .iter()
+                .filter(|exercise| exercise.concepts.contains(&concept));
+            let answers = quiz_answers
+                .iter()
+                .filter(|answer| tagged(answer.exercise_ind));
+
+            ConceptStats {
+                concept,
+                n_exercises: tagged_exercises.clone().count(),
+                n_done: tagged_exercises.filter(|exercise| exercise.done).count(),
+                n_quizzes: answers.clone().count(),
+                n_correct: answers.filter(|answer| answer.correct).count(),
+            }
+        })
+        .collect()
+}
+
+/// `rustlings stats`: the progress and the mastery of each concept
+pub fn stats(app_state: &AppState) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    let n_exercises = app_state.exercises().len();
+    let n_done = usize::from(app_state.n_done());
+    writeln!(
+        stdout,
+        "Exercises: {n_done}/{n_exercises} done ({}%)",
+        n_done * 100 / n_exercises.max(1),
+    )?;
+    let quiz_answers = app_state.quiz_answers();
+    let n_correct = quiz_answers.iter().filter(|answer| answer.correct).count();
+    writeln!(
+        stdout,
+        "Quizzes: {n_correct}/{} answered correctly",
+        quiz_answers.len(),
+    )?;
+
+    let stats = concept_stats(app_state.exercises(), quiz_answers);
+    if stats.is_empty() {
+        return Ok(());
+    }
+
+    stdout.write_all(b"\nMastery by concept (done exercises and right quiz answers)\n\n")?;
+    let name_width = stats
+        .iter()
+        .map(|stats| stats.concept.len())
+        .max()
+        .unwrap_or(0);
+    for stats in stats {
+        let mastery = stats.mastery();
+        let filled = mastery * BAR_WIDTH / 100;
+        let color = match mastery {
+            100 => Color::Green,
+            0 => Color::DarkGrey,
+            _ => Color::Yellow,
+        };
+
+        write!(stdout, "  {:<name_width$}  ", stats.concept)?;
+        stdout.queue(SetForegroundColor(color))?;
+        write!(
+            stdout,
+            "{}{}",
+            "█".repeat(filled),
+            "░".repeat(BAR_WIDTH - filled)
+        )?;
+        stdout.queue(ResetColor)?;
+        stdout.queue(SetAttribute(Attribute::Bold))?;
+        write!(stdout, " {mastery:>3}%")?;
+        stdout.queue(SetAttribute(Attribute::Reset))?;
+        write!(stdout, "  {}/{} exercises", stats.n_done, stats.n_exercises)?;
+        if stats.n_quizzes > 0 {
+            write!(stdout, ", {}/{} quizzes", stats.n_correct, stats.n_quizzes)?;
+        }
+        stdout.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(concepts: Vec<&'static str>, done: bool) -> Exercise {
+        Exercise {
+            dir: None,
+            name: "0",
+            path: "exercises/0.rs",
+            canonical_path: None,
+            test: false,
+            strict_clippy: false,
+            hint: "",
+            lesson: None,
+            concepts,
+            book: Vec::new(),
+            done,
+        }
+    }
+
+    #[test]
+    fn mastery_by_concept() {
+        let exercises = [
+            exercise(vec!["ownership"], true),
+            exercise(vec!["ownership", "borrowing"], false),
+            exercise(Vec::new(), true),
+        ];
+        let answer = |exercise_ind, correct| QuizAnswer {
+            exercise_ind,
+            slide: 0,
+            choice: 0,
+            correct,
+        };
+        let answers = [answer(0, true), answer(1, false), answer(2, true)];
+
+        let stats = concept_stats(&exercises, &answers);
+        assert_eq!(
+            stats,
+            [
+                ConceptStats {
+                    concept: "borrowing",
+                    n_exercises: 1,
+                    n_done: 0,
+                    n_quizzes: 1,
+                    n_correct: 0,
+                },
+                ConceptStats {
+                    concept: "ownership",
+                    n_exercises: 2,
+                    n_done: 1,
+                    n_quizzes: 2,
+                    n_correct: 1,
+                },
+            ]
+        );
+        assert_eq!(stats[1].mastery(), 50);
+    }
+}
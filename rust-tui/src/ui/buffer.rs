@@ -0,0 +1,317 @@
+//! Rope-backed text buffer addressed by line and grapheme column
+
+use std::borrow::Cow;
+
+use ropey::Rope;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A position as `(row, col)` where `col` counts graphemes (what the user sees as one character)
+pub type Pos = (usize, usize);
+
+/// Whether a grapheme is whitespace (graphemes are never empty)
+pub fn is_blank(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+/// Number of graphemes in `s`
+pub fn grapheme_len(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+/// Number of terminal cells `s` takes
+pub fn display_width(s: &str) -> usize {
+    s.graphemes(true).map(UnicodeWidthStr::width).sum()
+}
+
+/// Cells taken by the first `col` graphemes of `line`
+pub fn cells_before(line: &str, col: usize) -> usize {
+    line.graphemes(true)
+        .take(col)
+        .map(UnicodeWidthStr::width)
+        .sum()
+}
+
+/// Column of the first grapheme of `line` starting at or after `cell`,
+/// `None` if the line ends before that cell
+pub fn col_at_cell(line: &str, cell: usize) -> Option<usize> {
+    let mut x = 0;
+    for (col, grapheme) in line.graphemes(true).enumerate() {
+        if x >= cell {
+            return Some(col);
+        }
+        x += grapheme.width();
+    }
+    (x >= cell).then(|| grapheme_len(line))
+}
+
+/// Columns `[start, end)` of the graphemes of `line` that overlap the cells `left..=right`,
+/// like a block selection over wide characters. A zero-width grapheme counts as one cell.
+pub fn cols_in_cells(line: &str, left: usize, right: usize) -> (usize, usize) {
+    let (mut start, mut end) = (None, None);
+    let mut x = 0;
+    let mut len = 0;
+    for (col, grapheme) in line.graphemes(true).enumerate() {
+        if x > right {
+            end = Some(col);
+            break;
+        }
+        let width = grapheme.width();
+        if start.is_none() && x + width.max(1) > left {
+            start = Some(col);
+        }
+        x += width;
+        len = col + 1;
+    }
+    let end = end.unwrap_or(len);
+    (start.unwrap_or(end), end)
+}
+
+/// Display row and cell of grapheme `col` when `line` is soft-wrapped at `width` cells.
+/// A grapheme that doesn't fit on a row starts the next one.
+/// `col` can be the line length for the cursor after the last grapheme.
+pub fn wrapped_pos(line: &str, col: usize, width: usize) -> (usize, usize) {
+    let (mut row, mut x) = (0, 0);
+    for (ind, grapheme) in line.graphemes(true).chain([" "]).enumerate() {
+        let grapheme_width = grapheme.width();
+        if x > 0 && x + grapheme_width > width {
+            row += 1;
+            x = 0;
+        }
+        if ind == col {
+            break;
+        }
+        x += grapheme_width;
+    }
+    (row, x)
+}
+
+/// Byte offset of the char at `units` UTF-16 code units into `s`, clamped to its length
+pub fn utf16_to_byte(s: &str, units: usize) -> usize {
+    let mut offset = 0;
+    s.char_indices()
+        .find(|&(_, c)| {
+            offset += c.len_utf16();
+            offset > units
+        })
+        .map_or(s.len(), |(byte, _)| byte)
+}
+
+/// Lines are separated by `\n` only and the text has no trailing newline,
+/// so `len_lines` is the number of editor rows (at least 1).
+pub struct Buffer {
+    rope: Rope,
+}
+
+impl Buffer {
+    pub fn new(content: &str) -> Self {
+        // normalize `\r\n` and drop the final newline like `str::lines`
+        let text = content.lines().collect::<Vec<_>>().join("\n");
+        Self {
+            rope: Rope::from_str(&text),
+        }
+    }
+
+    pub fn from_rope(rope: Rope) -> Self {
+        Self { rope }
+    }
+
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Text of `row` without the line break (empty for rows past the end)
+    pub fn line(&self, row: usize) -> Cow<'_, str> {
+        if row >= self.len_lines() {
+            return Cow::Borrowed("");
+        }
+        let line = self.rope.line(row);
+        let len = line.len_chars();
+        if len > 0 && line.char(len - 1) == '\n' {
+            line.slice(..len - 1).into()
+        } else {
+            line.into()
+        }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        (0..self.len_lines()).map(|row| self.line(row))
+    }
+
+    /// Number of grapheme columns on `row`
+    pub fn line_len(&self, row: usize) -> usize {
+        grapheme_len(&self.line(row))
+    }
+
+    /// Rope char index of a position. `col` is clamped to the end of the line.
+    pub fn char_idx(&self, (row, col): Pos) -> usize {
+        let row = row.min(self.len_lines() - 1);
+        let line = self.line(row);
+        let chars: usize = line
+            .graphemes(true)
+            .take(col)
+            .map(|g| g.chars().count())
+            .sum();
+        self.rope.line_to_char(row) + chars
+    }
+
+    /// Position of a rope char index (inside a grapheme counts as after it)
+    pub fn pos_at(&self, idx: usize) -> Pos {
+        let idx = idx.min(self.rope.len_chars());
+        let row = self.rope.char_to_line(idx);
+        let line = self.line(row);
+        let chars = idx - self.rope.line_to_char(row);
+        let byte = line
+            .char_indices()
+            .nth(chars)
+            .map_or(line.len(), |(i, _)| i);
+        (row, grapheme_len(&line[..byte]))
+    }
+
+    /// `(line, UTF-16 column)` of a position as used by the language server protocol
+    pub fn utf16_pos(&self, pos: Pos) -> (usize, usize) {
+        let idx = self.char_idx(pos);
+        let row = self.rope.char_to_line(idx);
+        let line_start = self.rope.line_to_char(row);
+        let col = self.rope.char_to_utf16_cu(idx) - self.rope.char_to_utf16_cu(line_start);
+        (row, col)
+    }
+
+    /// Position of an LSP `(line, UTF-16 column)`, clamped to the buffer
+    pub fn pos_from_utf16(&self, (row, col): (usize, usize)) -> Pos {
+        let row = row.min(self.len_lines() - 1);
+        let line = self.line(row);
+        (row, grapheme_len(&line[..utf16_to_byte(&line, col)]))
+    }
+
+    /// Text between two positions (`end` exclusive)
+    pub fn slice(&self, start: Pos, end: Pos) -> String {
+        let (start, end) = (self.char_idx(start), self.char_idx(end));
+        self.rope.slice(start..end.max(start)).to_string()
+    }
+
+    pub fn insert(&mut self, pos: Pos, text: &str) {
+        let idx = self.char_idx(pos);
+        self.rope.insert(idx, text);
+    }
+
+    /// Remove the text between two positions (`end` exclusive)
+    pub fn remove(&mut self, start: Pos, end: Pos) {
+        let (start, end) = (self.char_idx(start), self.char_idx(end));
+        if start < end {
+            self.rope.remove(start..end);
+        }
+    }
+
+    /// Replace the text of `row` (its line break is kept)
+    pub fn set_line(&mut self, row: usize, text: &str) {
+        let len = self.line_len(row);
+        self.remove((row, 0), (row, len));
+        self.insert((row, 0), text);
+    }
+
+    /// Insert `text` as whole lines before `row`. `row` can be `len_lines()` to append.
+    pub fn insert_lines(&mut self, row: usize, text: &str) {
+        if row < self.len_lines() {
+            let idx = self.rope.line_to_char(row);
+            self.rope.insert(idx, &format!("{text}\n"));
+        } else {
+            let idx = self.rope.len_chars();
+            self.rope.insert(idx, &format!("\n{text}"));
+        }
+    }
+
+    /// Remove the rows `start..=end`. The buffer keeps at least one (empty) line.
+    pub fn remove_lines(&mut self, start: usize, end: usize) {
+        let last = self.len_lines() - 1;
+        let end = end.min(last);
+        let range = if end < last {
+            self.rope.line_to_char(start)..self.rope.line_to_char(end + 1)
+        } else if start > 0 {
+            // take the line break before the first row instead of the missing one after the last
+            self.rope.line_to_char(start) - 1..self.rope.len_chars()
+        } else {
+            0..self.rope.len_chars()
+        };
+        self.rope.remove(range);
+    }
+}
+
+impl std::fmt::Display for Buffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.rope.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_and_grapheme_columns() {
+        let buf = Buffer::new("let s = \"👨‍👩‍👧 e\u{301}\";\r\nfn\n");
+        assert_eq!(buf.len_lines(), 2);
+        assert_eq!(buf.line(1), "fn");
+        // the family emoji and the `e` + combining accent are one column each
+        assert_eq!(buf.line_len(0), 14);
+        assert_eq!(buf.slice((0, 9), (0, 10)), "👨‍👩‍👧");
+        // the emoji is 8 UTF-16 code units
+        assert_eq!(buf.utf16_pos((0, 10)), (0, 17));
+        assert_eq!(buf.pos_from_utf16((0, 17)), (0, 10));
+        assert_eq!(buf.to_string(), "let s = \"👨‍👩‍👧 e\u{301}\";\nfn");
+    }
+
+    #[test]
+    fn cells_of_wide_graphemes() {
+        // `世` and `界` take two cells each
+        let line = "a世界b";
+        assert_eq!(cells_before(line, 2), 3);
+        assert_eq!(col_at_cell(line, 1), Some(1));
+        assert_eq!(col_at_cell(line, 2), Some(2));
+        assert_eq!(col_at_cell(line, 6), Some(4));
+        assert_eq!(col_at_cell(line, 7), None);
+        // half of a wide grapheme selects all of it
+        assert_eq!(cols_in_cells(line, 2, 3), (1, 3));
+        assert_eq!(cols_in_cells(line, 0, 0), (0, 1));
+        assert_eq!(cols_in_cells(line, 5, 9), (3, 4));
+        assert_eq!(cols_in_cells(line, 7, 9), (4, 4));
+    }
+
+    #[test]
+    fn wrapped_positions() {
+        // the wide char doesn't fit after `abc` and moves to the second row
+        let line = "abc漢de";
+        assert_eq!(display_width(line), 7);
+        assert_eq!(wrapped_pos(line, 2, 4), (0, 2));
+        assert_eq!(wrapped_pos(line, 3, 4), (1, 0));
+        assert_eq!(wrapped_pos(line, 5, 4), (1, 3));
+        // the cursor after a full row is on the next one
+        assert_eq!(wrapped_pos("abcd", 4, 4), (1, 0));
+    }
+
+    #[test]
+    fn line_operations() {
+        let mut buf = Buffer::new("a\nb\nc");
+        buf.insert_lines(1, "x");
+        buf.insert_lines(4, "z");
+        assert_eq!(buf.to_string(), "a\nx\nb\nc\nz");
+
+        buf.remove_lines(3, 4);
+        assert_eq!(buf.to_string(), "a\nx\nb");
+        buf.remove((0, 1), (1, 1));
+        assert_eq!(buf.to_string(), "a\nb");
+        buf.set_line(1, "yy");
+        assert_eq!(buf.to_string(), "a\nyy");
+
+        buf.remove_lines(0, 1);
+        assert_eq!(buf.len_lines(), 1);
+        assert_eq!(buf.line(0), "");
+    }
+}
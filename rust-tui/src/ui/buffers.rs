@@ -0,0 +1,222 @@
+//! Files open in the editor next to the current exercise.
+//! The shown buffer lives in the editor fields of `TuiState`, the others wait in `buffers`
+//! until they are swapped in.
+
+use std::{fs, mem, path::Path, time::SystemTime};
+
+use crate::ui::{editor::TextEditor, state::TuiState, theme};
+
+/// The current exercise is always the first buffer, compiling and navigating act on it
+pub const EXERCISE_BUFFER: usize = 0;
+
+/// A file open in the editor with its own undo history and unsaved changes
+pub struct OpenFile {
+    pub path: String,
+    pub editor: TextEditor,
+    pub modified: bool,
+    pub last_file_modified: Option<SystemTime>,
+}
+
+impl OpenFile {
+    /// The slot of the shown buffer, its content is in `TuiState`
+    pub fn shown() -> Self {
+        Self {
+            path: String::new(),
+            editor: TextEditor::new(""),
+            modified: false,
+            last_file_modified: None,
+        }
+    }
+}
+
+fn same_file(a: &str, b: &str) -> bool {
+    match (Path::new(a).canonicalize(), Path::new(b).canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Paths starting with `prefix`, directories ending with `/`.
+/// Hidden files are only listed if the prefix of their name starts with a dot.
+pub fn complete_path(prefix: &str) -> Vec<String> {
+    let (dir, name) = match prefix.rfind('/') {
+        Some(ind) => prefix.split_at(ind + 1),
+        None => ("", prefix),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            if !file_name.starts_with(name)
+                || (file_name.starts_with('.') && !name.starts_with('.'))
+            {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{dir}{file_name}{slash}"))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+impl TuiState<'_> {
+    // exchange the shown buffer with the one in slot `ind`
+    fn swap_buffer(&mut self, ind: usize) {
+        let file = &mut self.buffers[ind];
+        mem::swap(&mut self.editor, &mut file.editor);
+        mem::swap(&mut self.file_path, &mut file.path);
+        mem::swap(&mut self.modified, &mut file.modified);
+        mem::swap(&mut self.last_file_modified, &mut file.last_file_modified);
+    }
+
+    /// Show the buffer `ind`
+    pub fn switch_buffer(&mut self, ind: usize) {
+        if ind == self.active_buffer || ind >= self.buffers.len() {
+            return;
+        }
+        self.swap_buffer(self.active_buffer);
+        self.active_buffer = ind;
+        self.swap_buffer(ind);
+        self.completion = None;
+        self.snippet = None;
+        self.lsp_open_current();
+    }
+
+    /// Path and unsaved changes of every buffer in the order of `:ls`
+    fn buffer_list(&self) -> impl Iterator<Item = (usize, &str, bool)> {
+        self.buffers.iter().enumerate().map(|(ind, file)| {
+            if ind == self.active_buffer {
+                (ind, self.file_path.as_str(), self.modified)
+            } else {
+                (ind, file.path.as_str(), file.modified)
+            }
+        })
+    }
+
+    /// A buffer with changes that would be lost when quitting
+    pub fn unsaved_buffer(&self) -> Option<&str> {
+        self.buffer_list()
+            .find(|&(_, _, modified)| modified)
+            .map(|(_, path, _)| path)
+    }
+
+    /// `:e <path>`: open a file, or show its buffer if it is already open
+    pub fn open_file(&mut self, path: &str) {
+        let open = self
+            .buffer_list()
+            .find(|(_, open_path, _)| same_file(open_path, path))
+            .map(|(ind, _, _)| ind);
+        if let Some(ind) = open {
+            self.switch_buffer(ind);
+            return;
+        }
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.output = format!("{} Failed to open {path}: {e}", theme::icons::ERROR);
+                return;
+            }
+        };
+        self.buffers.push(OpenFile {
+            path: path.to_string(),
+            editor: TextEditor::new(&content),
+            modified: false,
+            last_file_modified: Self::get_file_modified_time(path),
+        });
+        self.switch_buffer(self.buffers.len() - 1);
+    }
+
+    /// `:ls`: list the buffers in the output panel
+    pub fn list_buffers(&mut self) {
+        let list: Vec<String> = self
+            .buffer_list()
+            .map(|(ind, path, modified)| {
+                let shown = if ind == self.active_buffer { '%' } else { ' ' };
+                let modified = if modified { '+' } else { ' ' };
+                let exercise = if ind == EXERCISE_BUFFER {
+                    "  (exercise)"
+                } else {
+                    ""
+                };
+                format!("{:>3} {shown}{modified} {path}{exercise}", ind + 1)
+            })
+            .collect();
+        self.output = format!("{} Buffers:\n{}", theme::icons::INFO, list.join("\n"));
+        self.output_scroll = 0;
+    }
+
+    /// `:bd`: close the shown buffer and go back to the exercise
+    pub fn close_buffer(&mut self, force: bool) {
+        if self.active_buffer == EXERCISE_BUFFER {
+            self.output = format!(
+                "{} The exercise can't be closed, use ]/[ to switch exercises",
+                theme::icons::ERROR
+            );
+            return;
+        }
+        if self.modified && !force {
+            self.output = format!("{} Unsaved changes! Use :w or :bd!", theme::icons::ERROR);
+            return;
+        }
+
+        let closed = self.active_buffer;
+        self.switch_buffer(EXERCISE_BUFFER);
+        self.buffers.remove(closed);
+    }
+
+    /// Complete the path after `:e ` in the command line with Tab.
+    /// Several candidates are completed to their common prefix and listed in the output panel.
+    pub fn complete_command_path(&mut self) {
+        let Some(prefix) = self
+            .command_buffer
+            .strip_prefix("e ")
+            .or_else(|| self.command_buffer.strip_prefix("edit "))
+        else {
+            return;
+        };
+        let command_len = self.command_buffer.len() - prefix.len();
+
+        let candidates = complete_path(prefix.trim_start());
+        let Some(first) = candidates.first() else {
+            return;
+        };
+        let common_len = candidates
+            .iter()
+            .skip(1)
+            .fold(first.chars().count(), |len, path| {
+                first
+                    .chars()
+                    .zip(path.chars())
+                    .take(len)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+        let common: String = first.chars().take(common_len).collect();
+
+        self.command_buffer.truncate(command_len);
+        self.command_buffer.push_str(&common);
+        if candidates.len() > 1 {
+            self.output = candidates.join("  ");
+            self.output_scroll = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_paths() {
+        assert_eq!(complete_path("src/ma"), ["src/main.rs"]);
+        assert_eq!(complete_path("sr"), ["src/"]);
+        assert!(complete_path("src/ui/").contains(&"src/ui/buffers.rs".to_string()));
+        assert!(complete_path("does-not-exist/").is_empty());
+    }
+}
@@ -2,11 +2,45 @@
 
 use anyhow::Result;
 
-use crate::ui::{state::TuiState, theme};
+use crate::ui::{
+    state::TuiState,
+    theme,
+    undo::{self, TimeTravel},
+};
 
 impl TuiState<'_> {
     /// Execute a vim-style command
     pub fn execute_command(&mut self, cmd: &str) -> Result<Option<bool>> {
+        // commands with an argument
+        if let Some((name, arg)) = cmd.trim().split_once(' ') {
+            return match name {
+                "earlier" => self.cmd_time_travel(arg, false),
+                "later" => self.cmd_time_travel(arg, true),
+                "e" | "edit" => {
+                    self.open_file(arg.trim());
+                    Ok(Some(false))
+                }
+                "b" | "buffer" => self.cmd_buffer(arg),
+                "grep" => {
+                    self.grep(arg);
+                    Ok(Some(false))
+                }
+                "cc" => {
+                    self.cmd_jump_to_match(arg)?;
+                    Ok(Some(false))
+                }
+                "concept" => {
+                    self.show_concept(arg);
+                    Ok(Some(false))
+                }
+                "doc" => {
+                    self.show_std_doc(arg);
+                    Ok(Some(false))
+                }
+                _ => self.cmd_unknown(cmd),
+            };
+        }
+
         match cmd.trim() {
             "w" => {
                 self.save()?;
@@ -35,29 +69,128 @@ impl TuiState<'_> {
                 self.prev_exercise()?;
                 Ok(Some(false))
             }
+            "fmt" => {
+                self.format_buffer();
+                Ok(Some(false))
+            }
+            "fmtonsave" => self.cmd_toggle_format_on_save(),
+            "lsp" => self.cmd_toggle_lsp(),
+            "wrap" => self.cmd_toggle_wrap(),
             "auto" => self.cmd_toggle_auto(),
             "watch" => self.cmd_toggle_watch(),
             "r" | "reload" => self.cmd_reload(),
             "reset" => self.cmd_reset(),
+            "e" | "edit" => {
+                self.output = format!("{} Usage: :e <path>", theme::icons::ERROR);
+                Ok(Some(false))
+            }
+            "ls" | "buffers" => {
+                self.list_buffers();
+                Ok(Some(false))
+            }
+            "bd" | "bd!" => {
+                self.close_buffer(cmd.trim() == "bd!");
+                Ok(Some(false))
+            }
+            "grep" => {
+                self.grep("");
+                Ok(Some(false))
+            }
+            "cc" => {
+                self.cmd_jump_to_match("")?;
+                Ok(Some(false))
+            }
+            "concept" | "concepts" => {
+                self.show_concept("");
+                Ok(Some(false))
+            }
+            "doc" => {
+                self.show_std_doc("");
+                Ok(Some(false))
+            }
+            "cn" | "cnext" => {
+                self.jump_to_next_match(true)?;
+                Ok(Some(false))
+            }
+            "cp" | "cprevious" => {
+                self.jump_to_next_match(false)?;
+                Ok(Some(false))
+            }
+            "earlier" => self.cmd_time_travel("", false),
+            "later" => self.cmd_time_travel("", true),
             "help" => {
                 self.view_mode = crate::ui::state::ViewMode::HelpModal;
                 Ok(Some(false))
             }
-            _ => {
+            _ => self.cmd_unknown(cmd),
+        }
+    }
+
+    fn cmd_unknown(&mut self, cmd: &str) -> Result<Option<bool>> {
+        self.output = format!(
+            "{} Unknown command: {} (try :help)",
+            theme::icons::ERROR,
+            cmd
+        );
+        Ok(Some(false))
+    }
+
+    /// `:earlier`/`:later` with a count of changes or a duration (`30s`, `2m`, `1h`, `1d`)
+    fn cmd_time_travel(&mut self, arg: &str, forward: bool) -> Result<Option<bool>> {
+        let moved = match undo::parse_time_travel(arg) {
+            Some(TimeTravel::Steps(steps)) => {
+                // more steps than changes just go to the oldest or newest one
+                let steps = isize::try_from(steps).unwrap_or(isize::MAX);
+                self.editor
+                    .undo_chronological(if forward { steps } else { -steps })
+            }
+            Some(TimeTravel::Seconds(seconds)) => {
+                let seconds = i64::try_from(seconds).unwrap_or(i64::MAX);
+                self.editor
+                    .undo_seconds(if forward { seconds } else { -seconds })
+            }
+            None => {
                 self.output = format!(
-                    "{} Unknown command: {} (try :help)",
-                    theme::icons::ERROR,
-                    cmd
+                    "{} Invalid argument: {arg} (e.g. 3, 30s, 2m, 1h)",
+                    theme::icons::ERROR
                 );
-                Ok(Some(false))
+                return Ok(Some(false));
             }
+        };
+
+        if moved {
+            self.modified = true;
+        } else {
+            let end = if forward { "newest" } else { "oldest" };
+            self.output = format!("{} Already at the {end} change", theme::icons::INFO);
         }
+        Ok(Some(false))
+    }
+
+    /// `:b N`: show the buffer numbered N in `:ls`
+    fn cmd_buffer(&mut self, arg: &str) -> Result<Option<bool>> {
+        match arg.trim().parse::<usize>() {
+            Ok(num) if (1..=self.buffers.len()).contains(&num) => self.switch_buffer(num - 1),
+            _ => {
+                self.output = format!("{} No buffer {arg} (:ls lists them)", theme::icons::ERROR);
+            }
+        }
+        Ok(Some(false))
     }
 
     fn cmd_quit(&mut self, force: bool) -> Result<Option<bool>> {
-        if !force && self.modified {
+        if force {
+            return Ok(Some(true));
+        }
+        if self.modified {
             self.output = format!("{} Unsaved changes! Use :q! or :wq", theme::icons::ERROR);
             Ok(Some(false))
+        } else if let Some(path) = self.unsaved_buffer() {
+            self.output = format!(
+                "{} Unsaved changes in {path}! Use :q! or :ls to find it",
+                theme::icons::ERROR
+            );
+            Ok(Some(false))
         } else {
             Ok(Some(true))
         }
@@ -91,6 +224,41 @@ impl TuiState<'_> {
         Ok(Some(false))
     }
 
+    fn cmd_toggle_format_on_save(&mut self) -> Result<Option<bool>> {
+        self.config.format_on_save = !self.config.format_on_save;
+        self.config.write()?;
+        let status = if self.config.format_on_save {
+            "ON"
+        } else {
+            "OFF"
+        };
+        self.output = format!("{} Format on save: {}", theme::icons::DONE, status);
+        Ok(Some(false))
+    }
+
+    fn cmd_toggle_wrap(&mut self) -> Result<Option<bool>> {
+        self.config.soft_wrap = !self.config.soft_wrap;
+        self.config.write()?;
+        let status = if self.config.soft_wrap { "ON" } else { "OFF" };
+        self.output = format!("{} Soft wrap: {}", theme::icons::DONE, status);
+        Ok(Some(false))
+    }
+
+    fn cmd_toggle_lsp(&mut self) -> Result<Option<bool>> {
+        // a client that stopped after an error is restarted instead of disabled
+        self.config.rust_analyzer = !self.config.rust_analyzer || self.lsp.is_none();
+        self.config.write()?;
+        self.lsp = None;
+        self.completion = None;
+        if self.config.rust_analyzer {
+            self.output = format!("{} rust-analyzer: ON", theme::icons::DONE);
+            self.start_lsp();
+        } else {
+            self.output = format!("{} rust-analyzer: OFF", theme::icons::DONE);
+        }
+        Ok(Some(false))
+    }
+
     fn cmd_reload(&mut self) -> Result<Option<bool>> {
         self.reload_exercise()?;
         self.output = format!("{} Exercise reloaded from disk", theme::icons::INFO);
@@ -0,0 +1,219 @@
+//! Completion popup state for insert mode and completion without rust-analyzer
+
+use std::collections::HashSet;
+
+use crate::ui::{
+    buffer::Buffer,
+    lexer::{self, KEYWORDS, PRIMITIVES},
+    state::TuiState,
+};
+
+// prelude types and the constructors beginners use the most
+const STD_ITEMS: &[&str] = &[
+    "String",
+    "String::new",
+    "String::from",
+    "Vec",
+    "Vec::new",
+    "Vec::with_capacity",
+    "Option",
+    "Some",
+    "None",
+    "Result",
+    "Ok",
+    "Err",
+    "Box",
+    "Box::new",
+    "HashMap",
+    "HashMap::new",
+    "HashSet",
+    "HashSet::new",
+    "Iterator",
+    "IntoIterator",
+    "Default",
+    "Clone",
+    "Copy",
+    "Debug",
+    "Display",
+    "PartialEq",
+    "From",
+    "Into",
+    "ToString",
+    "AsRef",
+];
+
+const MACROS: &[&str] = &[
+    "println!",
+    "print!",
+    "eprintln!",
+    "format!",
+    "vec!",
+    "panic!",
+    "assert!",
+    "assert_eq!",
+    "assert_ne!",
+    "todo!",
+    "unimplemented!",
+    "unreachable!",
+    "write!",
+    "writeln!",
+    "matches!",
+    "dbg!",
+];
+
+// words shorter than this aren't worth completing
+const MIN_WORD_LEN: usize = 3;
+
+/// Chars of a word completed without rust-analyzer, including paths like `String::from`
+pub fn is_path_char(c: char) -> bool {
+    lexer::is_ident_char(c) || c == ':'
+}
+
+pub struct CompletionItem {
+    pub label: String,
+    /// Shown dimmed next to the label, e.g. a function signature
+    pub detail: Option<String>,
+    /// Text replacing the word before the cursor
+    pub insert: String,
+}
+
+/// Candidates offered for the word left of the cursor.
+/// Typing narrows them down to those starting with the typed prefix.
+pub struct CompletionMenu {
+    items: Vec<CompletionItem>,
+    // indices into `items` matching the prefix
+    matches: Vec<usize>,
+    selected: usize,
+    /// Column where the completed word starts
+    pub start_col: usize,
+}
+
+impl CompletionMenu {
+    /// `None` if no item matches `prefix`
+    pub fn new(items: Vec<CompletionItem>, start_col: usize, prefix: &str) -> Option<Self> {
+        let mut menu = Self {
+            items,
+            matches: Vec::new(),
+            selected: 0,
+            start_col,
+        };
+        menu.filter(prefix).then_some(menu)
+    }
+
+    /// Keep the items starting with `prefix` (ignoring case).
+    /// Returns `false` if none is left.
+    pub fn filter(&mut self, prefix: &str) -> bool {
+        let prefix = prefix.to_lowercase();
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.label.to_lowercase().starts_with(&prefix))
+            .map(|(ind, _)| ind)
+            .collect();
+        self.selected = 0;
+        !self.matches.is_empty()
+    }
+
+    pub fn matches(&self) -> impl ExactSizeIterator<Item = &CompletionItem> {
+        self.matches.iter().map(|&ind| &self.items[ind])
+    }
+
+    /// Index of the selected item in `matches()`
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> &CompletionItem {
+        &self.items[self.matches[self.selected]]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.matches.len();
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self
+            .selected
+            .checked_sub(1)
+            .unwrap_or(self.matches.len() - 1);
+    }
+}
+
+/// Candidates for `prefix` without rust-analyzer: words of the buffer, nearest below the
+/// cursor first like Vim's Ctrl+N, then macros, std items and keywords
+pub fn local_items(buffer: &Buffer, cursor_row: usize, prefix: &str) -> Vec<CompletionItem> {
+    let rows = (cursor_row..buffer.len_lines()).chain(0..cursor_row);
+    let mut words = Vec::new();
+    for row in rows {
+        let line = buffer.line(row);
+        words.extend(
+            line.split(|c: char| !lexer::is_ident_char(c))
+                .filter(|word| word.len() >= MIN_WORD_LEN && !word.starts_with(char::is_numeric))
+                .map(|word| (word.to_string(), "buffer")),
+        );
+    }
+
+    let builtin = [
+        (MACROS, "macro"),
+        (STD_ITEMS, "std"),
+        (PRIMITIVES, "type"),
+        (KEYWORDS, "keyword"),
+    ]
+    .into_iter()
+    .flat_map(|(list, detail)| list.iter().map(move |word| (word.to_string(), detail)));
+
+    // the word being typed is in the buffer too
+    let mut seen = HashSet::from([prefix.to_string()]);
+    words
+        .into_iter()
+        .chain(builtin)
+        .filter(|(word, _)| seen.insert(word.clone()))
+        .map(|(word, detail)| CompletionItem {
+            label: word.clone(),
+            detail: Some(detail.to_string()),
+            insert: word,
+        })
+        .collect()
+}
+
+impl TuiState<'_> {
+    /// Ctrl+N/Ctrl+P in insert mode: open the popup with the first or last local candidate
+    pub fn complete_locally(&mut self, backwards: bool) {
+        let editor = &self.editor;
+        let row = editor.cursor_row;
+        let start_col = editor.word_start_before_cursor(is_path_char);
+        let prefix = editor
+            .buffer
+            .slice((row, start_col), (row, editor.cursor_col));
+
+        let items = local_items(&editor.buffer, row, &prefix);
+        self.completion = CompletionMenu::new(items, start_col, &prefix);
+        if backwards && let Some(menu) = &mut self.completion {
+            menu.select_prev();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_candidates() {
+        let buffer = Buffer::new("let total = 1;\nlet count = to");
+        let items = local_items(&buffer, 1, "to");
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+
+        // buffer words first, then builtins, without duplicates or the typed word
+        assert_eq!(&labels[..3], ["let", "count", "total"]);
+        assert!(labels.contains(&"String::from"));
+        assert!(labels.contains(&"println!"));
+        assert!(!labels.contains(&"to"));
+        assert_eq!(labels.iter().filter(|&&label| label == "let").count(), 1);
+
+        let menu = CompletionMenu::new(items, 12, "to").unwrap();
+        let matches: Vec<&str> = menu.matches().map(|item| item.label.as_str()).collect();
+        assert_eq!(matches, ["total", "todo!", "ToString"]);
+    }
+}
@@ -0,0 +1,130 @@
+//! `:concept`: the glossary definition of a concept with the exercises that practice it
+//! and the lessons that mention it. The list is numbered like the matches of `:grep`,
+//! so `:cc N` jumps to an exercise.
+
+use std::fmt::Write;
+
+use crate::ui::{
+    lesson::SLIDE_SEPARATOR,
+    search::{MatchSource, SearchMatch, matches},
+    state::TuiState,
+    theme,
+};
+
+impl TuiState<'_> {
+    /// `:concept <name>`: explain a concept and list the related exercises and lessons,
+    /// all concepts without a name
+    pub fn show_concept(&mut self, concept: &str) {
+        let concept = concept.trim();
+        self.output_scroll = 0;
+        if concept.is_empty() {
+            self.output = self.concept_list();
+            return;
+        }
+        let Some(definition) = self.glossary.definition(concept) else {
+            self.output = format!(
+                "{} Unknown concept `{concept}`, :concept lists them",
+                theme::icons::ERROR
+            );
+            return;
+        };
+
+        let mut found = Vec::new();
+        let mut list = format!(
+            "{} {concept}: {definition}\n\nExercises (:cc N jumps to one)",
+            theme::icons::SOLUTION
+        );
+        let exercises = self.app_state.exercises();
+        for (ind, exercise) in exercises.iter().enumerate() {
+            if !exercise.concepts.contains(&concept) {
+                continue;
+            }
+            let has_lesson = matches!(self.app_state.lesson(ind), Ok(Some(_)));
+            let done = if exercise.done {
+                theme::icons::DONE
+            } else {
+                " "
+            };
+            let frog = if has_lesson { "  🐸" } else { "" };
+            let _ = write!(
+                list,
+                "\n{:>4} {done} {}{frog}",
+                found.len() + 1,
+                exercise.name
+            );
+            found.push(SearchMatch {
+                exercise_ind: ind,
+                source: if has_lesson {
+                    MatchSource::Lesson { slide: 0 }
+                } else {
+                    MatchSource::Exercise { row: 0 }
+                },
+                line: String::new(),
+            });
+        }
+
+        // the first slide of other lessons mentioning the concept like `pattern matching`
+        let term = concept.replace('_', " ");
+        let mut mentioned = false;
+        for (ind, exercise) in exercises.iter().enumerate() {
+            if exercise.concepts.contains(&concept) {
+                continue;
+            }
+            let Ok(Some(lesson)) = self.app_state.lesson(ind) else {
+                continue;
+            };
+            let Some(slide) = lesson
+                .split(SLIDE_SEPARATOR)
+                .position(|text| matches(text, &term))
+            else {
+                continue;
+            };
+
+            if !mentioned {
+                mentioned = true;
+                list.push_str("\n\nLessons that mention it");
+            }
+            let _ = write!(
+                list,
+                "\n{:>4}   {}  🐸 slide {}",
+                found.len() + 1,
+                exercise.name,
+                slide + 1
+            );
+            found.push(SearchMatch {
+                exercise_ind: ind,
+                source: MatchSource::Lesson { slide },
+                line: String::new(),
+            });
+        }
+
+        self.grep_matches = found;
+        self.grep_match = None;
+        self.output = list;
+    }
+
+    // the concepts of the glossary with the number of exercises practicing them
+    fn concept_list(&self) -> String {
+        let concepts: Vec<String> = self
+            .glossary
+            .concepts()
+            .map(|concept| {
+                let count = self
+                    .app_state
+                    .exercises()
+                    .iter()
+                    .filter(|exercise| exercise.concepts.contains(&concept))
+                    .count();
+                format!("{concept} ({count})")
+            })
+            .collect();
+        if concepts.is_empty() {
+            return format!("{} The glossary is empty", theme::icons::INFO);
+        }
+        format!(
+            "{} Concepts (:concept <name> explains one):\n{}",
+            theme::icons::INFO,
+            concepts.join("  ")
+        )
+    }
+}
@@ -0,0 +1,69 @@
+//! Persistent TUI settings
+
+use std::{collections::BTreeMap, fs};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+const CONFIG_FILE_NAME: &str = ".rustlings-tui.toml";
+
+/// User settings stored in `.rustlings-tui.toml` next to the state file.
+/// Missing fields fall back to their defaults.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Run `rustfmt` on the buffer before writing it to disk.
+    pub format_on_save: bool,
+    /// Start rust-analyzer for hover, completion and live diagnostics.
+    /// Off until `:lsp` turns it on since it needs to be installed.
+    pub rust_analyzer: bool,
+    /// Wrap long lines in the editor instead of scrolling sideways.
+    pub soft_wrap: bool,
+    /// Sizes and arrangement of the panels.
+    pub layout: LayoutConfig,
+    /// Snippets expanded with Tab in insert mode, by trigger word.
+    /// Bodies mark tab-stops with `$1`, `${1:placeholder}` and the final cursor with `$0`.
+    pub snippets: BTreeMap<String, String>,
+}
+
+/// Panel sizes changed with `Ctrl+W` or by dragging the borders.
+#[derive(Deserialize, Serialize, Clone, Copy)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Share of the solution or Frog panel in percent,
+    /// of the width or of the height if the panels are stacked.
+    pub side_percent: u16,
+    /// Rows of the output panel including the progress and status bar.
+    pub output_height: u16,
+    /// Show the side panel left of the editor, or above it if stacked.
+    pub swap_sides: bool,
+    /// Stack the editor and the side panel. Narrow terminals always do.
+    pub stacked: bool,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            side_percent: 50,
+            output_height: 10,
+            swap_sides: false,
+            stacked: false,
+        }
+    }
+}
+
+impl TuiConfig {
+    /// Load the config file. A missing or invalid file results in the default config.
+    pub fn load() -> Self {
+        fs::read_to_string(CONFIG_FILE_NAME)
+            .ok()
+            .and_then(|content| toml::de::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let content = toml::ser::to_string(self).context("Failed to serialize the TUI config")?;
+        fs::write(CONFIG_FILE_NAME, content)
+            .with_context(|| format!("Failed to write the file {CONFIG_FILE_NAME}"))
+    }
+}
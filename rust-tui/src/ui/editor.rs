@@ -1,12 +1,19 @@
-// don't blow up memory with infinite undo
-const MAX_UNDO_HISTORY: usize = 100;
-
-#[derive(Clone)]
-struct EditorSnapshot {
-    lines: Vec<String>,
-    cursor_row: usize,
-    cursor_col: usize,
-}
+use std::ops::RangeInclusive;
+
+use anyhow::Result;
+use ropey::Rope;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::readonly;
+use crate::ui::{
+    buffer::{Buffer, display_width, is_blank, wrapped_pos},
+    fold::Folds,
+    indent,
+    undo::{self, UndoTree},
+};
+
+// one level of indentation (matches what Tab inserts)
+pub const INDENT: &str = "    ";
 
 // encapsulates bracket matching state
 struct BracketMatch {
@@ -57,6 +64,13 @@ impl BracketMatch {
     }
 }
 
+// one char per grapheme column (its base char) for column-based scanning
+fn column_chars(line: &str) -> Vec<char> {
+    line.graphemes(true)
+        .map(|g| g.chars().next().unwrap_or(' '))
+        .collect()
+}
+
 // expand word boundaries to include surrounding whitespace (for daw)
 fn expand_whitespace(chars: &[char], mut start: usize, mut end: usize) -> (usize, usize) {
     let orig_end = end;
@@ -77,94 +91,263 @@ fn expand_whitespace(chars: &[char], mut start: usize, mut end: usize) -> (usize
 }
 
 pub struct TextEditor {
-    pub lines: Vec<String>,
+    pub buffer: Buffer,
     pub cursor_row: usize,
+    /// Column in graphemes, not chars or bytes
     pub cursor_col: usize,
     pub scroll_offset: usize,
-    undo_stack: Vec<EditorSnapshot>,
-    redo_stack: Vec<EditorSnapshot>,
+    /// Cells scrolled out to the left when lines aren't wrapped
+    pub h_scroll: usize,
+    // width the last view wrapped lines at
+    wrap_width: Option<usize>,
+    pub folds: Folds,
+    /// Text of the read-only regions after the last load or accepted edit,
+    /// `None` if the markers aren't balanced
+    pub readonly_snapshot: Option<Vec<String>>,
+    history: UndoTree,
+    // buffer at the current node of the history, edits since then aren't committed yet.
+    // Cloning a rope is cheap since clones share unchanged chunks.
+    checkpoint: Rope,
+    // cursor before the uncommitted edits
+    pending_cursor: (usize, usize),
+    // when the uncommitted edits were last changed, the time of their undo step
+    pending_time: u64,
+}
+
+fn readonly_text(buffer: &Buffer) -> Option<Vec<String>> {
+    let regions = readonly::regions(buffer.lines()).ok()?;
+    let texts = regions
+        .into_iter()
+        .map(|rows| buffer.slice((*rows.start(), 0), (*rows.end(), usize::MAX)))
+        .collect();
+    Some(texts)
 }
 
 impl TextEditor {
     pub fn new(content: &str) -> Self {
-        let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-        Self {
-            lines: if lines.is_empty() {
-                vec![String::new()]
-            } else {
-                lines
-            },
+        let buffer = Buffer::new(content);
+        let mut folds = Folds::default();
+        folds.close_test_modules(&buffer);
+        let mut editor = Self {
+            checkpoint: buffer.rope().clone(),
+            buffer,
             cursor_row: 0,
             cursor_col: 0,
             scroll_offset: 0,
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-        }
+            h_scroll: 0,
+            wrap_width: None,
+            folds,
+            readonly_snapshot: None,
+            history: UndoTree::default(),
+            pending_cursor: (0, 0),
+            pending_time: undo::now(),
+        };
+        editor.readonly_snapshot = editor.readonly_text();
+        editor
     }
 
-    pub fn content(&self) -> String {
-        self.lines.join("\n")
+    /// Continue the saved undo history of an exercise.
+    /// If the file changed since the history was saved, the difference becomes a new change.
+    pub fn load_history(&mut self, exercise_name: &str) {
+        let Some((history, text)) = undo::load_history(exercise_name) else {
+            return;
+        };
+        self.history = history;
+        self.checkpoint = text;
+        self.pending_cursor = (0, 0);
+        self.pending_time = undo::now();
+        self.commit_pending();
+    }
+
+    /// Persist the undo history of an exercise
+    pub fn write_history(&mut self, exercise_name: &str) -> Result<()> {
+        self.commit_pending();
+        undo::write_history(exercise_name, &self.history, &self.checkpoint)
     }
 
-    fn create_snapshot(&self) -> EditorSnapshot {
-        EditorSnapshot {
-            lines: self.lines.clone(),
-            cursor_row: self.cursor_row,
-            cursor_col: self.cursor_col,
+    pub fn content(&self) -> String {
+        self.buffer.to_string()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.buffer.len_lines()
+    }
+
+    /// Rows of the regions between `// rustlings:readonly-begin/end` markers.
+    /// Nothing is read-only if the markers aren't balanced.
+    pub fn readonly_regions(&self) -> Vec<RangeInclusive<usize>> {
+        readonly::regions(self.buffer.lines()).unwrap_or_default()
+    }
+
+    /// Text of the read-only regions, `None` if the markers aren't balanced
+    pub fn readonly_text(&self) -> Option<Vec<String>> {
+        readonly_text(&self.buffer)
+    }
+
+    /// Replace the whole buffer, keeping the cursor on the same code character.
+    /// Used after formatting where only whitespace changes.
+    /// Returns `false` and keeps the buffer if a read-only region would change.
+    pub fn replace_content(&mut self, content: &str) -> bool {
+        let buffer = Buffer::new(content);
+        if self.readonly_snapshot.is_some() && readonly_text(&buffer) != self.readonly_snapshot {
+            return false;
+        }
+
+        // count non-whitespace graphemes before the cursor, then find the same count in the new text
+        let before_cursor = self
+            .buffer
+            .lines()
+            .take(self.cursor_row)
+            .map(|line| line.graphemes(true).filter(|g| !is_blank(g)).count())
+            .sum::<usize>()
+            + self
+                .buffer
+                .line(self.cursor_row)
+                .graphemes(true)
+                .take(self.cursor_col)
+                .filter(|g| !is_blank(g))
+                .count();
+
+        self.buffer = buffer;
+
+        let mut remaining = before_cursor;
+        let mut target = None;
+        'rows: for (row, line) in self.buffer.lines().enumerate() {
+            for (col, g) in line.graphemes(true).enumerate() {
+                if is_blank(g) {
+                    continue;
+                }
+                if remaining == 0 {
+                    target = Some((row, col));
+                    break 'rows;
+                }
+                remaining -= 1;
+            }
+        }
+
+        if let Some((row, col)) = target {
+            self.cursor_row = row;
+            self.cursor_col = col;
+        } else {
+            self.goto_last_line();
+            self.move_to_line_end();
         }
+        true
+    }
+
+    /// Replace the buffer with a file changed on disk as one undo step.
+    /// Its read-only regions become the ones to keep.
+    pub fn reload(&mut self, content: &str) {
+        self.save_snapshot();
+        self.buffer = Buffer::new(content);
+        self.readonly_snapshot = self.readonly_text();
+        self.mark_edited();
+        self.cursor_row = self.cursor_row.min(self.line_count() - 1);
+        self.clamp_col();
     }
 
-    fn restore_snapshot(&mut self, snapshot: EditorSnapshot) {
-        self.lines = snapshot.lines;
-        self.cursor_row = snapshot.cursor_row;
-        self.cursor_col = snapshot.cursor_col;
+    // record the edits since the last checkpoint as one change
+    fn commit_pending(&mut self) {
+        self.history.commit(
+            &self.checkpoint,
+            self.buffer.rope(),
+            self.pending_cursor,
+            self.pending_time,
+        );
+        self.checkpoint = self.buffer.rope().clone();
     }
 
+    /// Mark the start of an edit. Everything edited since the previous call becomes one undo step.
     pub fn save_snapshot(&mut self) {
-        let snapshot = self.create_snapshot();
-        self.undo_stack.push(snapshot);
+        self.commit_pending();
+        self.pending_cursor = (self.cursor_row, self.cursor_col);
+    }
 
-        if self.undo_stack.len() > MAX_UNDO_HISTORY {
-            self.undo_stack.remove(0);
-        }
+    /// Stamp the uncommitted edits with the current time.
+    /// They are only committed with the next edit or undo, `:earlier 2m` needs the time they were made.
+    pub fn mark_edited(&mut self) {
+        self.pending_time = undo::now();
+    }
 
-        self.redo_stack.clear();
+    // sync the buffer with the checkpoint after moving in the history
+    fn restore_checkpoint(&mut self, cursor: Option<(usize, usize)>) -> bool {
+        let Some((row, col)) = cursor else {
+            return false;
+        };
+        self.buffer = Buffer::from_rope(self.checkpoint.clone());
+        self.cursor_row = row.min(self.line_count() - 1);
+        self.cursor_col = col;
+        self.clamp_col();
+        self.pending_cursor = (self.cursor_row, self.cursor_col);
+        true
     }
 
     pub fn undo(&mut self) -> bool {
-        if let Some(snapshot) = self.undo_stack.pop() {
-            self.redo_stack.push(self.create_snapshot());
-            self.restore_snapshot(snapshot);
-            true
-        } else {
-            false
-        }
+        self.commit_pending();
+        let cursor = self.history.undo(&mut self.checkpoint);
+        self.restore_checkpoint(cursor)
     }
 
     pub fn redo(&mut self) -> bool {
-        if let Some(snapshot) = self.redo_stack.pop() {
-            self.undo_stack.push(self.create_snapshot());
-            self.restore_snapshot(snapshot);
-            true
-        } else {
-            false
-        }
+        self.commit_pending();
+        let cursor = self.history.redo(&mut self.checkpoint);
+        self.restore_checkpoint(cursor)
+    }
+
+    /// `g-`/`g+`: go back or forward in time through all branches
+    pub fn undo_chronological(&mut self, steps: isize) -> bool {
+        self.commit_pending();
+        let cursor = self.history.step_chronological(steps, &mut self.checkpoint);
+        self.restore_checkpoint(cursor)
+    }
+
+    /// `:earlier 2m`/`:later 2m`: go to the buffer as it was `seconds` from the current state
+    pub fn undo_seconds(&mut self, seconds: i64) -> bool {
+        self.commit_pending();
+        let cursor = self.history.step_seconds(seconds, &mut self.checkpoint);
+        self.restore_checkpoint(cursor)
     }
 
     pub fn move_up(&mut self) {
         if self.cursor_row > 0 {
-            self.cursor_row -= 1;
+            self.cursor_row = self.folds.shown_row(self.cursor_row - 1);
             self.clamp_col();
         }
     }
 
     pub fn move_down(&mut self) {
-        if self.cursor_row < self.lines.len().saturating_sub(1) {
-            self.cursor_row += 1;
+        let next = self.next_shown_row(self.cursor_row);
+        if next < self.line_count() {
+            self.cursor_row = next;
             self.clamp_col();
         }
     }
 
+    // -- folds --
+
+    /// `za`: toggle the fold at the cursor
+    pub fn toggle_fold(&mut self) {
+        if let Some(open) = self.folds.toggle(&self.buffer, self.cursor_row) {
+            self.cursor_row = open;
+            self.clamp_col();
+        }
+    }
+
+    /// `zc`: close the innermost block around the cursor
+    pub fn close_fold(&mut self) {
+        if let Some(open) = self.folds.close(&self.buffer, self.cursor_row) {
+            self.cursor_row = open;
+            self.clamp_col();
+        }
+    }
+
+    /// `zM`: close every block
+    pub fn close_all_folds(&mut self) {
+        self.folds.close_all(&self.buffer);
+        self.cursor_row = self.folds.shown_row(self.cursor_row);
+        self.clamp_col();
+    }
+
     pub fn move_left(&mut self) {
         if self.cursor_col > 0 {
             self.cursor_col -= 1;
@@ -177,84 +360,222 @@ impl TextEditor {
     pub fn move_right(&mut self) {
         if self.cursor_col < self.current_line_len() {
             self.cursor_col += 1;
-        } else if self.cursor_row < self.lines.len().saturating_sub(1) {
+        } else if self.cursor_row < self.line_count() - 1 {
             self.cursor_row += 1;
             self.cursor_col = 0;
         }
     }
 
     pub fn insert_char(&mut self, c: char) {
-        if self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let byte_idx = Self::char_to_byte_idx(line, self.cursor_col);
-            line.insert(byte_idx, c);
-            self.cursor_col += 1;
+        self.insert_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Insert text without line breaks at the cursor and move the cursor after it
+    pub fn insert_str(&mut self, text: &str) {
+        // a combining char joins the grapheme before the cursor, so measure instead of counting
+        let len = self.current_line_len();
+        self.buffer.insert((self.cursor_row, self.cursor_col), text);
+        self.cursor_col += self.current_line_len() - len;
+    }
+
+    /// Column where the run of `is_word_char` chars ending at the cursor starts
+    pub fn word_start_before_cursor(&self, is_word_char: impl Fn(char) -> bool) -> usize {
+        let chars = column_chars(&self.buffer.line(self.cursor_row));
+        let end = self.cursor_col.min(chars.len());
+        chars[..end]
+            .iter()
+            .rposition(|&c| !is_word_char(c))
+            .map_or(0, |i| i + 1)
+    }
+
+    /// The run of `is_word_char` chars around the cursor, `None` if the cursor isn't on one
+    pub fn word_at_cursor(&self, is_word_char: impl Fn(char) -> bool) -> Option<String> {
+        let chars = column_chars(&self.buffer.line(self.cursor_row));
+        if !is_word_char(*chars.get(self.cursor_col)?) {
+            return None;
         }
+        let end = chars[self.cursor_col..]
+            .iter()
+            .position(|&c| !is_word_char(c))
+            .map_or(chars.len(), |i| self.cursor_col + i);
+        let start = self.word_start_before_cursor(&is_word_char);
+        Some(chars[start..end].iter().collect())
+    }
+
+    /// Replace the text between `start_col` and the cursor, e.g. a word being completed
+    pub fn replace_before_cursor(&mut self, start_col: usize, text: &str) {
+        let row = self.cursor_row;
+        self.buffer.remove((row, start_col), (row, self.cursor_col));
+        self.cursor_col = start_col.min(self.cursor_col);
+        self.insert_str(text);
     }
 
     pub fn insert_newline(&mut self) {
-        if self.cursor_row < self.lines.len() {
-            let line = self.lines[self.cursor_row].clone();
-            let byte_idx = Self::char_to_byte_idx(&line, self.cursor_col);
-            let (before, after) = line.split_at(byte_idx);
-            self.lines[self.cursor_row] = before.to_string();
-            self.lines.insert(self.cursor_row + 1, after.to_string());
-            self.cursor_row += 1;
-            self.cursor_col = 0;
-        }
+        self.buffer.insert((self.cursor_row, self.cursor_col), "\n");
+        self.cursor_row += 1;
+        self.cursor_col = 0;
     }
 
     pub fn backspace(&mut self) {
         if self.cursor_col > 0 {
-            let line = &mut self.lines[self.cursor_row];
-            // Get byte index of the character before cursor
-            let byte_idx = Self::char_to_byte_idx(line, self.cursor_col - 1);
-            line.remove(byte_idx);
+            let (row, col) = (self.cursor_row, self.cursor_col);
+            self.buffer.remove((row, col - 1), (row, col));
             self.cursor_col -= 1;
         } else if self.cursor_row > 0 {
-            let current_line = self.lines.remove(self.cursor_row);
             self.cursor_row -= 1;
-            self.cursor_col = self.lines[self.cursor_row].chars().count();
-            self.lines[self.cursor_row].push_str(&current_line);
+            self.cursor_col = self.current_line_len();
+            self.buffer
+                .remove((self.cursor_row, self.cursor_col), (self.cursor_row + 1, 0));
         }
     }
 
     pub fn delete(&mut self) {
-        if self.cursor_col < self.current_line_len() {
-            let line = &mut self.lines[self.cursor_row];
-            let byte_idx = Self::char_to_byte_idx(line, self.cursor_col);
-            line.remove(byte_idx);
-        } else if self.cursor_row < self.lines.len() - 1 {
-            let next_line = self.lines.remove(self.cursor_row + 1);
-            self.lines[self.cursor_row].push_str(&next_line);
+        let (row, col) = (self.cursor_row, self.cursor_col);
+        if col < self.current_line_len() {
+            self.buffer.remove((row, col), (row, col + 1));
+        } else if row < self.line_count() - 1 {
+            self.buffer.remove((row, col), (row + 1, 0));
         }
     }
 
     pub fn current_line_len(&self) -> usize {
-        self.lines
-            .get(self.cursor_row)
-            .map(|l| l.chars().count())
-            .unwrap_or(0)
-    }
-
-    // char idx -> byte idx
-    fn char_to_byte_idx(s: &str, char_idx: usize) -> usize {
-        s.char_indices()
-            .nth(char_idx)
-            .map(|(i, _)| i)
-            .unwrap_or(s.len())
+        self.buffer.line_len(self.cursor_row)
     }
 
     pub fn clamp_col(&mut self) {
         self.cursor_col = self.cursor_col.min(self.current_line_len());
     }
 
-    pub fn update_scroll(&mut self, visible_height: usize) {
+    /// Scroll the view of `height` rows and `width` cells to the cursor.
+    /// With `wrap`, long lines continue on the next screen rows instead of scrolling sideways.
+    pub fn update_scroll(&mut self, height: usize, width: usize, wrap: bool) {
+        // jumps and edits into a closed fold open it
+        self.folds.sync(&self.buffer);
+        self.folds.reveal(self.cursor_row);
+        self.wrap_width = wrap.then_some(width.max(1));
+
+        self.scroll_offset = self.folds.shown_row(self.scroll_offset);
         if self.cursor_row < self.scroll_offset {
             self.scroll_offset = self.cursor_row;
-        } else if self.cursor_row >= self.scroll_offset + visible_height {
-            self.scroll_offset = self.cursor_row - visible_height + 1;
         }
+        while self.scroll_offset < self.cursor_row
+            && self
+                .screen_pos((self.cursor_row, self.cursor_col))
+                .is_some_and(|(_, y)| y >= height.max(1))
+        {
+            self.scroll_offset = self.next_shown_row(self.scroll_offset);
+        }
+
+        let cursor_x = display_width(
+            &self
+                .buffer
+                .slice((self.cursor_row, 0), (self.cursor_row, self.cursor_col)),
+        );
+        if wrap || cursor_x < self.h_scroll {
+            self.h_scroll = if wrap { 0 } else { cursor_x };
+        } else if cursor_x >= self.h_scroll + width {
+            self.h_scroll = cursor_x + 1 - width.max(1);
+        }
+    }
+
+    /// Rows not hidden by folds, from the first visible one on screen
+    pub fn shown_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(self.scroll_offset), |&row| {
+            Some(self.next_shown_row(row))
+        })
+        .take_while(|&row| row < self.line_count())
+    }
+
+    /// Screen line of `row` counted from the top of the view
+    pub fn screen_row(&self, row: usize) -> Option<usize> {
+        if row < self.scroll_offset || self.folds.is_hidden(row) {
+            return None;
+        }
+        let rows_above = self.shown_rows().take_while(|&shown| shown < row);
+        Some(rows_above.map(|shown| self.display_rows(shown)).sum())
+    }
+
+    /// Cell `(x, y)` of a position in the view, `None` if it's scrolled out to the top or left
+    pub fn screen_pos(&self, (row, col): (usize, usize)) -> Option<(usize, usize)> {
+        let y = self.screen_row(row)?;
+        let line = self.buffer.line(row);
+        match self.wrap_width {
+            Some(width) => {
+                let (wrapped_row, x) = wrapped_pos(&line, col, width);
+                Some((x, y + wrapped_row))
+            }
+            None => {
+                let before: String = line.graphemes(true).take(col).collect();
+                let x = display_width(&before).checked_sub(self.h_scroll)?;
+                Some((x, y))
+            }
+        }
+    }
+
+    /// Position under the cell `(x, y)` of the view, clamped to the text
+    pub fn pos_at_screen(&self, x: usize, y: usize) -> (usize, usize) {
+        let mut row_top = 0;
+        let mut target = self.scroll_offset;
+        for row in self.shown_rows() {
+            target = row;
+            let rows = self.display_rows(row);
+            if y < row_top + rows {
+                break;
+            }
+            row_top += rows;
+        }
+
+        // the last column starting at or before the cell
+        let cell = (y.saturating_sub(row_top), x);
+        let col = (0..=self.buffer.line_len(target))
+            .take_while(|&col| {
+                self.screen_pos((target, col))
+                    .is_none_or(|(x, y)| (y - row_top, x) <= cell)
+            })
+            .last()
+            .unwrap_or(0);
+        (target, col)
+    }
+
+    /// Scroll the view by `delta` rows like the mouse wheel, moving the cursor along
+    /// if it would leave the `height` rows of the view
+    pub fn scroll_view(&mut self, delta: isize, height: usize) {
+        for _ in 0..delta.unsigned_abs() {
+            self.scroll_offset = if delta < 0 {
+                self.folds.shown_row(self.scroll_offset.saturating_sub(1))
+            } else {
+                let next = self.next_shown_row(self.scroll_offset);
+                if next >= self.line_count() {
+                    break;
+                }
+                next
+            };
+        }
+
+        if self.cursor_row < self.scroll_offset {
+            self.cursor_row = self.scroll_offset;
+        }
+        while self.cursor_row > self.scroll_offset
+            && self.screen_row(self.cursor_row).is_none_or(|y| y >= height)
+        {
+            self.move_up();
+        }
+        self.clamp_col();
+    }
+
+    // screen rows taken by `row` when soft-wrapping
+    fn display_rows(&self, row: usize) -> usize {
+        let Some(width) = self.wrap_width else {
+            return 1;
+        };
+        let line = self.buffer.line(row);
+        let last = self.buffer.line_len(row).saturating_sub(1);
+        wrapped_pos(&line, last, width).0 + 1
+    }
+
+    // row below `row`, skipping a closed fold starting on it
+    fn next_shown_row(&self, row: usize) -> usize {
+        self.folds.hidden_after(row).end
     }
 
     // -- vim movement --
@@ -268,33 +589,33 @@ impl TextEditor {
     }
 
     pub fn move_to_first_non_whitespace(&mut self) {
-        if let Some(line) = self.lines.get(self.cursor_row) {
-            self.cursor_col = line.chars().take_while(|c| c.is_whitespace()).count();
-        }
+        self.cursor_col = self
+            .buffer
+            .line(self.cursor_row)
+            .graphemes(true)
+            .take_while(|g| is_blank(g))
+            .count();
     }
 
     pub fn move_word_forward(&mut self) {
-        if let Some(line) = self.lines.get(self.cursor_row) {
-            let chars: Vec<char> = line.chars().collect();
-            let mut col = self.cursor_col;
+        let line = self.buffer.line(self.cursor_row);
+        let mut rest = line.graphemes(true).skip(self.cursor_col).peekable();
 
-            // Skip current word (non-whitespace)
-            while col < chars.len() && !chars[col].is_whitespace() {
-                col += 1;
-            }
-            // Skip whitespace
-            while col < chars.len() && chars[col].is_whitespace() {
-                col += 1;
-            }
+        // Skip current word (non-whitespace), then whitespace
+        let mut col = self.cursor_col;
+        while rest.next_if(|g| !is_blank(g)).is_some() {
+            col += 1;
+        }
+        while rest.next_if(|g| is_blank(g)).is_some() {
+            col += 1;
+        }
 
-            if col >= chars.len() && self.cursor_row < self.lines.len() - 1 {
-                // Move to next line
-                self.cursor_row += 1;
-                self.cursor_col = 0;
-                self.move_to_first_non_whitespace();
-            } else {
-                self.cursor_col = col;
-            }
+        if rest.peek().is_none() && self.cursor_row < self.line_count() - 1 {
+            // Move to next line
+            self.cursor_row += 1;
+            self.move_to_first_non_whitespace();
+        } else {
+            self.cursor_col = col;
         }
     }
 
@@ -304,21 +625,19 @@ impl TextEditor {
             self.cursor_col = self.current_line_len();
         }
 
-        if let Some(line) = self.lines.get(self.cursor_row) {
-            let chars: Vec<char> = line.chars().collect();
-            let mut col = self.cursor_col.saturating_sub(1);
+        let chars = column_chars(&self.buffer.line(self.cursor_row));
+        let mut col = self.cursor_col.saturating_sub(1);
 
-            // Skip whitespace backwards
-            while col > 0 && chars.get(col).is_some_and(|c| c.is_whitespace()) {
-                col -= 1;
-            }
-            // Skip word backwards
-            while col > 0 && chars.get(col - 1).is_some_and(|c| !c.is_whitespace()) {
-                col -= 1;
-            }
-
-            self.cursor_col = col;
+        // Skip whitespace backwards
+        while col > 0 && chars.get(col).is_some_and(|c| c.is_whitespace()) {
+            col -= 1;
         }
+        // Skip word backwards
+        while col > 0 && chars.get(col - 1).is_some_and(|c| !c.is_whitespace()) {
+            col -= 1;
+        }
+
+        self.cursor_col = col;
     }
 
     pub fn goto_first_line(&mut self) {
@@ -327,61 +646,158 @@ impl TextEditor {
     }
 
     pub fn goto_last_line(&mut self) {
-        self.cursor_row = self.lines.len().saturating_sub(1);
+        self.cursor_row = self.line_count() - 1;
         self.clamp_col();
     }
 
     // -- vim editing --
 
     pub fn delete_line(&mut self) -> Option<String> {
-        if self.lines.len() > 1 {
-            let deleted = self.lines.remove(self.cursor_row);
-            if self.cursor_row >= self.lines.len() {
-                self.cursor_row = self.lines.len().saturating_sub(1);
-            }
-            self.clamp_col();
-            Some(deleted)
-        } else {
-            // Last line - just clear it
-            let deleted = std::mem::take(&mut self.lines[0]);
-            self.cursor_col = 0;
-            Some(deleted)
-        }
+        let deleted = self.get_current_line();
+        self.buffer.remove_lines(self.cursor_row, self.cursor_row);
+        self.cursor_row = self.cursor_row.min(self.line_count() - 1);
+        self.clamp_col();
+        Some(deleted)
     }
 
     pub fn open_line_below(&mut self) {
-        self.lines.insert(self.cursor_row + 1, String::new());
+        self.buffer.insert_lines(self.cursor_row + 1, "");
         self.cursor_row += 1;
         self.cursor_col = 0;
     }
 
     pub fn open_line_above(&mut self) {
-        self.lines.insert(self.cursor_row, String::new());
+        self.buffer.insert_lines(self.cursor_row, "");
         self.cursor_col = 0;
     }
 
-    pub fn get_current_line(&self) -> Option<&String> {
-        self.lines.get(self.cursor_row)
+    pub fn get_current_line(&self) -> String {
+        self.buffer.line(self.cursor_row).into_owned()
     }
 
-    pub fn insert_line_below(&mut self, content: String) {
-        self.lines.insert(self.cursor_row + 1, content);
+    pub fn insert_lines_below(&mut self, text: &str) {
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        self.buffer.insert_lines(self.cursor_row + 1, text);
     }
 
-    pub fn replace_char(&mut self, c: char) {
-        if let Some(line) = self.lines.get_mut(self.cursor_row) {
-            let mut chars: Vec<char> = line.chars().collect();
-            if self.cursor_col < chars.len() {
-                chars[self.cursor_col] = c;
-                *line = chars.into_iter().collect();
+    // -- line range operations (visual mode) --
+
+    pub fn indent_lines(&mut self, start: usize, end: usize) {
+        for row in start..=end.min(self.line_count() - 1) {
+            if self.buffer.line_len(row) > 0 {
+                self.buffer.insert((row, 0), INDENT);
+            }
+        }
+    }
+
+    pub fn outdent_lines(&mut self, start: usize, end: usize) {
+        for row in start..=end.min(self.line_count() - 1) {
+            let n = self
+                .buffer
+                .line(row)
+                .chars()
+                .take(INDENT.len())
+                .take_while(|&c| c == ' ')
+                .count();
+            self.buffer.remove((row, 0), (row, n));
+        }
+        self.clamp_col();
+    }
+
+    // comment all lines at their common indent, or uncomment if all are commented
+    pub fn toggle_comment(&mut self, start: usize, end: usize) {
+        let end = end.min(self.line_count() - 1);
+        let targets: Vec<(usize, String)> = (start..=end)
+            .map(|row| (row, self.buffer.line(row).into_owned()))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect();
+
+        let all_commented = targets
+            .iter()
+            .all(|(_, line)| line.trim_start().starts_with("//"));
+        let indent = targets
+            .iter()
+            .map(|(_, line)| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        for (row, line) in targets {
+            if all_commented {
+                let at = line.len() - line.trim_start().len();
+                let marker_len = if line[at..].starts_with("// ") { 3 } else { 2 };
+                self.buffer.remove((row, at), (row, at + marker_len));
+            } else {
+                self.buffer.insert((row, indent), "// ");
+            }
+        }
+        self.clamp_col();
+    }
+
+    // = operator: reindent rows by bracket depth, cursor to first non-blank like vim
+    pub fn reindent(&mut self, start: usize, end: usize) {
+        let end = end.min(self.line_count() - 1);
+        let mut lines: Vec<String> = self
+            .buffer
+            .lines()
+            .take(end + 1)
+            .map(|line| line.into_owned())
+            .collect();
+        indent::reindent(&mut lines, start, end);
+
+        for (row, line) in lines.iter().enumerate().skip(start) {
+            if self.buffer.line(row) != line.as_str() {
+                self.buffer.set_line(row, line);
             }
         }
+        self.cursor_row = start.min(end);
+        self.move_to_first_non_whitespace();
+    }
+
+    // set the leading whitespace of the cursor line, keeping the cursor on the same char
+    pub fn set_line_indent(&mut self, width: usize) {
+        let row = self.cursor_row;
+        let old = self
+            .buffer
+            .line(row)
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        self.buffer.remove((row, 0), (row, old));
+        self.buffer.insert((row, 0), &" ".repeat(width));
+        self.cursor_col = if self.cursor_col <= old {
+            width
+        } else {
+            self.cursor_col + width - old
+        };
+    }
+
+    // insert text at (row, col), padding the line with spaces if it's too short
+    pub fn insert_str_at(&mut self, row: usize, col: usize, text: &str) {
+        if row >= self.line_count() {
+            return;
+        }
+        let len = self.buffer.line_len(row);
+        if len < col {
+            self.buffer.insert((row, len), &" ".repeat(col - len));
+        }
+        self.buffer.insert((row, col), text);
+    }
+
+    pub fn replace_char(&mut self, c: char) {
+        let (row, col) = (self.cursor_row, self.cursor_col);
+        if col < self.current_line_len() {
+            self.buffer.remove((row, col), (row, col + 1));
+            self.buffer.insert((row, col), c.encode_utf8(&mut [0; 4]));
+        }
     }
 
+    /// Base char of the grapheme under the cursor
     pub fn char_at_cursor(&self) -> Option<char> {
-        self.lines
-            .get(self.cursor_row)
-            .and_then(|line| line.chars().nth(self.cursor_col))
+        self.buffer
+            .line(self.cursor_row)
+            .graphemes(true)
+            .nth(self.cursor_col)
+            .and_then(|g| g.chars().next())
     }
 
     fn find_word_boundaries(&self, chars: &[char]) -> (usize, usize) {
@@ -399,31 +815,29 @@ impl TextEditor {
         (start, end)
     }
 
-    fn delete_range(&mut self, chars: &[char], start: usize, end: usize) -> String {
-        let deleted: String = chars[start..end].iter().collect();
-        let new_line: String = chars[..start].iter().chain(chars[end..].iter()).collect();
-        self.lines[self.cursor_row] = new_line;
+    fn delete_range(&mut self, start: usize, end: usize) -> String {
+        let row = self.cursor_row;
+        let deleted = self.buffer.slice((row, start), (row, end));
+        self.buffer.remove((row, start), (row, end));
         deleted
     }
 
     // diw
     pub fn delete_inner_word(&mut self) -> Option<String> {
-        let line = self.lines.get(self.cursor_row)?;
-        let chars: Vec<char> = line.chars().collect();
+        let chars = column_chars(&self.buffer.line(self.cursor_row));
         if chars.is_empty() || self.cursor_col >= chars.len() {
             return None;
         }
 
         let (start, end) = self.find_word_boundaries(&chars);
-        let deleted = self.delete_range(&chars, start, end);
+        let deleted = self.delete_range(start, end);
         self.cursor_col = start;
         Some(deleted)
     }
 
     // daw
     pub fn delete_around_word(&mut self) -> Option<String> {
-        let line = self.lines.get(self.cursor_row)?;
-        let chars: Vec<char> = line.chars().collect();
+        let chars = column_chars(&self.buffer.line(self.cursor_row));
         if chars.is_empty() || self.cursor_col >= chars.len() {
             return None;
         }
@@ -431,8 +845,9 @@ impl TextEditor {
         let (start, end) = self.find_word_boundaries(&chars);
         let (start, end) = expand_whitespace(&chars, start, end);
 
-        let deleted = self.delete_range(&chars, start, end);
-        self.cursor_col = start.min(self.lines[self.cursor_row].len());
+        let deleted = self.delete_range(start, end);
+        self.cursor_col = start;
+        self.clamp_col();
         Some(deleted)
     }
 
@@ -462,15 +877,13 @@ impl TextEditor {
 
     fn search_bracket_forward(&self, open: char, close: char) -> Option<(usize, usize)> {
         let mut matcher = BracketMatch::new(open, close);
-        let mut row = self.cursor_row;
         let mut col = self.cursor_col + 1;
 
-        while row < self.lines.len() {
-            let chars: Vec<char> = self.lines[row].chars().collect();
+        for (row, line) in self.buffer.lines().enumerate().skip(self.cursor_row) {
+            let chars = column_chars(&line);
             if let Some(result) = matcher.scan_forward(&chars, col) {
                 return Some((row, result));
             }
-            row += 1;
             col = 0;
         }
         None
@@ -478,32 +891,21 @@ impl TextEditor {
 
     fn search_bracket_backward(&self, open: char, close: char) -> Option<(usize, usize)> {
         let mut matcher = BracketMatch::new(open, close);
-        let mut row = self.cursor_row as i32;
-        let mut col = self.cursor_col as i32 - 1;
-
-        while row >= 0 {
-            if col < 0 {
-                row -= 1;
-                col = self.get_line_end_col(row);
-                continue;
-            }
 
-            let chars: Vec<char> = self.lines[row as usize].chars().collect();
-            if let Some(result) = matcher.scan_backward(&chars, col as usize) {
-                return Some((row as usize, result));
+        for row in (0..=self.cursor_row).rev() {
+            let chars = column_chars(&self.buffer.line(row));
+            // scan left of the cursor on its line, the whole line above
+            let end = if row == self.cursor_row {
+                self.cursor_col.min(chars.len())
+            } else {
+                chars.len()
+            };
+            if let Some(start) = end.checked_sub(1)
+                && let Some(result) = matcher.scan_backward(&chars, start)
+            {
+                return Some((row, result));
             }
-
-            row -= 1;
-            col = self.get_line_end_col(row);
         }
         None
     }
-
-    fn get_line_end_col(&self, row: i32) -> i32 {
-        if row >= 0 {
-            self.lines[row as usize].chars().count() as i32 - 1
-        } else {
-            -1
-        }
-    }
 }
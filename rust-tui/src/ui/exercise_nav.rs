@@ -7,6 +7,7 @@ use anyhow::Result;
 
 use crate::exercise::RunnableExercise;
 use crate::ui::{
+    buffers::EXERCISE_BUFFER,
     editor::TextEditor,
     state::{TuiState, ViewMode},
     theme,
@@ -36,10 +37,10 @@ impl<'a> TuiState<'a> {
         if file_changed {
             self.last_file_modified = current_modified;
             let content = fs::read_to_string(&self.file_path)?;
-            self.editor = TextEditor::new(&content);
+            self.editor.reload(&content);
             self.output = format!("{} File changed externally, reloaded!", theme::icons::INFO);
 
-            if self.auto_compile_on_change {
+            if self.auto_compile_on_change && self.active_buffer == EXERCISE_BUFFER {
                 self.compile()?;
             }
             return Ok(true);
@@ -51,16 +52,39 @@ impl<'a> TuiState<'a> {
 
     /// Save current file
     pub fn save(&mut self) -> Result<()> {
+        // a failed format keeps rustfmt's error in the output and saves the buffer as is
+        let is_rust = self.file_path.ends_with(".rs");
+        if self.config.format_on_save && is_rust && !self.format_buffer() {
+            let format_error = std::mem::take(&mut self.output);
+            self.write_buffer()?;
+            self.output = format!(
+                "{format_error}\n\n{} File saved unformatted",
+                theme::icons::INFO
+            );
+            return Ok(());
+        }
+
+        self.write_buffer()?;
+        self.output = format!("{} File saved!", theme::icons::DONE);
+        Ok(())
+    }
+
+    fn write_buffer(&mut self) -> Result<()> {
         let content = self.editor.content();
         fs::write(&self.file_path, &content)?;
+        // other files only keep their history while they are open
+        if self.active_buffer == EXERCISE_BUFFER {
+            self.editor
+                .write_history(self.app_state.current_exercise().name)?;
+        }
         self.modified = false;
         self.last_file_modified = Self::get_file_modified_time(&self.file_path);
-        self.output = format!("{} File saved!", theme::icons::DONE);
         Ok(())
     }
 
-    /// Compile and run exercise
+    /// Save and run the exercise, showing its buffer
     pub fn compile(&mut self) -> Result<bool> {
+        self.switch_buffer(EXERCISE_BUFFER);
         self.save()?;
         self.output = format!(
             "{} Checking {}...",
@@ -149,6 +173,7 @@ impl<'a> TuiState<'a> {
                 Ok(Some(solution_path)) => {
                     if let Ok(content) = fs::read_to_string(&solution_path) {
                         self.solution_content = Some(content);
+                        self.solution_scroll = 0;
                         self.view_mode = ViewMode::WithSolution;
                         self.output = format!(
                             "{} Solution loaded: {}",
@@ -193,12 +218,14 @@ impl<'a> TuiState<'a> {
         Ok(())
     }
 
-    /// Reload current exercise from disk
+    /// Reload current exercise from disk into the exercise buffer
     pub fn reload_exercise(&mut self) -> Result<()> {
+        self.switch_buffer(EXERCISE_BUFFER);
         let exercise = self.app_state.current_exercise();
         self.file_path = exercise.path.to_string();
         let content = fs::read_to_string(&self.file_path)?;
         self.editor = TextEditor::new(&content);
+        self.editor.load_history(exercise.name);
         self.modified = false;
         self.solution_content = None;
         self.view_mode = ViewMode::EditorOnly;
@@ -206,7 +233,12 @@ impl<'a> TuiState<'a> {
         self.output_scroll = 0;
         self.frog_step = 0;
         self.frog_scroll = 0;
-        self.current_frog_steps = Self::load_frog_content(exercise.path, exercise.name);
+        self.current_frog_steps = Self::load_frog_content(self.app_state);
+        self.frog_runs.clear();
+        self.frog_block = None;
+        self.completion = None;
+        self.snippet = None;
+        self.lsp_open_current();
         Ok(())
     }
 }
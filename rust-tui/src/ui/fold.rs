@@ -0,0 +1,217 @@
+//! Folding of multi-line brace blocks.
+//! A closed fold keeps its opening and closing lines and hides the rows in between.
+
+use std::collections::BTreeMap;
+
+use ropey::Rope;
+
+use crate::ui::{
+    buffer::Buffer,
+    lexer::{self, TokenKind},
+};
+
+/// Rows `(open, close)` of the `{` and `}` of every block spanning more than one line,
+/// inner blocks after the blocks containing them
+pub fn blocks(buffer: &Buffer) -> Vec<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut open_rows = Vec::new();
+    let mut state = lexer::LexState::Code;
+
+    for (row, line) in buffer.lines().enumerate() {
+        let (tokens, next) = lexer::tokenize_line(&line, state);
+        state = next;
+        // braces in strings and comments don't count
+        for token in tokens.iter().filter(|token| token.kind == TokenKind::Punct) {
+            for c in line[token.range.clone()].chars() {
+                if c == '{' {
+                    open_rows.push(row);
+                } else if c == '}'
+                    && let Some(open) = open_rows.pop()
+                    && open < row
+                {
+                    blocks.push((open, row));
+                }
+            }
+        }
+    }
+
+    // blocks are found by their end, sort outer blocks first
+    blocks.sort_by_key(|&(open, close)| (open, usize::MAX - close));
+    blocks
+}
+
+/// Innermost block containing `row`
+fn innermost(blocks: &[(usize, usize)], row: usize) -> Option<(usize, usize)> {
+    blocks
+        .iter()
+        .rev()
+        .find(|(open, close)| (*open..=*close).contains(&row))
+        .copied()
+}
+
+/// Closed folds by the row of their opening line
+#[derive(Default)]
+pub struct Folds {
+    // open row -> close row
+    closed: BTreeMap<usize, usize>,
+    // the buffer the rows refer to, to follow edits
+    synced: Rope,
+}
+
+impl Folds {
+    /// Fold the `#[cfg(test)]` modules which exercises end with
+    pub fn close_test_modules(&mut self, buffer: &Buffer) {
+        self.sync(buffer);
+        for (open, close) in blocks(buffer) {
+            let is_test_module = buffer.line(open).trim_start().starts_with("mod ")
+                && (0..open)
+                    .rev()
+                    .map(|row| buffer.line(row))
+                    .find(|line| !line.trim().is_empty())
+                    .is_some_and(|line| line.contains("#[cfg(test)]"));
+            if is_test_module {
+                self.closed.insert(open, close);
+            }
+        }
+    }
+
+    /// Move the folds along with the lines inserted or removed since the last call,
+    /// dropping those that aren't a block anymore
+    pub fn sync(&mut self, buffer: &Buffer) {
+        let rope = buffer.rope();
+        if self.synced == *rope {
+            return;
+        }
+
+        // rows after the first changed line move by the difference in line count
+        let first_change = self
+            .synced
+            .lines()
+            .zip(rope.lines())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let delta = rope.len_lines() as isize - self.synced.len_lines() as isize;
+        let shifted: Vec<usize> = self
+            .closed
+            .keys()
+            .filter_map(|&open| {
+                if open <= first_change {
+                    Some(open)
+                } else {
+                    open.checked_add_signed(delta)
+                        .filter(|&row| row > first_change)
+                }
+            })
+            .collect();
+
+        let blocks = blocks(buffer);
+        self.closed = shifted
+            .into_iter()
+            .filter_map(|open| blocks.iter().find(|block| block.0 == open).copied())
+            .collect();
+        self.synced = rope.clone();
+    }
+
+    /// Rows hidden by a closed fold starting at `row` (empty if there is none)
+    pub fn hidden_after(&self, row: usize) -> std::ops::Range<usize> {
+        match self.closed.get(&row) {
+            Some(&close) => row + 1..close,
+            None => row + 1..row + 1,
+        }
+    }
+
+    /// Outermost closed fold hiding `row`
+    fn hiding(&self, row: usize) -> Option<(usize, usize)> {
+        self.closed
+            .range(..row)
+            .find(|&(&open, &close)| open < row && row < close)
+            .map(|(&open, &close)| (open, close))
+    }
+
+    pub fn is_hidden(&self, row: usize) -> bool {
+        self.hiding(row).is_some()
+    }
+
+    /// The row shown in place of `row`: itself or the opening line of the fold hiding it
+    pub fn shown_row(&self, row: usize) -> usize {
+        self.hiding(row).map_or(row, |(open, _)| open)
+    }
+
+    /// Open the folds hiding `row`, e.g. after jumping into one
+    pub fn reveal(&mut self, row: usize) {
+        self.closed
+            .retain(|&open, &mut close| !(open < row && row < close));
+    }
+
+    /// `za`: open the fold starting at `row`, or close the innermost block around it.
+    /// Returns the opening row of the closed fold.
+    pub fn toggle(&mut self, buffer: &Buffer, row: usize) -> Option<usize> {
+        self.sync(buffer);
+        if self.closed.remove(&row).is_some() {
+            return None;
+        }
+        self.close(buffer, row)
+    }
+
+    /// `zc`: close the innermost open block around `row` and return its opening row
+    pub fn close(&mut self, buffer: &Buffer, row: usize) -> Option<usize> {
+        self.sync(buffer);
+        let blocks: Vec<_> = blocks(buffer)
+            .into_iter()
+            .filter(|(open, _)| !self.closed.contains_key(open))
+            .collect();
+        let (open, close) = innermost(&blocks, row)?;
+        self.closed.insert(open, close);
+        Some(open)
+    }
+
+    /// `zo`: open the fold starting at `row`
+    pub fn open(&mut self, row: usize) {
+        self.closed.remove(&row);
+    }
+
+    /// `zM`
+    pub fn close_all(&mut self, buffer: &Buffer) {
+        self.sync(buffer);
+        self.closed = blocks(buffer).into_iter().collect();
+    }
+
+    /// `zR`
+    pub fn open_all(&mut self) {
+        self.closed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "fn main() {\n    let s = \"{\";\n}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {\n        assert!(true);\n    }\n}";
+
+    #[test]
+    fn fold_blocks_and_follow_edits() {
+        let mut buffer = Buffer::new(CODE);
+        // the brace in the string is ignored
+        assert_eq!(blocks(&buffer), [(0, 2), (5, 10), (7, 9)]);
+
+        let mut folds = Folds::default();
+        folds.close_test_modules(&buffer);
+        assert_eq!(folds.hidden_after(5), 6..10);
+        assert!(folds.is_hidden(8));
+        assert_eq!(folds.shown_row(8), 5);
+
+        // lines inserted above move the fold down
+        buffer.insert_lines(1, "    let x = 1;");
+        folds.sync(&buffer);
+        assert_eq!(folds.hidden_after(6), 7..11);
+
+        // `za` on the closing line of `main` folds it, then unfolds it again
+        assert_eq!(folds.toggle(&buffer, 3), Some(0));
+        assert!(folds.is_hidden(1));
+        assert_eq!(folds.toggle(&buffer, 0), None);
+        assert!(!folds.is_hidden(1));
+
+        folds.reveal(8);
+        assert!(!folds.is_hidden(8));
+    }
+}
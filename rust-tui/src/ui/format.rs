@@ -0,0 +1,89 @@
+//! Formatting the editor buffer with `rustfmt`
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    EDITION,
+    ui::{state::TuiState, theme},
+};
+
+/// Pipe `source` through `rustfmt`.
+/// The inner `Err` contains rustfmt's error output if the source couldn't be formatted.
+fn rustfmt(source: &str) -> Result<Result<String, String>> {
+    let mut child = Command::new("rustfmt")
+        .arg("--edition")
+        .arg(EDITION)
+        .arg("--color")
+        .arg("never")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run `rustfmt`. Is it installed? (`rustup component add rustfmt`)")?;
+
+    // Dropping stdin closes the pipe so that rustfmt starts formatting.
+    child
+        .stdin
+        .take()
+        .context("Failed to open the stdin of `rustfmt`")?
+        .write_all(source.as_bytes())
+        .context("Failed to write the buffer to `rustfmt`")?;
+
+    let output = child
+        .wait_with_output()
+        .context("Failed to wait for `rustfmt` to exit")?;
+
+    if output.status.success() {
+        Ok(Ok(String::from_utf8_lossy(&output.stdout).into_owned()))
+    } else {
+        Ok(Err(String::from_utf8_lossy(&output.stderr).into_owned()))
+    }
+}
+
+impl TuiState<'_> {
+    /// Format the buffer in place as a single undo step.
+    /// Errors are shown in the output panel and leave the buffer untouched.
+    /// Returns `false` if formatting failed.
+    pub fn format_buffer(&mut self) -> bool {
+        let content = self.editor.content();
+
+        let formatted = match rustfmt(&content) {
+            Ok(Ok(formatted)) => formatted,
+            Ok(Err(stderr)) => {
+                self.output = format!(
+                    "{} rustfmt couldn't format the file:\n\n{}",
+                    theme::icons::ERROR,
+                    stderr.trim_end()
+                );
+                return false;
+            }
+            Err(e) => {
+                self.output = format!("{} {e:#}", theme::icons::ERROR);
+                return false;
+            }
+        };
+
+        // `content()` doesn't end with a newline, rustfmt's output does
+        if formatted.trim_end_matches('\n') == content.trim_end_matches('\n') {
+            self.output = format!("{} Already formatted", theme::icons::DONE);
+            return true;
+        }
+
+        self.editor.save_snapshot();
+        if !self.editor.replace_content(&formatted) {
+            self.output = format!(
+                "{} rustfmt would change a read-only part of the exercise",
+                theme::icons::ERROR
+            );
+            return false;
+        }
+        self.modified = true;
+        self.output = format!("{} Formatted with rustfmt", theme::icons::DONE);
+        true
+    }
+}
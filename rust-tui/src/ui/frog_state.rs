@@ -1,33 +1,201 @@
 //! Frog learning panel state management
 
-use std::fs;
+use std::{
+    sync::mpsc::{self, TryRecvError},
+    thread,
+};
 
-use crate::ui::state::TuiState;
+use anyhow::Result;
+
+use crate::app_state::AppState;
+use crate::ui::{
+    lesson::{CodeRun, SLIDE_SEPARATOR, Slide, parse_slide},
+    state::{FrogRun, TuiState},
+    theme,
+};
+
+// shown in the output panel until the code ran
+const RUNNING: &str = "Running the code of the slide...";
 
 impl TuiState<'_> {
-    /// Load Frog content for an exercise from markdown files
-    pub fn load_frog_content(exercise_path: &str, exercise_name: &str) -> Vec<String> {
-        let dir = std::path::Path::new(exercise_path)
-            .parent()
-            .and_then(|p| p.file_name())
-            .and_then(|s| s.to_str())
-            .unwrap_or("");
-
-        let possible_paths = [
-            format!("rust-tui/frog/{}/{}.md", dir, exercise_name),
-            format!("frog/{}/{}.md", dir, exercise_name),
-        ];
-
-        for path in possible_paths {
-            if let Ok(content) = fs::read_to_string(&path) {
-                return content
-                    .split("--- slide ---")
-                    .map(|s| s.trim().to_string())
-                    .collect();
+    /// Load the slides of the current exercise's Frog lesson.
+    /// A lesson or quiz that can't be read shows the error instead.
+    pub fn load_frog_content(app_state: &AppState) -> Vec<Slide> {
+        let error = |e: anyhow::Error| Slide::text(format!("{} {e:#}", theme::icons::ERROR));
+        match app_state.current_lesson() {
+            Ok(Some(content)) => content
+                .split(SLIDE_SEPARATOR)
+                .map(|slide| parse_slide(slide).unwrap_or_else(error))
+                .collect(),
+            Ok(None) => Vec::new(),
+            Err(e) => vec![error(e)],
+        }
+    }
+
+    /// Answer the quiz on the current slide with the choice `choice` (0-based).
+    /// The panel scrolls down to the feedback below the choices.
+    pub fn answer_quiz(&mut self, choice: usize) -> Result<()> {
+        let Some(quiz) = self
+            .current_frog_steps
+            .get(self.frog_step)
+            .and_then(|slide| slide.quiz.as_ref())
+        else {
+            return Ok(());
+        };
+        if choice >= quiz.choices.len() || self.app_state.quiz_answer(self.frog_step).is_some() {
+            return Ok(());
+        }
+
+        let correct = choice == quiz.answer;
+        self.app_state
+            .answer_quiz(self.frog_step, choice, correct)?;
+        // clamped to the content height when rendering
+        self.frog_scroll = usize::MAX;
+        Ok(())
+    }
+
+    /// `Shift+R`: compile and run the runnable code blocks of the current slide on another thread,
+    /// `poll_frog_run` shows their output below them
+    pub fn run_frog_code(&mut self) {
+        // the runs share the scratch directory
+        if self.frog_run.is_some() {
+            self.output = format!("{} The code is still running", theme::icons::INFO);
+            return;
+        }
+        let Some(slide) = self.current_frog_steps.get(self.frog_step) else {
+            return;
+        };
+        let runnable: Vec<(usize, String)> = slide
+            .code_blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.runnable)
+            .map(|(ind, block)| (ind, block.code.clone()))
+            .collect();
+        if runnable.is_empty() {
+            self.output = format!(
+                "{} This slide has no runnable code block",
+                theme::icons::INFO
+            );
+            return;
+        }
+
+        let cmd_runner = self.app_state.cmd_runner().clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for (ind, code) in runnable {
+                let mut output = Vec::new();
+                let run = cmd_runner.run_scratch(&code, &mut output).map(|success| {
+                    // `rustc` leaves an empty line when it has nothing to say
+                    let output = String::from_utf8_lossy(&output);
+                    let output = output.trim_start_matches('\n').trim_end().to_string();
+                    CodeRun { success, output }
+                });
+                let failed = run.is_err();
+                if sender.send((ind, run)).is_err() || failed {
+                    return;
+                }
             }
+        });
+
+        self.frog_run = Some(FrogRun {
+            exercise_ind: self.app_state.current_exercise_ind(),
+            step: self.frog_step,
+            results: receiver,
+        });
+        self.output = format!("{} {RUNNING}", theme::icons::COMPILING);
+    }
+
+    /// Show the results of `run_frog_code` that arrived.
+    /// They are dropped if the user moved to another slide in the meantime.
+    pub fn poll_frog_run(&mut self) {
+        let Some(run) = &self.frog_run else {
+            return;
+        };
+        let on_slide =
+            run.exercise_ind == self.app_state.current_exercise_ind() && run.step == self.frog_step;
+
+        loop {
+            match run.results.try_recv() {
+                Ok((ind, Ok(code_run))) => {
+                    if on_slide {
+                        self.frog_runs.insert(ind, code_run);
+                    }
+                }
+                Ok((_, Err(e))) => {
+                    self.output = format!("{} {e:#}", theme::icons::ERROR);
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+
+        if on_slide && self.output.ends_with(RUNNING) {
+            self.output = format!(
+                "{} Ran the code, its output is below each block",
+                theme::icons::DONE
+            );
+        }
+        self.frog_run = None;
+    }
+
+    /// `Tab`/`Shift+Tab`: select the next or previous code block of the slide to copy it.
+    /// Going past the last or first block clears the selection.
+    pub fn select_frog_block(&mut self, forward: bool) {
+        let count = self
+            .current_frog_steps
+            .get(self.frog_step)
+            .map_or(0, |slide| slide.code_blocks.len());
+        if count == 0 {
+            return;
+        }
+        self.frog_block = match (self.frog_block, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(count - 1),
+            (Some(ind), true) => Some(ind + 1).filter(|&ind| ind < count),
+            (Some(ind), false) => ind.checked_sub(1),
+        };
+
+        // scroll to the block if it's out of view, the rows are from the last render
+        if let Some(rows) = self
+            .frog_block
+            .and_then(|ind| self.frog_block_rows.get(ind))
+            && (rows.start < self.frog_scroll
+                || rows.end > self.frog_scroll + self.frog_visible_height)
+        {
+            self.frog_scroll = rows.start.saturating_sub(1);
+        }
+    }
+
+    fn selected_frog_code(&self) -> Option<String> {
+        let slide = self.current_frog_steps.get(self.frog_step)?;
+        let block = slide.code_blocks.get(self.frog_block?)?;
+        Some(block.code.clone())
+    }
+
+    /// `Shift+Y`: yank the selected code block, `p` puts it below the cursor line
+    pub fn yank_frog_block(&mut self) {
+        if let Some(code) = self.selected_frog_code() {
+            self.yank_buffer = Some(code);
+            self.output = format!("{} Yanked the code block", theme::icons::INFO);
         }
+    }
 
-        Vec::new()
+    /// `Shift+P`: put the selected code block below the cursor line
+    pub fn put_frog_block(&mut self) {
+        if let Some(code) = self.selected_frog_code() {
+            self.modified = true;
+            self.editor.save_snapshot();
+            self.editor.insert_lines_below(&code);
+        }
+    }
+
+    // a new slide starts without the output and the selection of the previous one
+    pub fn set_frog_step(&mut self, step: usize) {
+        self.frog_step = step;
+        self.frog_scroll = 0;
+        self.frog_runs.clear();
+        self.frog_block = None;
     }
 
     /// Advance to next frog step or scroll to bottom first
@@ -45,16 +213,21 @@ impl TuiState<'_> {
         }
 
         if self.frog_step < self.current_frog_steps.len().saturating_sub(1) {
-            self.frog_step += 1;
-            self.frog_scroll = 0;
+            self.set_frog_step(self.frog_step + 1);
+        }
+    }
+
+    /// Jump to a step, e.g. when its number is clicked
+    pub fn goto_frog_step(&mut self, step: usize) {
+        if step < self.current_frog_steps.len() {
+            self.set_frog_step(step);
         }
     }
 
     /// Go to previous frog step
     pub fn prev_frog_step(&mut self) {
         if self.frog_step > 0 {
-            self.frog_step -= 1;
-            self.frog_scroll = 0;
+            self.set_frog_step(self.frog_step - 1);
         }
     }
 
@@ -20,6 +20,10 @@ pub fn handle_command_mode(key: event::KeyEvent, state: &mut TuiState) -> Result
             }
             Ok(None)
         }
+        KeyCode::Tab => {
+            state.complete_command_path();
+            Ok(None)
+        }
         KeyCode::Char(c) => {
             state.command_buffer.push(c);
             Ok(None)
@@ -1,6 +1,10 @@
+use crate::ui::completion;
+use crate::ui::handlers::visual::finish_block_insert;
+use crate::ui::indent;
 use crate::ui::state::{EditorMode, TuiState};
 use anyhow::Result;
 use crossterm::event::{self, KeyCode, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
 
 // chars that auto-pair when typed
 const AUTO_PAIR: &[(char, char)] = &[('(', ')'), ('{', '}'), ('[', ']'), ('"', '"'), ('\'', '\'')];
@@ -15,16 +19,83 @@ fn get_closing_pair(c: char) -> Option<char> {
         .map(|(_, close)| *close)
 }
 
-fn get_current_indent(state: &TuiState) -> usize {
+// text of the cursor line left of the cursor
+fn text_before_cursor(state: &TuiState) -> String {
     state
         .editor
-        .lines
-        .get(state.editor.cursor_row)
-        .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
-        .unwrap_or(0)
+        .buffer
+        .line(state.editor.cursor_row)
+        .graphemes(true)
+        .take(state.editor.cursor_col)
+        .collect()
+}
+
+// cursor sits between an auto-paired opener and its closer, e.g. `{|}`
+fn between_pair(state: &TuiState) -> bool {
+    let before = text_before_cursor(state).chars().next_back();
+    match (before, state.editor.char_at_cursor()) {
+        (Some(open), Some(close)) => {
+            open != close && matches!(get_closing_pair(open), Some(c) if c == close)
+        }
+        _ => false,
+    }
 }
 
 pub fn handle_insert_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<Option<bool>> {
+    // tab-stops of a snippet expanded before this key follow its edits
+    let in_snippet = state.snippet.is_some();
+    let len_before = state.editor.buffer.rope().len_chars();
+    let cursor_before = state
+        .editor
+        .buffer
+        .char_idx((state.editor.cursor_row, state.editor.cursor_col));
+
+    let result = if state.completion.is_some() && handle_completion_key(key, state) {
+        Ok(None)
+    } else {
+        let result = handle_snippet_key(key, state);
+        update_completion(state);
+        result
+    };
+
+    if in_snippet {
+        state.track_snippet_edit(len_before, cursor_before);
+    }
+    result
+}
+
+// typing over an untouched placeholder replaces it, Tab/Shift+Tab jump between stops
+fn handle_snippet_key(key: event::KeyEvent, state: &mut TuiState) -> Result<Option<bool>> {
+    let Some(snippet) = &mut state.snippet else {
+        return handle_edit_key(key, state);
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+        KeyCode::Tab => {
+            state.jump_snippet_stop(true);
+            Ok(None)
+        }
+        KeyCode::BackTab => {
+            state.jump_snippet_stop(false);
+            Ok(None)
+        }
+        KeyCode::Backspace | KeyCode::Delete if snippet.pristine_range().is_some() => {
+            state.clear_snippet_placeholder();
+            Ok(None)
+        }
+        KeyCode::Char(_) if !ctrl => {
+            state.clear_snippet_placeholder();
+            handle_edit_key(key, state)
+        }
+        _ => {
+            snippet.pristine = false;
+            handle_edit_key(key, state)
+        }
+    }
+}
+
+fn handle_edit_key(key: event::KeyEvent, state: &mut TuiState) -> Result<Option<bool>> {
     // ctrl+z / ctrl+shift+z for undo/redo
     if key.modifiers.contains(KeyModifiers::CONTROL) {
         return handle_ctrl_key(key.code, state);
@@ -32,6 +103,10 @@ pub fn handle_insert_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
 
     match key.code {
         KeyCode::Esc => {
+            state.snippet = None;
+            if let Some(block_insert) = state.block_insert.take() {
+                finish_block_insert(state, block_insert);
+            }
             state.mode = EditorMode::Normal;
             Ok(None)
         }
@@ -66,21 +141,66 @@ pub fn handle_insert_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
 
 fn handle_ctrl_key(code: KeyCode, state: &mut TuiState) -> Result<Option<bool>> {
     match code {
-        KeyCode::Char('z') => {
-            if state.editor.undo() {
-                state.modified = true;
-            }
+        KeyCode::Char('z') if state.editor.undo() => {
+            state.modified = true;
         }
-        KeyCode::Char('Z') => {
-            if state.editor.redo() {
-                state.modified = true;
-            }
+        KeyCode::Char('Z') if state.editor.redo() => {
+            state.modified = true;
         }
+        // ctrl+space - completion from rust-analyzer
+        KeyCode::Char(' ') => state.lsp_complete(),
+        // ctrl+n / ctrl+p - complete from the buffer, keywords and std
+        KeyCode::Char('n') => state.complete_locally(false),
+        KeyCode::Char('p') => state.complete_locally(true),
         _ => {}
     }
     Ok(None)
 }
 
+// navigate or accept the completion popup. Returns false for keys it doesn't use.
+fn handle_completion_key(key: event::KeyEvent, state: &mut TuiState) -> bool {
+    let Some(menu) = &mut state.completion else {
+        return false;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+        KeyCode::Down => menu.select_next(),
+        KeyCode::Char('n') if ctrl => menu.select_next(),
+        KeyCode::Up => menu.select_prev(),
+        KeyCode::Char('p') if ctrl => menu.select_prev(),
+        KeyCode::Esc => state.completion = None,
+        KeyCode::Enter | KeyCode::Tab => {
+            let start_col = menu.start_col;
+            let insert = menu.selected_item().insert.clone();
+            state.completion = None;
+            state.modified = true;
+            state.editor.save_snapshot();
+            state.editor.replace_before_cursor(start_col, &insert);
+        }
+        _ => return false,
+    }
+    true
+}
+
+// narrow the popup down to the typed prefix, close it when leaving the word
+fn update_completion(state: &mut TuiState) {
+    let Some(menu) = &mut state.completion else {
+        return;
+    };
+    let editor = &state.editor;
+    let row = editor.cursor_row;
+    let keep = state.mode == EditorMode::Insert && editor.cursor_col >= menu.start_col && {
+        let prefix = editor
+            .buffer
+            .slice((row, menu.start_col), (row, editor.cursor_col));
+        prefix.chars().all(completion::is_path_char) && menu.filter(&prefix)
+    };
+    if !keep {
+        state.completion = None;
+    }
+}
+
 fn handle_char(c: char, state: &mut TuiState) -> Result<Option<bool>> {
     // skip over closing bracket if already there
     if SKIP_CHARS.contains(&c) && state.editor.char_at_cursor() == Some(c) {
@@ -91,6 +211,12 @@ fn handle_char(c: char, state: &mut TuiState) -> Result<Option<bool>> {
     state.modified = true;
     state.editor.save_snapshot();
 
+    // closing bracket typed at the start of a line: dedent to its opener's level
+    if matches!(c, '}' | ')' | ']') && text_before_cursor(state).trim().is_empty() {
+        let width = indent::indent_for_closer(state.editor.buffer.lines(), state.editor.cursor_row);
+        state.editor.set_line_indent(width);
+    }
+
     // auto-pair: insert both open and close, cursor between
     if let Some(close) = get_closing_pair(c) {
         state.editor.insert_char(c);
@@ -104,6 +230,10 @@ fn handle_char(c: char, state: &mut TuiState) -> Result<Option<bool>> {
 }
 
 fn handle_tab(state: &mut TuiState) -> Result<Option<bool>> {
+    if state.expand_snippet() {
+        return Ok(None);
+    }
+
     state.modified = true;
     state.editor.save_snapshot();
     for _ in 0..4 {
@@ -116,11 +246,19 @@ fn handle_enter(state: &mut TuiState) -> Result<Option<bool>> {
     state.modified = true;
     state.editor.save_snapshot();
 
-    let indent = get_current_indent(state);
+    let before = text_before_cursor(state);
+    let indent = indent::indent_after(&before);
+    let split_pair = between_pair(state);
     state.editor.insert_newline();
+    state.editor.set_line_indent(indent);
 
-    for _ in 0..indent {
-        state.editor.insert_char(' ');
+    // `{|}` + Enter puts the closer on its own line below the cursor
+    if split_pair {
+        let base = before.chars().take_while(|c| c.is_whitespace()).count();
+        state.editor.insert_newline();
+        state.editor.set_line_indent(base);
+        state.editor.move_up();
+        state.editor.move_to_line_end();
     }
     Ok(None)
 }
@@ -1,9 +1,11 @@
 pub mod command;
 pub mod insert;
+pub mod mouse;
 pub mod normal;
 pub mod visual;
 
 pub use command::handle_command_mode;
 pub use insert::handle_insert_mode;
+pub use mouse::handle_mouse;
 pub use normal::handle_normal_mode;
 pub use visual::handle_visual_mode;
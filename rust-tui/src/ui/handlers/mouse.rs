@@ -0,0 +1,163 @@
+//! Mouse handler - routes events to the pane under the pointer
+
+use anyhow::Result;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+use crate::ui::{
+    layout::{self, Divider},
+    render::{editor::editor_chunks, frog},
+    state::{EditorMode, TuiState, ViewMode},
+};
+
+// rows scrolled by one step of the wheel
+const WHEEL_ROWS: usize = 3;
+
+/// Handle a mouse event in the pane under the pointer
+pub fn handle_mouse(mouse: MouseEvent, state: &mut TuiState) -> Result<()> {
+    let Ok((width, height)) = crossterm::terminal::size() else {
+        return Ok(());
+    };
+    let panes = layout::panes(
+        Rect::new(0, 0, width, height),
+        state.view_mode,
+        state.show_frog,
+        &state.config.layout,
+    );
+    let pointer = Position::new(mouse.column, mouse.row);
+
+    if state.view_mode == ViewMode::HelpModal {
+        if matches!(mouse.kind, MouseEventKind::Down(_)) {
+            state.view_mode = ViewMode::EditorOnly;
+        }
+        return Ok(());
+    }
+
+    // dragging a border resizes the panes, the layout is saved when it's released
+    if let Some(divider) = state.resizing {
+        match mouse.kind {
+            MouseEventKind::Drag(MouseButton::Left) => match divider {
+                Divider::Side => {
+                    state.set_side_percent(
+                        panes.side_percent_at(pointer, state.config.layout.swap_sides),
+                    );
+                }
+                Divider::Output => state.set_output_height(height.saturating_sub(mouse.row)),
+            },
+            MouseEventKind::Up(_) => {
+                state.resizing = None;
+                state.config.write()?;
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+    if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+        state.resizing = panes.divider_at(pointer);
+        if state.resizing.is_some() {
+            return Ok(());
+        }
+    }
+
+    // a selection keeps following the pointer when it's dragged out of the editor
+    let dragging = mouse.kind == MouseEventKind::Drag(MouseButton::Left);
+    if let Some(editor) = panes.editor
+        && (editor.contains(pointer) || dragging)
+    {
+        handle_editor_mouse(mouse, editor, state);
+    } else if let Some(side) = panes.side
+        && side.contains(pointer)
+    {
+        handle_side_mouse(mouse, side, state);
+    } else {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                state.output_scroll = state.output_scroll.saturating_add(2);
+            }
+            MouseEventKind::ScrollUp => {
+                state.output_scroll = state.output_scroll.saturating_sub(2);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// click to place the cursor, drag to select, wheel to scroll
+fn handle_editor_mouse(mouse: MouseEvent, area: Rect, state: &mut TuiState) {
+    let code = editor_chunks(area, state.editor.line_count())[1];
+    let x = mouse.column.saturating_sub(code.x) as usize;
+    let y = mouse.row.saturating_sub(code.y) as usize;
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if state.mode.is_visual() || state.mode == EditorMode::Command {
+                state.mode = EditorMode::Normal;
+            }
+            state.completion = None;
+            state.doc_popup = None;
+            state.snippet = None;
+            (state.editor.cursor_row, state.editor.cursor_col) = state.editor.pos_at_screen(x, y);
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            // the selection starts where the button was pressed
+            if state.mode == EditorMode::Normal {
+                state.enter_visual(EditorMode::Visual);
+            }
+            (state.editor.cursor_row, state.editor.cursor_col) = state.editor.pos_at_screen(x, y);
+        }
+        MouseEventKind::ScrollDown => {
+            state
+                .editor
+                .scroll_view(WHEEL_ROWS as isize, code.height as usize);
+        }
+        MouseEventKind::ScrollUp => {
+            state
+                .editor
+                .scroll_view(-(WHEEL_ROWS as isize), code.height as usize);
+        }
+        _ => {}
+    }
+}
+
+// wheel to scroll the solution or Frog panel, click on a step number to open that slide
+fn handle_side_mouse(mouse: MouseEvent, area: Rect, state: &mut TuiState) {
+    if state.view_mode == ViewMode::WithSolution {
+        let lines = state
+            .solution_content
+            .as_deref()
+            .map_or(0, |s| s.lines().count());
+        let max_scroll = lines.saturating_sub(area.height.saturating_sub(2) as usize);
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                state.solution_scroll = (state.solution_scroll + WHEEL_ROWS).min(max_scroll);
+            }
+            MouseEventKind::ScrollUp => {
+                state.solution_scroll = state.solution_scroll.saturating_sub(WHEEL_ROWS);
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(step) = frog::step_at(area, state, mouse.column, mouse.row) {
+                state.goto_frog_step(step);
+            } else if let Some(block) = frog::code_block_at(area, state, mouse.column, mouse.row) {
+                state.frog_block = Some(block);
+            }
+        }
+        MouseEventKind::ScrollDown => {
+            for _ in 0..WHEEL_ROWS {
+                state.scroll_frog_down();
+            }
+        }
+        MouseEventKind::ScrollUp => {
+            for _ in 0..WHEEL_ROWS {
+                state.scroll_frog_up();
+            }
+        }
+        _ => {}
+    }
+}
@@ -41,12 +41,10 @@ pub fn handle_editing(code: KeyCode, state: &mut TuiState) -> Result<Option<bool
             if let Some(text) = &state.yank_buffer {
                 state.modified = true;
                 state.editor.save_snapshot();
-                if text.contains('\n') || text.ends_with('\n') {
-                    state.editor.insert_line_below(text.trim_end().to_string());
+                if text.contains('\n') {
+                    state.editor.insert_lines_below(text);
                 } else {
-                    for c in text.chars() {
-                        state.editor.insert_char(c);
-                    }
+                    state.editor.insert_str(text);
                 }
             }
             Ok(None)
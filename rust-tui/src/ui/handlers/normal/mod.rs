@@ -17,6 +17,16 @@ pub fn handle_normal_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
         return Ok(None);
     }
 
+    // `o` or a number shows the path of a page of the std docs popup, other keys close it
+    if state.doc_popup.is_some() {
+        match key.code {
+            KeyCode::Char('o') => state.std_doc_path(0),
+            KeyCode::Char(c @ '1'..='9') => state.std_doc_path(c as usize - '1' as usize),
+            _ => state.doc_popup = None,
+        }
+        return Ok(None);
+    }
+
     // Handle pending key sequences first
     if !state.pending_keys.is_empty() {
         if let KeyCode::Char(c) = key.code {
@@ -78,9 +88,15 @@ pub fn handle_normal_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
             Ok(None)
         }
         KeyCode::Char('v') => {
-            state.mode = EditorMode::Visual;
-            state.visual_start_row = state.editor.cursor_row;
-            state.visual_start_col = state.editor.cursor_col;
+            state.enter_visual(EditorMode::Visual);
+            Ok(None)
+        }
+        KeyCode::Char('V') => {
+            state.enter_visual(EditorMode::VisualLine);
+            Ok(None)
+        }
+        KeyCode::Char('K') => {
+            state.lsp_hover();
             Ok(None)
         }
         KeyCode::Char('s') => {
@@ -107,6 +123,14 @@ pub fn handle_normal_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
             state.pending_keys.push('c');
             Ok(None)
         }
+        KeyCode::Char('=') => {
+            state.pending_keys.push('=');
+            Ok(None)
+        }
+        KeyCode::Char('z') => {
+            state.pending_keys.push('z');
+            Ok(None)
+        }
         KeyCode::Char('u') => {
             // Vim undo
             if state.editor.undo() {
@@ -123,6 +147,20 @@ pub fn handle_normal_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
             Ok(None)
         }
         KeyCode::Char('q') => Ok(Some(true)),
+        // select a code block on the Frog slide
+        KeyCode::Tab => {
+            if state.show_frog {
+                state.select_frog_block(true);
+            }
+            Ok(None)
+        }
+        // answer a quiz slide in the Frog panel
+        KeyCode::Char(c @ '1'..='9') => {
+            if state.show_frog {
+                state.answer_quiz(c as usize - '1' as usize)?;
+            }
+            Ok(None)
+        }
         KeyCode::PageDown => {
             state.output_scroll = state.output_scroll.saturating_add(10);
             Ok(None)
@@ -135,6 +173,15 @@ pub fn handle_normal_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
     }
 }
 
+impl TuiState<'_> {
+    /// Start a visual selection of the given shape at the cursor
+    pub fn enter_visual(&mut self, mode: EditorMode) {
+        self.mode = mode;
+        self.visual_start_row = self.editor.cursor_row;
+        self.visual_start_col = self.editor.cursor_col;
+    }
+}
+
 /// Handle Ctrl+key combinations
 fn handle_ctrl_keys(code: KeyCode, state: &mut TuiState) -> Result<Option<bool>> {
     match code {
@@ -145,6 +192,25 @@ fn handle_ctrl_keys(code: KeyCode, state: &mut TuiState) -> Result<Option<bool>>
             }
             Ok(None)
         }
+        // Ctrl+V - visual block
+        KeyCode::Char('v') => {
+            state.enter_visual(EditorMode::VisualBlock);
+            Ok(None)
+        }
+        // Ctrl+D / Ctrl+U - scroll output down/up
+        KeyCode::Char('d') => {
+            state.output_scroll = state.output_scroll.saturating_add(5);
+            Ok(None)
+        }
+        KeyCode::Char('u') => {
+            state.output_scroll = state.output_scroll.saturating_sub(5);
+            Ok(None)
+        }
+        // Ctrl+W - window command
+        KeyCode::Char('w') => {
+            state.pending_keys.push(text_objects::CTRL_W);
+            Ok(None)
+        }
         // Ctrl+O - toggle expanded output
         KeyCode::Char('o') => {
             state.view_mode = if state.view_mode == ViewMode::ExpandedOutput {
@@ -174,6 +240,11 @@ fn handle_shift_keys(code: KeyCode, state: &mut TuiState) -> Result<Option<bool>
             state.mode = EditorMode::Insert;
             Ok(None)
         }
+        // Shift+V - visual line
+        KeyCode::Char('V') => {
+            state.enter_visual(EditorMode::VisualLine);
+            Ok(None)
+        }
         // Shift+G - go to last line
         KeyCode::Char('G') => {
             state.editor.goto_last_line();
@@ -190,12 +261,35 @@ fn handle_shift_keys(code: KeyCode, state: &mut TuiState) -> Result<Option<bool>
             state.show_frog = !state.show_frog;
             Ok(None)
         }
-        KeyCode::Char('J') => {
-            state.output_scroll = state.output_scroll.saturating_add(5);
+        // Shift+R - run the code blocks of the Frog slide
+        KeyCode::Char('R') => {
+            if state.show_frog {
+                state.run_frog_code();
+            }
+            Ok(None)
+        }
+        // Shift+Y / Shift+P - yank / put the selected code block of the Frog slide
+        KeyCode::Char('Y') => {
+            if state.show_frog {
+                state.yank_frog_block();
+            }
+            Ok(None)
+        }
+        KeyCode::Char('P') => {
+            if state.show_frog {
+                state.put_frog_block();
+            }
             Ok(None)
         }
+        // Shift+K - type and docs from rust-analyzer
         KeyCode::Char('K') => {
-            state.output_scroll = state.output_scroll.saturating_sub(5);
+            state.lsp_hover();
+            Ok(None)
+        }
+        KeyCode::BackTab => {
+            if state.show_frog {
+                state.select_frog_block(false);
+            }
             Ok(None)
         }
         KeyCode::Right => {
@@ -1,8 +1,12 @@
-//! Text object commands (dd, yy, daw, diw, caw, ciw, gg, r<char>)
+//! Text object commands (dd, yy, daw, diw, caw, ciw, gg, gd, gK, g-, g+, r<char>, ==, =G, =gg)
+//! and the window commands following Ctrl+W
 
 use crate::ui::state::{EditorMode, TuiState};
 use anyhow::Result;
 
+/// Pending key for Ctrl+W, the control character the terminal sends for it
+pub const CTRL_W: char = '\u{17}';
+
 /// Handle pending key sequences for text objects and multi-key commands
 pub fn handle_pending_keys(key_char: char, state: &mut TuiState) -> Result<Option<bool>> {
     let pending = state.pending_keys.clone();
@@ -21,9 +25,7 @@ pub fn handle_pending_keys(key_char: char, state: &mut TuiState) -> Result<Optio
         // yy - yank line
         (&['y'], 'y') => {
             state.pending_keys.clear();
-            if let Some(line) = state.editor.get_current_line() {
-                state.yank_buffer = Some(line.clone());
-            }
+            state.yank_buffer = Some(state.editor.get_current_line());
             Ok(None)
         }
         // r<char> - replace char
@@ -81,15 +83,85 @@ pub fn handle_pending_keys(key_char: char, state: &mut TuiState) -> Result<Optio
             state.mode = EditorMode::Insert;
             Ok(None)
         }
+        // == / =j / =k / =G - reindent lines
+        (&['='], '=' | 'j' | 'k' | 'G') => {
+            state.pending_keys.clear();
+            let row = state.editor.cursor_row;
+            let last = state.editor.line_count() - 1;
+            let (start, end) = match key_char {
+                'j' => (row, (row + 1).min(last)),
+                'k' => (row.saturating_sub(1), row),
+                'G' => (row, last),
+                _ => (row, row),
+            };
+            reindent(state, start, end);
+            Ok(None)
+        }
+        (&['='], 'g') => {
+            state.pending_keys.push(key_char);
+            Ok(None)
+        }
+        // =gg - reindent up to the first line
+        (&['=', 'g'], 'g') => {
+            state.pending_keys.clear();
+            reindent(state, 0, state.editor.cursor_row);
+            Ok(None)
+        }
         // gg - go to first line
         (['g'], 'g') => {
             state.pending_keys.clear();
             state.editor.goto_first_line();
             Ok(None)
         }
+        // gd - go to definition
+        (['g'], 'd') => {
+            state.pending_keys.clear();
+            state.lsp_goto_definition();
+            Ok(None)
+        }
+        // gK - offline std docs of the identifier under the cursor
+        (['g'], 'K') => {
+            state.pending_keys.clear();
+            state.std_doc_under_cursor();
+            Ok(None)
+        }
+        // g- / g+ - older/newer buffer state across undo branches
+        (['g'], '-' | '+') => {
+            state.pending_keys.clear();
+            let steps = if key_char == '-' { -1 } else { 1 };
+            if state.editor.undo_chronological(steps) {
+                state.modified = true;
+            }
+            Ok(None)
+        }
+        // za / zc / zo / zM / zR - folds
+        (['z'], 'a' | 'c' | 'o' | 'M' | 'R') => {
+            state.pending_keys.clear();
+            let editor = &mut state.editor;
+            match key_char {
+                'a' => editor.toggle_fold(),
+                'c' => editor.close_fold(),
+                'o' => editor.folds.open(editor.cursor_row),
+                'M' => editor.close_all_folds(),
+                _ => editor.folds.open_all(),
+            }
+            Ok(None)
+        }
+        // Ctrl+W < > + - x s v = - resize and arrange the panels
+        ([CTRL_W], c) => {
+            state.pending_keys.clear();
+            state.window_command(c)?;
+            Ok(None)
+        }
         _ => {
             state.pending_keys.clear();
             Ok(None)
         }
     }
 }
+
+fn reindent(state: &mut TuiState, start: usize, end: usize) {
+    state.modified = true;
+    state.editor.save_snapshot();
+    state.editor.reindent(start, end);
+}
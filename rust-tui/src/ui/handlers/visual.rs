@@ -1,7 +1,8 @@
-use crate::ui::render::editor::get_selection_bounds;
-use crate::ui::state::{EditorMode, TuiState};
+use crate::ui::buffer::{col_at_cell, display_width, grapheme_len};
+use crate::ui::render::editor::{get_block_cols, get_selection_bounds, selection_cols_for_row};
+use crate::ui::state::{BlockInsert, EditorMode, TuiState};
 use anyhow::Result;
-use crossterm::event::{self, KeyCode};
+use crossterm::event::{self, KeyCode, KeyModifiers};
 
 // encapsulate selection range to reduce arg count
 struct Bounds {
@@ -21,18 +22,56 @@ impl Bounds {
             end_col,
         }
     }
+}
 
-    fn is_single_line(&self) -> bool {
-        self.start_row == self.end_row
-    }
+// switch between the visual shapes, or leave visual mode when pressing the current one again
+fn toggle_visual(state: &mut TuiState, mode: EditorMode) {
+    state.mode = if state.mode == mode {
+        EditorMode::Normal
+    } else {
+        mode
+    };
 }
 
 pub fn handle_visual_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<Option<bool>> {
+    // Ctrl+V - block selection
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        if key.code == KeyCode::Char('v') {
+            toggle_visual(state, EditorMode::VisualBlock);
+        }
+        return Ok(None);
+    }
+
+    // gc / gg
+    if state.pending_keys == ['g'] {
+        state.pending_keys.clear();
+        match key.code {
+            KeyCode::Char('c') => {
+                let b = Bounds::from_state(state);
+                state.modified = true;
+                state.editor.save_snapshot();
+                state.editor.toggle_comment(b.start_row, b.end_row);
+                state.mode = EditorMode::Normal;
+            }
+            KeyCode::Char('g') => state.editor.goto_first_line(),
+            _ => {}
+        }
+        return Ok(None);
+    }
+
     match key.code {
         KeyCode::Esc => {
             state.mode = EditorMode::Normal;
             Ok(None)
         }
+        KeyCode::Char('v') => {
+            toggle_visual(state, EditorMode::Visual);
+            Ok(None)
+        }
+        KeyCode::Char('V') => {
+            toggle_visual(state, EditorMode::VisualLine);
+            Ok(None)
+        }
 
         // movement
         KeyCode::Char('h') | KeyCode::Left => {
@@ -51,6 +90,14 @@ pub fn handle_visual_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
             state.editor.move_right();
             Ok(None)
         }
+        KeyCode::Char('w') => {
+            state.editor.move_word_forward();
+            Ok(None)
+        }
+        KeyCode::Char('b') => {
+            state.editor.move_word_backward();
+            Ok(None)
+        }
         KeyCode::Char('0') | KeyCode::Home => {
             state.editor.move_to_line_start();
             Ok(None)
@@ -59,6 +106,14 @@ pub fn handle_visual_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
             state.editor.move_to_line_end();
             Ok(None)
         }
+        KeyCode::Char('G') => {
+            state.editor.goto_last_line();
+            Ok(None)
+        }
+        KeyCode::Char('g') => {
+            state.pending_keys.push('g');
+            Ok(None)
+        }
 
         // actions
         KeyCode::Char('y') => {
@@ -66,58 +121,136 @@ pub fn handle_visual_mode(key: event::KeyEvent, state: &mut TuiState) -> Result<
             state.mode = EditorMode::Normal;
             Ok(None)
         }
-        KeyCode::Char('d') => {
+        KeyCode::Char('d') | KeyCode::Char('x') => {
             delete_visual_selection(state);
             state.mode = EditorMode::Normal;
             Ok(None)
         }
+        KeyCode::Char('>') => {
+            let b = Bounds::from_state(state);
+            state.modified = true;
+            state.editor.save_snapshot();
+            state.editor.indent_lines(b.start_row, b.end_row);
+            state.mode = EditorMode::Normal;
+            Ok(None)
+        }
+        KeyCode::Char('<') => {
+            let b = Bounds::from_state(state);
+            state.modified = true;
+            state.editor.save_snapshot();
+            state.editor.outdent_lines(b.start_row, b.end_row);
+            state.mode = EditorMode::Normal;
+            Ok(None)
+        }
+        KeyCode::Char('=') => {
+            let b = Bounds::from_state(state);
+            state.modified = true;
+            state.editor.save_snapshot();
+            state.editor.reindent(b.start_row, b.end_row);
+            state.mode = EditorMode::Normal;
+            Ok(None)
+        }
+        KeyCode::Char('I') if state.mode == EditorMode::VisualBlock => {
+            let (left, _) = get_block_cols(state);
+            start_block_insert(state, left, false);
+            Ok(None)
+        }
+        KeyCode::Char('A') if state.mode == EditorMode::VisualBlock => {
+            let (_, right) = get_block_cols(state);
+            start_block_insert(state, right + 1, true);
+            Ok(None)
+        }
 
         _ => Ok(None),
     }
 }
 
-// helper: get chars from a line, safely
-fn line_chars(lines: &[String], row: usize) -> Vec<char> {
-    lines
-        .get(row)
-        .map(|l| l.chars().collect())
-        .unwrap_or_default()
+// column at display `cell` of `row`, past the end of a shorter line by the padding it needs
+fn col_for_cell(state: &TuiState, row: usize, cell: usize) -> usize {
+    let line = state.editor.buffer.line(row);
+    col_at_cell(&line, cell).unwrap_or_else(|| grapheme_len(&line) + cell - display_width(&line))
 }
 
-// helper: slice chars from start to end (inclusive end_col)
-fn slice_chars(chars: &[char], start: usize, end: usize) -> String {
-    let s = start.min(chars.len());
-    let e = end.min(chars.len());
-    chars[s..e].iter().collect()
+// I/A in block mode: type on the first row, replicated to the rest on Esc
+fn start_block_insert(state: &mut TuiState, cell: usize, append: bool) {
+    let b = Bounds::from_state(state);
+    state.modified = true;
+    state.editor.save_snapshot();
+    let col = col_for_cell(state, b.start_row, cell);
+
+    // `A` past the end of the first line pads it like the others will be
+    if append {
+        state.editor.insert_str_at(b.start_row, col, "");
+    }
+    state.editor.cursor_row = b.start_row;
+    state.editor.cursor_col = col;
+    state.editor.clamp_col();
+
+    state.block_insert = Some(BlockInsert {
+        start_row: b.start_row,
+        end_row: b.end_row,
+        col: state.editor.cursor_col,
+        cell,
+        line_len: state.editor.current_line_len(),
+        append,
+    });
+    state.mode = EditorMode::Insert;
 }
 
-pub fn get_visual_selection(state: &TuiState) -> String {
-    let b = Bounds::from_state(state);
+/// Replicate the text typed during a block insert on the remaining rows
+pub fn finish_block_insert(state: &mut TuiState, insert: BlockInsert) {
+    // bail out if the user left the first row (e.g. typed a newline)
+    if state.editor.cursor_row != insert.start_row {
+        return;
+    }
 
-    if b.is_single_line() {
-        let chars = line_chars(&state.editor.lines, b.start_row);
-        return slice_chars(&chars, b.start_col, b.end_col + 1);
+    let buffer = &state.editor.buffer;
+    let Some(typed_len) = buffer
+        .line_len(insert.start_row)
+        .checked_sub(insert.line_len)
+    else {
+        return;
+    };
+    let row = insert.start_row;
+    let typed = buffer.slice((row, insert.col), (row, insert.col + typed_len));
+    if typed.is_empty() {
+        return;
     }
 
-    // multi-line
-    let mut result = String::new();
-    for row in b.start_row..=b.end_row {
-        let chars = line_chars(&state.editor.lines, row);
-
-        let slice = if row == b.start_row {
-            slice_chars(&chars, b.start_col, chars.len())
-        } else if row == b.end_row {
-            slice_chars(&chars, 0, b.end_col + 1)
-        } else {
-            chars.iter().collect()
-        };
+    for row in insert.start_row + 1..=insert.end_row {
+        let width = display_width(&state.editor.buffer.line(row));
+        // `I` skips lines that end before the block, `A` pads them
+        if !insert.append && width < insert.cell {
+            continue;
+        }
+        let col = col_for_cell(state, row, insert.cell);
+        state.editor.insert_str_at(row, col, &typed);
+    }
+}
 
-        result.push_str(&slice);
-        if row != b.end_row {
+pub fn get_visual_selection(state: &TuiState) -> String {
+    let b = Bounds::from_state(state);
+    let buffer = &state.editor.buffer;
+
+    match state.mode {
+        // linewise yanks end with a newline so `p` puts them below the cursor line
+        EditorMode::VisualLine => {
+            let end = (b.end_row, buffer.line_len(b.end_row));
+            let mut result = buffer.slice((b.start_row, 0), end);
             result.push('\n');
+            result
         }
+        EditorMode::VisualBlock => (b.start_row..=b.end_row)
+            .map(|row| {
+                let (start, end) =
+                    selection_cols_for_row(state, row, buffer.line_len(row)).unwrap_or_default();
+                buffer.slice((row, start), (row, end))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        // the end column is inclusive, the line break after it isn't selected
+        _ => buffer.slice((b.start_row, b.start_col), (b.end_row, b.end_col + 1)),
     }
-    result
 }
 
 pub fn delete_visual_selection(state: &mut TuiState) {
@@ -126,54 +259,40 @@ pub fn delete_visual_selection(state: &mut TuiState) {
     // yank before delete
     state.yank_buffer = Some(get_visual_selection(state));
     state.modified = true;
+    state.editor.save_snapshot();
 
-    if b.is_single_line() {
-        delete_single_line(state, &b);
-    } else {
-        delete_multi_line(state, &b);
-    }
-
-    state.editor.cursor_row = b.start_row;
-    state.editor.cursor_col = b.start_col;
-}
-
-fn delete_single_line(state: &mut TuiState, b: &Bounds) {
-    let Some(line) = state.editor.lines.get_mut(b.start_row) else {
-        return;
-    };
-
-    let chars: Vec<char> = line.chars().collect();
-    let start = b.start_col.min(chars.len());
-    let end = (b.end_col + 1).min(chars.len());
-
-    // char indices -> byte indices
-    let byte_start: usize = chars[..start].iter().map(|c| c.len_utf8()).sum();
-    let byte_end: usize = byte_start
-        + chars[start..end]
-            .iter()
-            .map(|c| c.len_utf8())
-            .sum::<usize>();
-
-    line.replace_range(byte_start..byte_end, "");
-}
-
-fn delete_multi_line(state: &mut TuiState, b: &Bounds) {
-    // get the parts to keep
-    let first_chars = line_chars(&state.editor.lines, b.start_row);
-    let last_chars = line_chars(&state.editor.lines, b.end_row);
-
-    let keep_first = slice_chars(&first_chars, 0, b.start_col);
-    let keep_last = slice_chars(&last_chars, b.end_col + 1, last_chars.len());
-
-    // remove middle lines
-    for _ in b.start_row..b.end_row {
-        if b.start_row + 1 < state.editor.lines.len() {
-            state.editor.lines.remove(b.start_row + 1);
+    match state.mode {
+        EditorMode::VisualLine => {
+            state.editor.buffer.remove_lines(b.start_row, b.end_row);
+            state.editor.cursor_row = b.start_row.min(state.editor.line_count() - 1);
+            state.editor.move_to_first_non_whitespace();
+        }
+        EditorMode::VisualBlock => {
+            let len = state.editor.buffer.line_len(b.start_row);
+            let left = selection_cols_for_row(state, b.start_row, len).map_or(0, |(left, _)| left);
+            delete_block(state, &b);
+            state.editor.cursor_row = b.start_row;
+            state.editor.cursor_col = left;
+            state.editor.clamp_col();
+        }
+        _ => {
+            state
+                .editor
+                .buffer
+                .remove((b.start_row, b.start_col), (b.end_row, b.end_col + 1));
+            state.editor.cursor_row = b.start_row;
+            state.editor.cursor_col = b.start_col;
+            state.editor.clamp_col();
         }
     }
+}
 
-    // merge
-    if let Some(line) = state.editor.lines.get_mut(b.start_row) {
-        *line = keep_first + &keep_last;
+fn delete_block(state: &mut TuiState, b: &Bounds) {
+    for row in b.start_row..=b.end_row {
+        let len = state.editor.buffer.line_len(row);
+        let Some((start, end)) = selection_cols_for_row(state, row, len) else {
+            continue;
+        };
+        state.editor.buffer.remove((row, start), (row, end));
     }
 }
@@ -0,0 +1,202 @@
+//! Rust-aware indentation based on bracket depth
+
+use crate::ui::{
+    editor::INDENT,
+    lexer::{self, LexState, TokenKind},
+};
+
+/// Summary of one line for indentation purposes
+struct LineScan {
+    // net bracket depth change over the whole line
+    delta: i32,
+    // closing brackets before any other code on the line
+    leading_closers: i32,
+    // the line starts inside a (multi-line) string literal
+    starts_in_string: bool,
+    state: LexState,
+}
+
+// brackets in strings, comments and char literals aren't punctuation tokens
+fn scan_line(line: &str, state: LexState) -> LineScan {
+    let (tokens, next) = lexer::tokenize_line(line, state);
+    let mut delta = 0;
+    let mut leading_closers = 0;
+    let mut seen_code = false;
+
+    for token in tokens {
+        let text = &line[token.range];
+        match token.kind {
+            TokenKind::Punct => {}
+            TokenKind::Comment | TokenKind::DocComment => continue,
+            _ => {
+                seen_code |= !text.trim().is_empty();
+                continue;
+            }
+        }
+        for c in text.chars() {
+            match c {
+                '{' | '(' | '[' => delta += 1,
+                '}' | ')' | ']' => {
+                    delta -= 1;
+                    if !seen_code {
+                        leading_closers += 1;
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            seen_code = true;
+        }
+    }
+
+    LineScan {
+        delta,
+        leading_closers,
+        starts_in_string: matches!(state, LexState::Str { .. }),
+        state: next,
+    }
+}
+
+fn leading_whitespace(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+// the line up to its last code token, `//` in a string like `"http://"` isn't a comment
+fn code_part(line: &str, state: LexState) -> &str {
+    let (tokens, _) = lexer::tokenize_line(line, state);
+    let end = tokens
+        .into_iter()
+        .filter(|token| {
+            !matches!(token.kind, TokenKind::Comment | TokenKind::DocComment)
+                && !line[token.range.clone()].trim().is_empty()
+        })
+        .map(|token| token.range.end)
+        .max()
+        .unwrap_or(0);
+    &line[..end]
+}
+
+/// Whether the line leaves the next one indented one level deeper
+fn opens_block(line: &str) -> bool {
+    let code = code_part(line, LexState::Code);
+    code.ends_with(['{', '(', '[']) || code.ends_with("=>")
+}
+
+/// Indentation (in spaces) for a new line inserted after `line`
+pub fn indent_after(line: &str) -> usize {
+    let base = leading_whitespace(line);
+    if opens_block(line) {
+        base + INDENT.len()
+    } else {
+        base
+    }
+}
+
+/// Bracket depth and lexer state at the start of `row`
+fn depth_at(lines: impl IntoIterator<Item = impl AsRef<str>>, row: usize) -> (i32, LexState) {
+    lines
+        .into_iter()
+        .take(row)
+        .fold((0, LexState::Code), |(depth, state), line| {
+            let scan = scan_line(line.as_ref(), state);
+            ((depth + scan.delta).max(0), scan.state)
+        })
+}
+
+/// Indentation (in spaces) of a line at `row` that starts with a closing bracket
+pub fn indent_for_closer(lines: impl IntoIterator<Item = impl AsRef<str>>, row: usize) -> usize {
+    let (depth, _) = depth_at(lines, row);
+    (depth - 1).max(0) as usize * INDENT.len()
+}
+
+/// Reindent the rows `start..=end` according to bracket depth.
+/// Lines inside multi-line string literals are left untouched.
+pub fn reindent(lines: &mut [String], start: usize, end: usize) {
+    let end = end.min(lines.len().saturating_sub(1));
+    let (mut depth, mut state) = depth_at(lines.iter(), start);
+    // a match arm or expression continued from the previous line
+    let mut continuation = start > 0 && {
+        let (_, prev_state) = depth_at(lines.iter(), start - 1);
+        opens_continuation(&lines[start - 1], prev_state)
+    };
+
+    for line in lines.iter_mut().take(end + 1).skip(start) {
+        let scan = scan_line(line, state);
+        let trimmed = line.trim_start().to_string();
+
+        if !scan.starts_in_string {
+            let mut level = (depth - scan.leading_closers).max(0) as usize;
+            if continuation || trimmed.starts_with('.') {
+                level += 1;
+            }
+
+            *line = if trimmed.is_empty() {
+                String::new()
+            } else {
+                format!("{}{trimmed}", INDENT.repeat(level))
+            };
+        }
+
+        if !trimmed.is_empty() {
+            continuation = opens_continuation(line, state);
+        }
+        depth = (depth + scan.delta).max(0);
+        state = scan.state;
+    }
+}
+
+// `=>` at the end of a line indents only the next line (not a block)
+fn opens_continuation(line: &str, state: LexState) -> bool {
+    code_part(line, state).ends_with("=>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn indent_after_openers() {
+        assert_eq!(indent_after("fn main() {"), 4);
+        assert_eq!(indent_after("    foo("), 8);
+        assert_eq!(indent_after("        Some(x) =>"), 12);
+        assert_eq!(indent_after("    let x = 1; // {"), 4);
+        assert_eq!(indent_after("    x"), 4);
+        assert_eq!(indent_after("    let url = \"http://x\"; foo("), 8);
+        assert_eq!(indent_after("    let c = '/'; m(c) { // x"), 8);
+    }
+
+    #[test]
+    fn reindent_nested_blocks() {
+        let mut buf = lines(
+            "fn main() {\nlet v = vec![\n1,\n];\nmatch v.len() {\n0 =>\nprintln!(\"{{\"),\n_ => {}\n}\n}",
+        );
+        let len = buf.len();
+        reindent(&mut buf, 0, len - 1);
+        assert_eq!(
+            buf,
+            lines(
+                "fn main() {\n    let v = vec![\n        1,\n    ];\n    match v.len() {\n        0 =>\n            println!(\"{{\"),\n        _ => {}\n    }\n}",
+            ),
+        );
+    }
+
+    #[test]
+    fn reindent_ignores_strings_comments_and_chars() {
+        let mut buf = lines("fn f() {\n/* { */ let c = '{';\nlet s = \"}\";\n}");
+        reindent(&mut buf, 0, 3);
+        assert_eq!(
+            buf,
+            lines("fn f() {\n    /* { */ let c = '{';\n    let s = \"}\";\n}"),
+        );
+    }
+
+    #[test]
+    fn closer_dedents_to_opener() {
+        let buf = lines("fn f() {\n    if x {\n        y();\n        }");
+        assert_eq!(indent_for_closer(&buf, 3), 4);
+    }
+}
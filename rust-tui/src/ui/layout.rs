@@ -1,14 +1,136 @@
 //! Layout utilities for the Rustlings TUI
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use anyhow::Result;
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+
+use crate::ui::{
+    config::LayoutConfig,
+    state::{TuiState, ViewMode},
+    theme,
+};
+
+/// Terminals narrower than this stack the editor and the side panel
+pub const NARROW_WIDTH: u16 = 100;
+const MIN_SIDE_PERCENT: u16 = 20;
+const MAX_SIDE_PERCENT: u16 = 80;
+// one line of output between the borders, progress bar and status bar
+const MIN_OUTPUT_HEIGHT: u16 = 5;
+// percent or rows changed by one `Ctrl+W` resize
+const RESIZE_STEP: u16 = 5;
+const RESIZE_ROWS: u16 = 2;
+// header and editor rows kept when the output panel grows
+const MIN_MAIN_HEIGHT: u16 = 11;
+
+/// Where the panes are drawn, shared by rendering and mouse handling
+pub struct Panes {
+    pub header: Rect,
+    /// Everything between the header and the footer
+    pub main: Rect,
+    /// `None` while the output is expanded
+    pub editor: Option<Rect>,
+    /// The solution or the Frog panel next to the editor
+    pub side: Option<Rect>,
+    pub footer: Rect,
+    /// The side panel is above or below the editor
+    pub stacked: bool,
+}
+
+/// A border that can be dragged to resize the panes
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Divider {
+    /// Between the editor and the side panel
+    Side,
+    /// Above the output panel
+    Output,
+}
+
+/// Split the screen into the panes of a view
+pub fn panes(area: Rect, view_mode: ViewMode, show_frog: bool, config: &LayoutConfig) -> Panes {
+    let (header, main, footer) = if view_mode == ViewMode::ExpandedOutput {
+        expanded_layout(area)
+    } else {
+        main_layout(area, config.output_height)
+    };
+    let stacked = config.stacked || area.width < NARROW_WIDTH;
+
+    let (editor, side) = match view_mode {
+        ViewMode::ExpandedOutput => (None, None),
+        ViewMode::WithSolution => {
+            let (editor, side) = split_editors_layout(main, config, stacked);
+            (Some(editor), Some(side))
+        }
+        ViewMode::EditorOnly | ViewMode::HelpModal if show_frog => {
+            let (editor, side) = split_editors_layout(main, config, stacked);
+            (Some(editor), Some(side))
+        }
+        ViewMode::EditorOnly | ViewMode::HelpModal => (Some(main), None),
+    };
+
+    Panes {
+        header,
+        main,
+        editor,
+        side,
+        footer,
+        stacked,
+    }
+}
+
+impl Panes {
+    /// The divider under `pos`: the borders between the editor and the side panel,
+    /// or the top border of the output panel
+    pub fn divider_at(&self, pos: Position) -> Option<Divider> {
+        let editor = self.editor?;
+        if let Some(side) = self.side {
+            let (first, second) = if side.x < editor.x || side.y < editor.y {
+                (side, editor)
+            } else {
+                (editor, side)
+            };
+            let on_border = if self.stacked {
+                pos.y + 1 == first.bottom() || pos.y == second.y
+            } else {
+                pos.x + 1 == first.right() || pos.x == second.x
+            };
+            if on_border && self.main.contains(pos) {
+                return Some(Divider::Side);
+            }
+        }
+        (pos.y == self.footer.y).then_some(Divider::Output)
+    }
+
+    /// Side panel share in percent with the divider dragged to `pos`
+    pub fn side_percent_at(&self, pos: Position, swap_sides: bool) -> u16 {
+        let (start, len, at) = if self.stacked {
+            (self.main.y, self.main.height, pos.y)
+        } else {
+            (self.main.x, self.main.width, pos.x)
+        };
+        let first_len = at.saturating_sub(start) + 1;
+        let side_len = if swap_sides {
+            first_len
+        } else {
+            len.saturating_sub(first_len)
+        };
+        (side_len as u32 * 100 / len.max(1) as u32) as u16
+    }
+}
+
+/// Largest output panel leaving room for the header and the editor
+fn max_output_height(screen_height: u16) -> u16 {
+    screen_height
+        .saturating_sub(MIN_MAIN_HEIGHT)
+        .max(MIN_OUTPUT_HEIGHT)
+}
 
 /// Split the screen into header, main content, and output areas
-pub fn main_layout(area: Rect) -> (Rect, Rect, Rect) {
+pub fn main_layout(area: Rect, output_height: u16) -> (Rect, Rect, Rect) {
+    let output_height = output_height.clamp(MIN_OUTPUT_HEIGHT, max_output_height(area.height));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(1),  // Header
-            Constraint::Min(10),    // Main content
-            Constraint::Length(10), // Output + progress bar + status bar
+            Constraint::Length(1),             // Header
+            Constraint::Min(10),               // Main content
+            Constraint::Length(output_height), // Output + progress bar + status bar
         ])
         .split(area);
 
@@ -29,12 +151,106 @@ pub fn expanded_layout(area: Rect) -> (Rect, Rect, Rect) {
     (chunks[0], chunks[1], chunks[2])
 }
 
-/// Split main content for editor + solution mode
-pub fn split_editors_layout(area: Rect) -> (Rect, Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
+/// Split main content into the editor and the solution or Frog panel, returned in that order
+pub fn split_editors_layout(area: Rect, config: &LayoutConfig, stacked: bool) -> (Rect, Rect) {
+    let side_percent = config
+        .side_percent
+        .clamp(MIN_SIDE_PERCENT, MAX_SIDE_PERCENT);
+    let editor = Constraint::Percentage(100 - side_percent);
+    let side = Constraint::Percentage(side_percent);
+    let direction = if stacked {
+        Direction::Vertical
+    } else {
+        Direction::Horizontal
+    };
+
+    if config.swap_sides {
+        let chunks = Layout::default()
+            .direction(direction)
+            .constraints([side, editor])
+            .split(area);
+        (chunks[1], chunks[0])
+    } else {
+        let chunks = Layout::default()
+            .direction(direction)
+            .constraints([editor, side])
+            .split(area);
+        (chunks[0], chunks[1])
+    }
+}
+
+impl TuiState<'_> {
+    /// Set the share of the side panel in percent
+    pub fn set_side_percent(&mut self, percent: u16) {
+        self.config.layout.side_percent = percent.clamp(MIN_SIDE_PERCENT, MAX_SIDE_PERCENT);
+    }
+
+    /// Set the height of the output panel, limited by the terminal height
+    pub fn set_output_height(&mut self, height: u16) {
+        let max = crossterm::terminal::size().map_or(u16::MAX, |(_, h)| max_output_height(h));
+        self.config.layout.output_height = height.clamp(MIN_OUTPUT_HEIGHT, max);
+    }
+
+    /// `Ctrl+W` commands, the editor being the current window like in Vim:
+    /// `<`/`>` narrow/widen the editor, `+`/`-` make it taller/shorter than the output panel,
+    /// `x` swaps the editor and the side panel, `s`/`v` stack them or put them side by side
+    /// and `=` restores the default sizes.
+    pub fn window_command(&mut self, c: char) -> Result<()> {
+        let layout = self.config.layout;
+        match c {
+            '<' => self.set_side_percent(layout.side_percent + RESIZE_STEP),
+            '>' => self.set_side_percent(layout.side_percent.saturating_sub(RESIZE_STEP)),
+            '+' => self.set_output_height(layout.output_height.saturating_sub(RESIZE_ROWS)),
+            '-' => self.set_output_height(layout.output_height + RESIZE_ROWS),
+            'x' => self.config.layout.swap_sides = !layout.swap_sides,
+            's' => self.config.layout.stacked = true,
+            'v' => {
+                self.config.layout.stacked = false;
+                if crossterm::terminal::size().is_ok_and(|(width, _)| width < NARROW_WIDTH) {
+                    self.output = format!(
+                        "{} The panels stay stacked while the terminal is narrower than {NARROW_WIDTH} columns",
+                        theme::icons::INFO
+                    );
+                }
+            }
+            '=' => {
+                let default = LayoutConfig::default();
+                self.config.layout = LayoutConfig {
+                    side_percent: default.side_percent,
+                    output_height: default.output_height,
+                    ..layout
+                };
+            }
+            _ => return Ok(()),
+        }
+        self.config.write()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drag_the_side_divider() {
+        let area = Rect::new(0, 0, 120, 40);
+        let mut config = LayoutConfig::default();
+        let panes = panes(area, ViewMode::EditorOnly, true, &config);
+        let (editor, side) = (panes.editor.unwrap(), panes.side.unwrap());
+        assert_eq!((editor.width, side.x), (60, 60));
+        assert!(panes.divider_at(Position::new(59, 10)) == Some(Divider::Side));
+        assert!(
+            panes.divider_at(Position::new(panes.footer.x + 3, panes.footer.y))
+                == Some(Divider::Output)
+        );
+        assert!(panes.divider_at(Position::new(30, 10)).is_none());
+        assert_eq!(panes.side_percent_at(Position::new(89, 10), false), 25);
+        assert_eq!(panes.side_percent_at(Position::new(29, 10), true), 25);
 
-    (chunks[0], chunks[1])
+        // narrow terminals stack the panels, the side panel goes above the editor when swapped
+        config.swap_sides = true;
+        let panes = self::panes(Rect::new(0, 0, 80, 40), ViewMode::EditorOnly, true, &config);
+        assert!(panes.stacked);
+        assert!(panes.side.unwrap().bottom() == panes.editor.unwrap().y);
+    }
 }
@@ -0,0 +1,353 @@
+//! Slides of Frog lessons, their quizzes and code blocks.
+//!
+//! A slide becomes a quiz with a front-matter block at its top:
+//!
+//! ```text
+//! ---
+//! quiz: output
+//! choices:
+//!   - `5`
+//!   - `6`
+//! answer: 2
+//! explanation: The second `let` shadows the first `x`.
+//! ---
+//! let x = 5;
+//! ...
+//! ```
+//!
+//! The `quiz` key must come right after the opening `---`, otherwise the line is a
+//! Markdown thematic break. `quiz` is `choice`, `output` for "what does this print?" or `compile` for
+//! "will this compile?". `compile` quizzes have the fixed choices yes and no,
+//! their answer is `yes` or `no` instead of the number of a choice.
+//!
+//! Fenced code blocks with `run` in their info string, like ```` ```rust,run ````,
+//! can be compiled and run from the panel. `rustlings dev check` makes sure that
+//! the Rust blocks marked with `run` or `check` compile, and that the ones marked
+//! with `compile_fail` don't.
+
+use anyhow::{Context, Result, bail};
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+/// Separates the slides of a lesson
+pub const SLIDE_SEPARATOR: &str = "--- slide ---";
+const FRONT_MATTER_DELIMITER: &str = "---";
+/// Choices are picked with the number keys 1 to 9
+pub const MAX_CHOICES: usize = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuizKind {
+    Choice,
+    Output,
+    Compile,
+}
+
+impl QuizKind {
+    /// Question shown above the slide
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Choice => "Quiz",
+            Self::Output => "What does this print?",
+            Self::Compile => "Will this compile?",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Quiz {
+    pub kind: QuizKind,
+    /// Markdown of each choice
+    pub choices: Vec<String>,
+    /// Index of the right choice
+    pub answer: usize,
+    /// Markdown shown once the quiz is answered
+    pub explanation: Option<String>,
+}
+
+/// What compiling a code block should result in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompileCheck {
+    /// Not compiled by `rustlings dev check`
+    None,
+    Compiles,
+    Fails,
+}
+
+/// A code block of a slide, in the order they are rendered
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CodeBlock {
+    /// First word of the info string, empty for unlabeled and indented blocks
+    pub lang: String,
+    pub code: String,
+    /// Marked with `run` to be compiled and run from the panel
+    pub runnable: bool,
+    pub compile_check: CompileCheck,
+}
+
+impl CodeBlock {
+    /// An empty block with the language and marks of a fenced block's info string
+    pub fn from_info(info: &str) -> Self {
+        let mut words = info.split(',').map(str::trim);
+        let lang = words.next().unwrap_or("").to_string();
+        let (mut runnable, mut check, mut compile_fail) = (false, false, false);
+        for word in words {
+            match word {
+                "run" => runnable = true,
+                "check" => check = true,
+                "compile_fail" => compile_fail = true,
+                _ => (),
+            }
+        }
+
+        let compile_check = if compile_fail {
+            CompileCheck::Fails
+        } else if runnable || check {
+            CompileCheck::Compiles
+        } else {
+            CompileCheck::None
+        };
+        Self {
+            lang,
+            code: String::new(),
+            runnable,
+            compile_check,
+        }
+    }
+
+    /// Unlabeled blocks are mostly Rust
+    pub fn is_rust(&self) -> bool {
+        matches!(self.lang.as_str(), "" | "rust" | "rs")
+    }
+}
+
+/// The result of compiling and running a runnable code block
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CodeRun {
+    pub success: bool,
+    /// Compiler and program output
+    pub output: String,
+}
+
+/// A slide of a Frog lesson
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Slide {
+    /// Markdown of the slide without its front-matter
+    pub body: String,
+    pub quiz: Option<Quiz>,
+    pub code_blocks: Vec<CodeBlock>,
+}
+
+impl Slide {
+    /// A slide without a quiz
+    pub fn text(body: String) -> Self {
+        let code_blocks = code_blocks(&body);
+        Self {
+            body,
+            quiz: None,
+            code_blocks,
+        }
+    }
+}
+
+/// The code blocks of a markdown text
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open = None;
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                open = Some(match kind {
+                    CodeBlockKind::Fenced(info) => CodeBlock::from_info(&info),
+                    CodeBlockKind::Indented => CodeBlock::from_info(""),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(block) = &mut open {
+                    block.code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => blocks.extend(open.take()),
+            _ => (),
+        }
+    }
+    blocks
+}
+
+/// Parse a slide and the quiz in its front-matter if it has one
+pub fn parse_slide(text: &str) -> Result<Slide> {
+    let text = text.trim();
+    let lines: Vec<&str> = text.lines().collect();
+    // a slide can also start with a thematic break
+    let is_front_matter = lines.first().map(|line| line.trim_end()) == Some(FRONT_MATTER_DELIMITER)
+        && lines
+            .get(1)
+            .is_some_and(|line| line.trim_start().starts_with("quiz:"));
+    if !is_front_matter {
+        return Ok(Slide::text(text.to_string()));
+    }
+    let Some(end) = lines
+        .iter()
+        .skip(1)
+        .position(|line| line.trim_end() == FRONT_MATTER_DELIMITER)
+    else {
+        bail!("The front-matter isn't closed with a `{FRONT_MATTER_DELIMITER}` line");
+    };
+
+    let quiz = parse_front_matter(&lines[1..=end])?;
+    let body = lines[end + 2..].join("\n").trim().to_string();
+    Ok(Slide {
+        quiz: Some(quiz),
+        ..Slide::text(body)
+    })
+}
+
+fn parse_front_matter(lines: &[&str]) -> Result<Quiz> {
+    let mut kind = None;
+    let mut choices = Vec::new();
+    let mut answer = None;
+    let mut explanation = None;
+
+    for line in lines.iter().map(|line| line.trim()) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(choice) = line.strip_prefix("- ") {
+            choices.push(choice.trim().to_string());
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            bail!("Expected `key: value` in the front-matter, found `{line}`");
+        };
+        let value = value.trim();
+        match key.trim() {
+            "quiz" => {
+                kind = Some(match value {
+                    "choice" => QuizKind::Choice,
+                    "output" => QuizKind::Output,
+                    "compile" => QuizKind::Compile,
+                    _ => bail!(
+                        "Unknown quiz type `{value}`, expected `choice`, `output` or `compile`"
+                    ),
+                });
+            }
+            // the choices follow as a list
+            "choices" => (),
+            "answer" => answer = Some(value),
+            "explanation" => explanation = Some(value.to_string()),
+            key => bail!("Unknown front-matter key `{key}`"),
+        }
+    }
+
+    let kind = kind.context("The front-matter has no `quiz` type")?;
+    let answer = answer.context("The quiz has no `answer`")?;
+
+    let answer = if kind == QuizKind::Compile {
+        if !choices.is_empty() {
+            bail!("The choices of a `compile` quiz are always yes and no");
+        }
+        choices = vec!["Yes, it compiles".to_string(), "No, it doesn't".to_string()];
+        match answer {
+            "yes" => 0,
+            "no" => 1,
+            _ => bail!("The answer of a `compile` quiz is `yes` or `no`"),
+        }
+    } else {
+        if !(2..=MAX_CHOICES).contains(&choices.len()) {
+            bail!("A quiz needs 2 to {MAX_CHOICES} choices");
+        }
+        answer
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=choices.len()).contains(n))
+            .with_context(|| {
+                format!(
+                    "The answer must be the number of a choice from 1 to {}",
+                    choices.len()
+                )
+            })?
+            - 1
+    };
+
+    Ok(Quiz {
+        kind,
+        choices,
+        answer,
+        explanation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiz_front_matter() {
+        let slide = parse_slide(
+            "---\nquiz: output\nchoices:\n  - `5`\n  - `6`\nanswer: 2\n\
+             explanation: Shadowing: a new `x`.\n---\n\n```rust\nlet x = 5;\n```\n",
+        )
+        .unwrap();
+        assert_eq!(slide.body, "```rust\nlet x = 5;\n```");
+        let quiz = slide.quiz.unwrap();
+        assert_eq!(quiz.kind, QuizKind::Output);
+        assert_eq!(quiz.choices, ["`5`", "`6`"]);
+        assert_eq!(quiz.answer, 1);
+        assert_eq!(quiz.explanation.as_deref(), Some("Shadowing: a new `x`."));
+
+        let quiz = parse_slide("---\nquiz: compile\nanswer: no\n---\nlet x;")
+            .unwrap()
+            .quiz
+            .unwrap();
+        assert_eq!((quiz.choices.len(), quiz.answer), (2, 1));
+
+        assert_eq!(parse_slide("# Title\n\n---\n").unwrap().quiz, None);
+    }
+
+    #[test]
+    fn thematic_break_at_the_top() {
+        let slide = parse_slide("---\n\n# Title\n\nText\n\n---\n").unwrap();
+        assert_eq!(slide.quiz, None);
+        assert_eq!(slide.body, "---\n\n# Title\n\nText\n\n---");
+    }
+
+    #[test]
+    fn runnable_code_blocks() {
+        let slide = parse_slide(
+            "```rust,run\nlet v = vec![1];\n```\n\n> ```\n> plain\n> ```\n\n```text\nout\n```\n\n\
+             ```rust,run,compile_fail\nlet x = 1;\nx = 2;\n```",
+        )
+        .unwrap();
+        let blocks: Vec<_> = slide
+            .code_blocks
+            .iter()
+            .map(|block| {
+                (
+                    block.lang.as_str(),
+                    block.code.as_str(),
+                    block.runnable,
+                    block.compile_check,
+                )
+            })
+            .collect();
+        assert_eq!(
+            blocks,
+            [
+                ("rust", "let v = vec![1];\n", true, CompileCheck::Compiles),
+                ("", "plain\n", false, CompileCheck::None),
+                ("text", "out\n", false, CompileCheck::None),
+                ("rust", "let x = 1;\nx = 2;\n", true, CompileCheck::Fails),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_quizzes() {
+        let error = |text: &str| parse_slide(text).unwrap_err().to_string();
+        assert!(error("---\nquiz: choice\n").contains("isn't closed"));
+        assert!(error("---\nquiz: essay\n---").contains("Unknown quiz type"));
+        assert!(error("---\nquiz: choice\n- a\n- b\n---").contains("no `answer`"));
+        assert!(error("---\nquiz: choice\n- a\n- b\nanswer: 3\n---").contains("from 1 to 2"));
+        assert!(error("---\nquiz: choice\n- a\nanswer: 1\n---").contains("2 to 9 choices"));
+        assert!(error("---\nquiz: compile\nanswer: 1\n---").contains("`yes` or `no`"));
+    }
+}
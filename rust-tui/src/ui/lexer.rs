@@ -0,0 +1,460 @@
+//! Line-based Rust tokenizer for syntax highlighting.
+//! The state at the end of a line is carried to the next one
+//! for block comments and strings spanning multiple lines.
+
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    Type,
+    Macro,
+    /// Only known from rust-analyzer's semantic tokens
+    Function,
+    Lifetime,
+    Attribute,
+    Comment,
+    DocComment,
+    String,
+    Escape,
+    Char,
+    Number,
+    Punct,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte range in the line
+    pub range: Range<usize>,
+}
+
+/// What the next line starts in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LexState {
+    #[default]
+    Code,
+    /// Block comments nest in Rust
+    BlockComment { depth: u32, doc: bool },
+    /// `raw_hashes` is the number of `#` of a raw string (`None` for normal strings)
+    Str { raw_hashes: Option<u8> },
+}
+
+pub const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+pub const PRIMITIVES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64", "bool", "char", "str",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+pub fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+struct LineLexer<'a> {
+    line: &'a str,
+    pos: usize,
+    tokens: Vec<Token>,
+}
+
+impl<'a> LineLexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.line[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn bump_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+    }
+
+    // merge with the previous token of the same kind to keep the span count low
+    fn push(&mut self, kind: TokenKind, start: usize) {
+        if start == self.pos {
+            return;
+        }
+        match self.tokens.last_mut() {
+            Some(last) if last.kind == kind && last.range.end == start => last.range.end = self.pos,
+            _ => self.tokens.push(Token {
+                kind,
+                range: start..self.pos,
+            }),
+        }
+    }
+
+    // `start` is where the comment token begins (before an opening `/*` already skipped)
+    fn block_comment(&mut self, start: usize, mut depth: u32, doc: bool) -> LexState {
+        let kind = if doc {
+            TokenKind::DocComment
+        } else {
+            TokenKind::Comment
+        };
+        while let Some(c) = self.peek() {
+            if self.rest().starts_with("/*") {
+                depth += 1;
+                self.pos += 2;
+            } else if self.rest().starts_with("*/") {
+                self.pos += 2;
+                depth -= 1;
+                if depth == 0 {
+                    self.push(kind, start);
+                    return LexState::Code;
+                }
+            } else {
+                self.pos += c.len_utf8();
+            }
+        }
+        self.push(kind, start);
+        LexState::BlockComment { depth, doc }
+    }
+
+    // `start` is where the string token begins (before an opening quote already skipped)
+    fn string(&mut self, mut start: usize, raw_hashes: Option<u8>) -> LexState {
+        while let Some(c) = self.peek() {
+            match (c, raw_hashes) {
+                ('\\', None) => {
+                    self.push(TokenKind::String, start);
+                    start = self.pos;
+                    self.escape();
+                    self.push(TokenKind::Escape, start);
+                    start = self.pos;
+                }
+                ('"', _) => {
+                    let hashes = usize::from(raw_hashes.unwrap_or(0));
+                    let closing = self.rest()[1..].chars().take_while(|&c| c == '#').count();
+                    self.bump();
+                    if closing >= hashes {
+                        self.pos += hashes;
+                        self.push(TokenKind::String, start);
+                        return LexState::Code;
+                    }
+                }
+                _ => self.bump(),
+            }
+        }
+        self.push(TokenKind::String, start);
+        LexState::Str { raw_hashes }
+    }
+
+    // `\n`, `\x41`, `\u{1F980}`, or `\` at the end of a line
+    fn escape(&mut self) {
+        self.bump();
+        match self.peek() {
+            Some('x') => {
+                self.bump();
+                for _ in 0..2 {
+                    if self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                        self.bump();
+                    }
+                }
+            }
+            Some('u') if self.peek_nth(1) == Some('{') => {
+                self.bump_while(|c| c != '}' && c != '"');
+                if self.peek() == Some('}') {
+                    self.bump();
+                }
+            }
+            Some(_) => self.bump(),
+            None => {}
+        }
+    }
+
+    // `'` starts a char literal or a lifetime
+    fn quote(&mut self, start: usize) {
+        self.bump();
+        // `'\n'` and `'x'` but not `'a` or `'static`
+        let is_char = matches!(
+            (self.peek(), self.peek_nth(1)),
+            (Some('\\'), _) | (Some(_), Some('\''))
+        );
+
+        if is_char {
+            if self.peek() == Some('\\') {
+                self.push(TokenKind::Char, start);
+                let escape_start = self.pos;
+                self.escape();
+                self.push(TokenKind::Escape, escape_start);
+                let rest_start = self.pos;
+                self.bump_while(|c| c != '\'');
+                self.bump();
+                self.push(TokenKind::Char, rest_start);
+            } else {
+                self.bump();
+                self.bump();
+                self.push(TokenKind::Char, start);
+            }
+        } else if self.peek().is_some_and(is_ident_start) {
+            self.bump_while(is_ident_char);
+            self.push(TokenKind::Lifetime, start);
+        } else {
+            self.push(TokenKind::Punct, start);
+        }
+    }
+
+    // `#[...]` or `#![...]` up to the matching bracket on the same line
+    fn attribute(&mut self, start: usize) {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.bump();
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.push(TokenKind::Attribute, start);
+    }
+
+    fn number(&mut self, start: usize) {
+        while let Some(c) = self.peek() {
+            // `1.5` but not the range `1..2` or the method call `1.max(2)`
+            let is_fraction =
+                c == '.' && self.peek_nth(1).is_some_and(|next| next.is_ascii_digit());
+            if !(is_ident_char(c) || is_fraction) {
+                break;
+            }
+            self.bump();
+        }
+        self.push(TokenKind::Number, start);
+    }
+
+    fn ident(&mut self, start: usize) {
+        self.bump_while(is_ident_char);
+        let word = &self.line[start..self.pos];
+
+        let kind = if self.peek() == Some('!') && self.peek_nth(1) != Some('=') {
+            self.bump();
+            TokenKind::Macro
+        } else if KEYWORDS.contains(&word) {
+            TokenKind::Keyword
+        } else if PRIMITIVES.contains(&word) || word.starts_with(char::is_uppercase) {
+            TokenKind::Type
+        } else {
+            TokenKind::Plain
+        };
+        self.push(kind, start);
+    }
+
+    // string literal prefixes: `b"`, `c"`, `r"`, `r#"`, `br#"`, …
+    fn string_prefix(&self) -> Option<(usize, Option<u8>)> {
+        let rest = self.rest();
+        let after_prefix = rest
+            .strip_prefix("br")
+            .or_else(|| rest.strip_prefix("cr"))
+            .or_else(|| rest.strip_prefix('r'));
+        if let Some(after) = after_prefix {
+            let hashes = after.chars().take_while(|&c| c == '#').count();
+            if after[hashes..].starts_with('"') {
+                let prefix_len = rest.len() - after.len() + hashes + 1;
+                return Some((prefix_len, Some(hashes.min(255) as u8)));
+            }
+        }
+        if rest.starts_with("b\"") || rest.starts_with("c\"") {
+            return Some((2, None));
+        }
+        None
+    }
+
+    fn code(&mut self) -> LexState {
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            let rest = self.rest();
+
+            if rest.starts_with("//") {
+                // `///` and `//!` but not `////`
+                let doc = (rest.starts_with("///") && !rest.starts_with("////"))
+                    || rest.starts_with("//!");
+                self.pos = self.line.len();
+                let kind = if doc {
+                    TokenKind::DocComment
+                } else {
+                    TokenKind::Comment
+                };
+                self.push(kind, start);
+            } else if rest.starts_with("/*") {
+                let doc = (rest.starts_with("/**") && !rest.starts_with("/**/"))
+                    || rest.starts_with("/*!");
+                self.pos += 2;
+                let state = self.block_comment(start, 1, doc);
+                if state != LexState::Code {
+                    return state;
+                }
+            } else if let Some((prefix_len, raw_hashes)) = self.string_prefix() {
+                self.pos += prefix_len;
+                let state = self.string(start, raw_hashes);
+                if state != LexState::Code {
+                    return state;
+                }
+            } else if c == '"' {
+                self.bump();
+                let state = self.string(start, None);
+                if state != LexState::Code {
+                    return state;
+                }
+            } else if rest.starts_with("b'") {
+                self.bump();
+                self.quote(start);
+            } else if c == '\'' {
+                self.quote(start);
+            } else if rest.starts_with("#[") || rest.starts_with("#![") {
+                self.attribute(start);
+            } else if c.is_ascii_digit() {
+                self.number(start);
+            } else if is_ident_start(c) {
+                self.ident(start);
+            } else if c.is_whitespace() {
+                self.bump_while(char::is_whitespace);
+                self.push(TokenKind::Plain, start);
+            } else {
+                self.bump();
+                self.push(TokenKind::Punct, start);
+            }
+        }
+        LexState::Code
+    }
+}
+
+/// Split a line into tokens covering all of it.
+/// Returns the tokens and the state the next line starts in.
+pub fn tokenize_line(line: &str, state: LexState) -> (Vec<Token>, LexState) {
+    let mut lexer = LineLexer {
+        line,
+        pos: 0,
+        tokens: Vec::new(),
+    };
+
+    let mut state = match state {
+        LexState::Code => LexState::Code,
+        LexState::BlockComment { depth, doc } => lexer.block_comment(0, depth, doc),
+        LexState::Str { raw_hashes } => lexer.string(0, raw_hashes),
+    };
+    if state == LexState::Code {
+        state = lexer.code();
+    }
+
+    (lexer.tokens, state)
+}
+
+/// The state after the given lines, e.g. at the first visible line of a scrolled view
+pub fn state_after<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> LexState {
+    lines.into_iter().fold(LexState::Code, |state, line| {
+        tokenize_line(line.as_ref(), state).1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str, state: LexState) -> Vec<(TokenKind, &str)> {
+        tokenize_line(line, state)
+            .0
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Plain)
+            .map(|token| (token.kind, &line[token.range]))
+            .collect()
+    }
+
+    #[test]
+    fn lifetimes_chars_and_macros() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(
+                "fn f<'a>(c: char) { println!(\"{c}\\n\", '\\''); }",
+                LexState::Code
+            ),
+            [
+                (Keyword, "fn"),
+                (Punct, "<"),
+                (Lifetime, "'a"),
+                (Punct, ">("),
+                (Punct, ":"),
+                (Type, "char"),
+                (Punct, ")"),
+                (Punct, "{"),
+                (Macro, "println!"),
+                (Punct, "("),
+                (String, "\"{c}"),
+                (Escape, "\\n"),
+                (String, "\""),
+                (Punct, ","),
+                (Char, "'"),
+                (Escape, "\\'"),
+                (Char, "'"),
+                (Punct, ");"),
+                (Punct, "}"),
+            ],
+        );
+    }
+
+    #[test]
+    fn attributes_and_doc_comments() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("#[derive(Debug)] /// doc", LexState::Code),
+            [(Attribute, "#[derive(Debug)]"), (DocComment, "/// doc")],
+        );
+        assert_eq!(
+            kinds("//// not doc", LexState::Code),
+            [(Comment, "//// not doc")]
+        );
+    }
+
+    #[test]
+    fn state_carries_across_lines() {
+        let (_, state) = tokenize_line("let s = r#\"a \" b", LexState::Code);
+        assert_eq!(
+            state,
+            LexState::Str {
+                raw_hashes: Some(1)
+            }
+        );
+        assert_eq!(
+            kinds("c\"# + 1", state),
+            [
+                (TokenKind::String, "c\"#"),
+                (TokenKind::Punct, "+"),
+                (TokenKind::Number, "1"),
+            ],
+        );
+
+        let state = state_after(["/* outer /* inner */", "still comment"]);
+        assert_eq!(
+            state,
+            LexState::BlockComment {
+                depth: 1,
+                doc: false
+            }
+        );
+        assert_eq!(state_after(["/* a /* b */ */ let"]), LexState::Code);
+    }
+}
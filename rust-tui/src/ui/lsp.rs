@@ -0,0 +1,863 @@
+//! Optional rust-analyzer client speaking the language server protocol over stdio.
+//! It provides hover, go-to-definition, completion, live diagnostics and semantic
+//! highlighting for the open exercise. Without rust-analyzer the editor works as before.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+};
+
+use anyhow::{Context, Result, bail};
+use ropey::Rope;
+use serde_json::{Value, json};
+
+use crate::ui::{
+    completion::{CompletionItem, CompletionMenu},
+    lexer::{self, TokenKind},
+    state::{EditorMode, TuiState},
+    theme,
+};
+
+// the most completion items kept from one response
+const MAX_COMPLETION_ITEMS: usize = 200;
+// JSON-RPC error code of an unsupported request
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// An LSP position: line and UTF-16 column
+pub type LspPos = (usize, usize);
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+pub struct Diagnostic {
+    pub start: LspPos,
+    pub end: LspPos,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// A token classified by rust-analyzer, `start` and `len` in UTF-16 units of its line
+pub struct SemanticToken {
+    pub start: usize,
+    pub len: usize,
+    pub kind: TokenKind,
+}
+
+/// Answers to requests made by the editor
+enum Response {
+    Hover(Option<String>),
+    Definition(Option<(String, LspPos)>),
+    Completion(Vec<CompletionItem>),
+}
+
+enum Pending {
+    Initialize,
+    Hover,
+    Definition,
+    Completion,
+    // document version the tokens were requested for
+    SemanticTokens(i32),
+}
+
+pub struct LspClient {
+    child: Child,
+    // written by a thread, a busy server that doesn't read its input doesn't block the editor
+    outgoing: Sender<Value>,
+    messages: Receiver<Value>,
+    next_id: u64,
+    pending: HashMap<u64, Pending>,
+    initialized: bool,
+    // messages sent before the server answered `initialize`
+    queue: Vec<Value>,
+    token_types: Vec<String>,
+    // the open document
+    uri: String,
+    path: PathBuf,
+    version: i32,
+    synced: Rope,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Semantic tokens of each line
+    pub semantic_tokens: Vec<Vec<SemanticToken>>,
+}
+
+/// Read one message framed by a `Content-Length` header.
+/// `None` once the server closed its output.
+fn read_message(reader: &mut impl BufRead) -> Option<Vec<u8>> {
+    let mut len = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            len = value.trim().parse().ok();
+        }
+    }
+
+    let mut body = vec![0; len?];
+    reader.read_exact(&mut body).ok()?;
+    Some(body)
+}
+
+/// Answer to a request from the server. The settings asked for with `workspace/configuration`
+/// keep their defaults, one `null` for each item. Other requests aren't supported.
+fn reply(id: &Value, method: &str, params: &Value) -> Value {
+    if method == "workspace/configuration" {
+        let items = params["items"].as_array().map_or(0, Vec::len);
+        return json!({ "jsonrpc": "2.0", "id": id, "result": vec![Value::Null; items] });
+    }
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": METHOD_NOT_FOUND, "message": format!("`{method}` isn't supported") },
+    })
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // Windows paths start with a drive letter
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    uri
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let decoded = (encoded[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(encoded[i]);
+                i += 1;
+            }
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+    // `/C:/…` on Windows
+    let path = if cfg!(windows) {
+        path.trim_start_matches('/').to_string()
+    } else {
+        path
+    };
+    Some(PathBuf::from(path))
+}
+
+fn position(value: &Value) -> LspPos {
+    let get = |key| value[key].as_u64().unwrap_or(0) as usize;
+    (get("line"), get("character"))
+}
+
+/// Plain text of a hover result, without the code fences around signatures
+fn hover_text(result: &Value) -> Option<String> {
+    let contents = &result["contents"];
+    let part = |value: &Value| {
+        value
+            .as_str()
+            .or_else(|| value["value"].as_str())
+            .map(str::to_string)
+    };
+    let text = match contents.as_array() {
+        Some(parts) => parts
+            .iter()
+            .filter_map(part)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        None => part(contents)?,
+    };
+
+    let text = text
+        .lines()
+        .filter(|line| !line.starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+// classes we highlight differently than the lexer guesses
+fn semantic_kind(token_type: &str) -> Option<TokenKind> {
+    let kind = match token_type {
+        "function" | "method" => TokenKind::Function,
+        "struct" | "enum" | "union" | "interface" | "typeAlias" | "typeParameter"
+        | "builtinType" | "selfTypeKeyword" | "enumMember" => TokenKind::Type,
+        "macro" => TokenKind::Macro,
+        "lifetime" => TokenKind::Lifetime,
+        "variable" | "parameter" | "property" | "selfKeyword" => TokenKind::Plain,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Decode the relative `[line, start, length, type, modifiers]` quintuples of a response
+fn decode_semantic_tokens(data: &[Value], token_types: &[String]) -> Vec<Vec<SemanticToken>> {
+    let mut lines: Vec<Vec<SemanticToken>> = Vec::new();
+    let (mut line, mut start) = (0, 0);
+    for quintuple in data.chunks_exact(5) {
+        let [delta_line, delta_start, len, token_type, _] =
+            [0, 1, 2, 3, 4].map(|i| quintuple[i].as_u64().unwrap_or(0) as usize);
+        if delta_line > 0 {
+            line += delta_line;
+            start = 0;
+        }
+        start += delta_start;
+
+        let Some(kind) = token_types
+            .get(token_type)
+            .and_then(|name| semantic_kind(name))
+        else {
+            continue;
+        };
+        if lines.len() <= line {
+            lines.resize_with(line + 1, Vec::new);
+        }
+        lines[line].push(SemanticToken { start, len, kind });
+    }
+    lines
+}
+
+fn completion_items(result: &Value) -> Vec<CompletionItem> {
+    let items = result
+        .as_array()
+        .or_else(|| result["items"].as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut items: Vec<(&str, CompletionItem)> = items
+        .iter()
+        .filter_map(|item| {
+            let label = item["label"].as_str()?.to_string();
+            let insert = item["textEdit"]["newText"]
+                .as_str()
+                .or_else(|| item["insertText"].as_str())
+                .unwrap_or(&label)
+                .to_string();
+            let sort_text = item["sortText"].as_str().unwrap_or_default();
+            let detail = item["detail"].as_str().map(str::to_string);
+            Some((
+                sort_text,
+                CompletionItem {
+                    label,
+                    detail,
+                    insert,
+                },
+            ))
+        })
+        .collect();
+    items.sort_by_key(|(sort_text, _)| *sort_text);
+    items.truncate(MAX_COMPLETION_ITEMS);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+impl LspClient {
+    /// Spawn rust-analyzer for the workspace at `root`.
+    /// Fails if it isn't installed.
+    pub fn start(root: &Path) -> Result<Self> {
+        let mut child = Command::new("rust-analyzer")
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run `rust-analyzer`. Is it installed? (`rustup component add rust-analyzer`)")?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("Failed to open the stdin of `rust-analyzer`")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("Failed to open the stdout of `rust-analyzer`")?;
+
+        // the channel disconnects when the server exits
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(body) = read_message(&mut reader) {
+                let Ok(message) = serde_json::from_slice(&body) else {
+                    continue;
+                };
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (outgoing, to_write) = mpsc::channel::<Value>();
+        thread::spawn(move || {
+            for message in to_write {
+                let body = message.to_string();
+                let written = write!(stdin, "Content-Length: {}\r\n\r\n{body}", body.len())
+                    .and_then(|()| stdin.flush());
+                if written.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self {
+            child,
+            outgoing,
+            messages,
+            next_id: 0,
+            pending: HashMap::new(),
+            initialized: false,
+            queue: Vec::new(),
+            token_types: Vec::new(),
+            uri: String::new(),
+            path: PathBuf::new(),
+            version: 0,
+            synced: Rope::new(),
+            diagnostics: Vec::new(),
+            semantic_tokens: Vec::new(),
+        };
+
+        let root_uri = path_to_uri(root);
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": "rustlings" }],
+            "capabilities": {
+                "textDocument": {
+                    "hover": { "contentFormat": ["plaintext"] },
+                    "definition": {},
+                    "completion": { "completionItem": { "snippetSupport": false } },
+                    "publishDiagnostics": {},
+                    "semanticTokens": {
+                        "requests": { "full": true },
+                        "tokenTypes": [],
+                        "tokenModifiers": [],
+                        "formats": ["relative"],
+                    },
+                },
+            },
+            // live diagnostics come from rust-analyzer itself, not from building the exercises
+            "initializationOptions": {
+                "checkOnSave": false,
+                "cargo": { "buildScripts": { "enable": false } },
+                "procMacro": { "enable": false },
+            },
+        });
+        client.request("initialize", params, Pending::Initialize)?;
+        Ok(client)
+    }
+
+    /// Whether answers are expected soon
+    pub fn is_busy(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    // the writing thread stops once the server closed its input
+    fn write(&self, message: Value) -> Result<()> {
+        self.outgoing
+            .send(message)
+            .ok()
+            .context("Failed to write to `rust-analyzer`")
+    }
+
+    // everything but `initialize` waits for the server to be initialized
+    fn send(&mut self, message: Value) -> Result<()> {
+        if self.initialized || message["method"] == "initialize" {
+            self.write(message)
+        } else {
+            self.queue.push(message);
+            Ok(())
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    fn request(&mut self, method: &str, params: Value, kind: Pending) -> Result<()> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.pending.insert(id, kind);
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+    }
+
+    fn position_params(&self, (line, character): LspPos) -> Value {
+        json!({
+            "textDocument": { "uri": self.uri },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    fn request_semantic_tokens(&mut self) -> Result<()> {
+        let already_requested = self
+            .pending
+            .values()
+            .any(|kind| matches!(kind, Pending::SemanticTokens(_)));
+        if already_requested {
+            return Ok(());
+        }
+        let params = json!({ "textDocument": { "uri": self.uri } });
+        self.request(
+            "textDocument/semanticTokens/full",
+            params,
+            Pending::SemanticTokens(self.version),
+        )
+    }
+
+    /// Open `path` with the text of the editor, closing the previously open file
+    pub fn open(&mut self, path: &Path, text: &Rope) -> Result<()> {
+        self.close()?;
+
+        self.path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.uri = path_to_uri(&self.path);
+        self.version = 0;
+        self.synced = text.clone();
+        self.diagnostics.clear();
+        self.semantic_tokens.clear();
+
+        let params = json!({
+            "textDocument": {
+                "uri": self.uri,
+                "languageId": "rust",
+                "version": self.version,
+                "text": text.to_string(),
+            },
+        });
+        self.notify("textDocument/didOpen", params)?;
+        self.request_semantic_tokens()
+    }
+
+    /// Close the open file, e.g. while the editor shows a file that isn't Rust code
+    pub fn close(&mut self) -> Result<()> {
+        if self.uri.is_empty() {
+            return Ok(());
+        }
+        let params = json!({ "textDocument": { "uri": self.uri } });
+        self.notify("textDocument/didClose", params)?;
+        self.uri.clear();
+        self.diagnostics.clear();
+        self.semantic_tokens.clear();
+        Ok(())
+    }
+
+    /// Send the editor's text if it changed since the last sync
+    pub fn sync(&mut self, text: &Rope) -> Result<()> {
+        if self.uri.is_empty() || *text == self.synced {
+            return Ok(());
+        }
+
+        self.version += 1;
+        self.synced = text.clone();
+        let params = json!({
+            "textDocument": { "uri": self.uri, "version": self.version },
+            "contentChanges": [{ "text": text.to_string() }],
+        });
+        self.notify("textDocument/didChange", params)?;
+        self.request_semantic_tokens()
+    }
+
+    fn hover(&mut self, pos: LspPos) -> Result<()> {
+        let params = self.position_params(pos);
+        self.request("textDocument/hover", params, Pending::Hover)
+    }
+
+    fn definition(&mut self, pos: LspPos) -> Result<()> {
+        let params = self.position_params(pos);
+        self.request("textDocument/definition", params, Pending::Definition)
+    }
+
+    fn completion(&mut self, pos: LspPos) -> Result<()> {
+        let params = self.position_params(pos);
+        self.request("textDocument/completion", params, Pending::Completion)
+    }
+
+    fn is_open_file(&self, uri: &str) -> bool {
+        uri == self.uri
+            || uri_to_path(uri)
+                .and_then(|path| path.canonicalize().ok())
+                .is_some_and(|path| path == self.path)
+    }
+
+    /// Handle the messages received so far.
+    /// Fails if the server exited.
+    fn poll(&mut self) -> Result<Vec<Response>> {
+        let mut responses = Vec::new();
+        loop {
+            let message = match self.messages.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => return Ok(responses),
+                // rustup's proxy exits right away if the component isn't installed
+                Err(TryRecvError::Disconnected) if !self.initialized => bail!(
+                    "rust-analyzer exited. Is it installed? (`rustup component add rust-analyzer`)"
+                ),
+                Err(TryRecvError::Disconnected) => bail!("rust-analyzer exited"),
+            };
+
+            // the server's own requests may have string IDs
+            if let (false, Some(method)) = (message["id"].is_null(), message["method"].as_str()) {
+                self.write(reply(&message["id"], method, &message["params"]))?;
+                continue;
+            }
+            match (message["id"].as_u64(), message["method"].as_str()) {
+                (None, Some("textDocument/publishDiagnostics")) => {
+                    self.update_diagnostics(&message["params"])
+                }
+                (Some(id), None) => {
+                    let Some(kind) = self.pending.remove(&id) else {
+                        continue;
+                    };
+                    if let Some(response) = self.handle_response(kind, &message["result"])? {
+                        responses.push(response);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_response(&mut self, kind: Pending, result: &Value) -> Result<Option<Response>> {
+        let response = match kind {
+            Pending::Initialize => {
+                let legend = &result["capabilities"]["semanticTokensProvider"]["legend"];
+                self.token_types = legend["tokenTypes"]
+                    .as_array()
+                    .map(|types| {
+                        types
+                            .iter()
+                            .map(|name| name.as_str().unwrap_or_default().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                self.initialized = true;
+                self.notify("initialized", json!({}))?;
+                for message in std::mem::take(&mut self.queue) {
+                    self.write(message)?;
+                }
+                return Ok(None);
+            }
+            Pending::SemanticTokens(version) => {
+                if let Some(data) = result["data"].as_array() {
+                    self.semantic_tokens = decode_semantic_tokens(data, &self.token_types);
+                }
+                // the text changed while rust-analyzer was busy
+                if version != self.version {
+                    self.request_semantic_tokens()?;
+                }
+                return Ok(None);
+            }
+            Pending::Hover => Response::Hover(hover_text(result)),
+            Pending::Definition => {
+                // a location, a list of locations or a list of location links
+                let location = result
+                    .as_array()
+                    .and_then(|list| list.first())
+                    .unwrap_or(result);
+                let uri = location["uri"].as_str().or(location["targetUri"].as_str());
+                let range = if location["targetSelectionRange"].is_object() {
+                    &location["targetSelectionRange"]
+                } else {
+                    &location["range"]
+                };
+                Response::Definition(uri.map(|uri| (uri.to_string(), position(&range["start"]))))
+            }
+            Pending::Completion => Response::Completion(completion_items(result)),
+        };
+        Ok(Some(response))
+    }
+
+    fn update_diagnostics(&mut self, params: &Value) {
+        if !params["uri"]
+            .as_str()
+            .is_some_and(|uri| self.is_open_file(uri))
+        {
+            return;
+        }
+
+        let diagnostics = params["diagnostics"].as_array().map(Vec::as_slice);
+        self.diagnostics = diagnostics
+            .unwrap_or_default()
+            .iter()
+            .map(|diagnostic| Diagnostic {
+                start: position(&diagnostic["range"]["start"]),
+                end: position(&diagnostic["range"]["end"]),
+                severity: match diagnostic["severity"].as_u64() {
+                    Some(1) => Severity::Error,
+                    Some(2) => Severity::Warning,
+                    _ => Severity::Info,
+                },
+                message: diagnostic["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+            .collect();
+        self.diagnostics
+            .sort_by_key(|diagnostic| diagnostic.severity);
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl TuiState<'_> {
+    /// Start rust-analyzer if enabled. Its absence is reported but not an error.
+    pub fn start_lsp(&mut self) {
+        if !self.config.rust_analyzer {
+            return;
+        }
+
+        let started = std::env::current_dir()
+            .context("Failed to get the current directory")
+            .and_then(|root| LspClient::start(&root));
+        match started {
+            Ok(lsp) => {
+                self.lsp = Some(lsp);
+                self.lsp_open_current();
+            }
+            Err(e) => self.stop_lsp(e),
+        }
+    }
+
+    // drop the client after an error, the editor keeps working without it
+    fn stop_lsp(&mut self, error: anyhow::Error) {
+        self.lsp = None;
+        self.output = format!(
+            "{} {error:#}\nHover, completion and diagnostics are disabled (`:lsp` to retry)",
+            theme::icons::INFO
+        );
+    }
+
+    /// Tell rust-analyzer about the file now shown in the editor
+    pub fn lsp_open_current(&mut self) {
+        let Some(lsp) = &mut self.lsp else {
+            return;
+        };
+        let path = Path::new(&self.file_path);
+        let opened = if path.extension().is_some_and(|ext| ext == "rs") {
+            lsp.open(path, self.editor.buffer.rope())
+        } else {
+            lsp.close()
+        };
+        if let Err(e) = opened {
+            self.stop_lsp(e);
+        }
+    }
+
+    /// Send edits and handle the server's answers. Called on every tick of the event loop.
+    pub fn poll_lsp(&mut self) {
+        let Some(lsp) = &mut self.lsp else {
+            return;
+        };
+        let responses = lsp
+            .sync(self.editor.buffer.rope())
+            .and_then(|()| lsp.poll());
+        match responses {
+            Ok(responses) => {
+                for response in responses {
+                    self.handle_lsp_response(response);
+                }
+            }
+            Err(e) => self.stop_lsp(e),
+        }
+    }
+
+    fn handle_lsp_response(&mut self, response: Response) {
+        match response {
+            Response::Hover(Some(text)) => {
+                self.output = format!("{} {text}", theme::icons::INFO);
+                self.output_scroll = 0;
+            }
+            Response::Hover(None) => {
+                self.output = format!("{} No type information here", theme::icons::INFO);
+            }
+            Response::Definition(Some((uri, pos))) => self.jump_to(&uri, pos),
+            Response::Definition(None) => {
+                self.output = format!("{} No definition found", theme::icons::INFO);
+            }
+            Response::Completion(items) => self.open_completion(items),
+        }
+    }
+
+    fn jump_to(&mut self, uri: &str, pos: LspPos) {
+        let Some(lsp) = &self.lsp else {
+            return;
+        };
+        if lsp.is_open_file(uri) {
+            let (row, col) = self.editor.buffer.pos_from_utf16(pos);
+            self.editor.cursor_row = row;
+            self.editor.cursor_col = col;
+            self.editor.clamp_col();
+            return;
+        }
+
+        // only the exercise is editable, show where other definitions are
+        self.output = match uri_to_path(uri) {
+            Some(path) => {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                let path = path.strip_prefix(&cwd).unwrap_or(&path);
+                format!(
+                    "{} Defined in {}:{}:{}",
+                    theme::icons::INFO,
+                    path.display(),
+                    pos.0 + 1,
+                    pos.1 + 1
+                )
+            }
+            None => format!("{} Defined in {uri}", theme::icons::INFO),
+        };
+    }
+
+    fn open_completion(&mut self, items: Vec<CompletionItem>) {
+        // the user moved on while rust-analyzer was thinking
+        if self.mode != EditorMode::Insert {
+            return;
+        }
+        let start_col = self.editor.word_start_before_cursor(lexer::is_ident_char);
+        let row = self.editor.cursor_row;
+        let prefix = self
+            .editor
+            .buffer
+            .slice((row, start_col), (row, self.editor.cursor_col));
+        self.completion = CompletionMenu::new(items, start_col, &prefix);
+    }
+
+    // run a request at the cursor, or explain why nothing happens
+    fn lsp_request(&mut self, request: impl FnOnce(&mut LspClient, LspPos) -> Result<()>) {
+        let pos = self
+            .editor
+            .buffer
+            .utf16_pos((self.editor.cursor_row, self.editor.cursor_col));
+        let Some(lsp) = &mut self.lsp else {
+            self.output = format!(
+                "{} rust-analyzer isn't running (`:lsp` to start it)",
+                theme::icons::INFO
+            );
+            return;
+        };
+        if lsp.uri.is_empty() {
+            self.output = format!("{} rust-analyzer only knows Rust files", theme::icons::INFO);
+            return;
+        }
+        if let Err(e) = lsp
+            .sync(self.editor.buffer.rope())
+            .and_then(|()| request(lsp, pos))
+        {
+            self.stop_lsp(e);
+        }
+    }
+
+    /// `K`: show the type and docs of the item under the cursor,
+    /// from the offline std docs if rust-analyzer isn't running
+    pub fn lsp_hover(&mut self) {
+        if self.lsp.is_none() {
+            self.std_doc_under_cursor();
+            return;
+        }
+        self.lsp_request(LspClient::hover);
+    }
+
+    /// `gd`: jump to the definition of the item under the cursor
+    pub fn lsp_goto_definition(&mut self) {
+        self.lsp_request(LspClient::definition);
+    }
+
+    /// Ctrl+Space in insert mode: ask for completions of the word before the cursor
+    pub fn lsp_complete(&mut self) {
+        self.lsp_request(LspClient::completion);
+    }
+
+    /// Diagnostics starting on `row`, most severe first
+    pub fn diagnostics_on(&self, row: usize) -> impl Iterator<Item = &Diagnostic> {
+        self.lsp
+            .iter()
+            .flat_map(|lsp| &lsp.diagnostics)
+            .filter(move |diagnostic| diagnostic.start.0 == row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_roundtrip() {
+        let path = Path::new("/home/ferris/rust lings/exercises/é.rs");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///home/ferris/rust%20lings/exercises/%C3%A9.rs");
+        if !cfg!(windows) {
+            assert_eq!(uri_to_path(&uri).as_deref(), Some(path));
+        }
+    }
+
+    #[test]
+    fn server_requests() {
+        let params = json!({ "items": [{ "section": "rust-analyzer" }, { "section": "files" }] });
+        let answer = reply(&json!(3), "workspace/configuration", &params);
+        assert_eq!(answer["id"], 3);
+        assert_eq!(answer["result"], json!([null, null]));
+
+        let answer = reply(&json!("a"), "window/showDocument", &json!({}));
+        assert_eq!(answer["id"], "a");
+        assert_eq!(answer["error"]["code"], METHOD_NOT_FOUND);
+        assert!(answer.get("result").is_none());
+    }
+
+    #[test]
+    fn semantic_tokens_are_relative() {
+        let types = ["function", "keyword", "struct"].map(String::from);
+        // `fn f` on line 0, `S` on line 2 at 4, `g` right after it
+        let data = json!([0, 0, 2, 1, 0, 0, 3, 1, 0, 0, 2, 4, 1, 2, 0, 0, 2, 1, 0, 0]);
+        let lines = decode_semantic_tokens(data.as_array().unwrap(), &types);
+        assert_eq!(lines.len(), 3);
+        // keywords are left to the lexer
+        assert_eq!(lines[0].len(), 1);
+        assert_eq!(
+            (lines[0][0].start, lines[0][0].kind),
+            (3, TokenKind::Function)
+        );
+        assert_eq!((lines[2][0].start, lines[2][0].kind), (4, TokenKind::Type));
+        assert_eq!(
+            (lines[2][1].start, lines[2][1].kind),
+            (6, TokenKind::Function)
+        );
+    }
+
+    #[test]
+    fn hover_without_fences() {
+        let result = json!({ "contents": { "kind": "markdown", "value": "```rust\nfn main()\n```\n\nDocs" } });
+        assert_eq!(hover_text(&result).as_deref(), Some("fn main()\n\nDocs"));
+        assert_eq!(hover_text(&json!({ "contents": "" })), None);
+    }
+}
@@ -1,15 +1,30 @@
 //! UI module for the Rustlings TUI
 
+pub mod buffer;
+mod buffers;
 mod commands;
+pub mod completion;
+mod concepts;
+pub mod config;
 pub mod editor;
 mod exercise_nav;
+pub mod fold;
+mod format;
 mod frog_state;
 pub mod handlers;
+pub mod indent;
 pub mod layout;
+pub mod lesson;
+pub mod lexer;
+pub mod lsp;
 pub mod render;
+mod search;
+pub mod snippets;
 pub mod state;
+mod std_docs;
 pub mod theme;
 pub mod tui;
+pub mod undo;
 
 // Types are accessed directly via crate::ui::state within UI modules
 pub use tui::run_tui;
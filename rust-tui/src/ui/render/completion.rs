@@ -0,0 +1,93 @@
+//! Completion popup next to the cursor
+
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::ui::{state::TuiState, theme};
+
+const MAX_ROWS: usize = 8;
+const MAX_WIDTH: u16 = 60;
+
+/// Render the popup below the cursor, or above it if there is no room. `area` is the code area.
+pub fn render_completion(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let Some(menu) = &state.completion else {
+        return;
+    };
+    let editor = &state.editor;
+    let Some((_, cursor_y)) = editor.screen_pos((editor.cursor_row, editor.cursor_col)) else {
+        return;
+    };
+
+    // align the labels with the word being completed
+    let word_x = editor
+        .screen_pos((editor.cursor_row, menu.start_col))
+        .map_or(0, |(x, _)| x);
+    let word_x = area.x + word_x as u16;
+    let cursor_y = area.y + cursor_y as u16;
+
+    let label_width = menu
+        .matches()
+        .map(|item| item.label.len())
+        .max()
+        .unwrap_or(0);
+    let detail_width = menu
+        .matches()
+        .filter_map(|item| item.detail.as_ref())
+        .map(|detail| detail.len() + 2)
+        .max()
+        .unwrap_or(0);
+    let width = ((label_width + detail_width) as u16 + 4)
+        .min(MAX_WIDTH)
+        .min(area.width);
+    let height = menu.matches().len().min(MAX_ROWS) as u16 + 2;
+
+    let x = word_x
+        .saturating_sub(2)
+        .min(area.right().saturating_sub(width));
+    let y = if cursor_y + 1 + height <= area.bottom() {
+        cursor_y + 1
+    } else {
+        cursor_y.saturating_sub(height)
+    };
+    let popup = Rect::new(x, y, width, height).intersection(frame.area());
+
+    // keep the selection in view
+    let first = menu.selected().saturating_sub(MAX_ROWS - 1);
+    let lines: Vec<Line> = menu
+        .matches()
+        .enumerate()
+        .skip(first)
+        .take(MAX_ROWS)
+        .map(|(ind, item)| {
+            let mut spans = vec![Span::styled(
+                format!("{:<label_width$}", item.label),
+                Style::default().fg(theme::colors::TEXT),
+            )];
+            if let Some(detail) = &item.detail {
+                spans.push(Span::styled(
+                    format!("  {detail}"),
+                    Style::default().fg(theme::colors::MUTED),
+                ));
+            }
+            let line = Line::from(spans);
+            if ind == menu.selected() {
+                line.style(Style::default().bg(theme::colors::BG_LIGHT))
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::colors::MUTED));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().bg(theme::colors::BACKGROUND)),
+        popup,
+    );
+}
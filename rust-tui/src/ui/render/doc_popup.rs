@@ -0,0 +1,96 @@
+//! Popup with the results of a std docs lookup next to the cursor
+
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use super::syntax::highlight_code;
+use crate::ui::{state::TuiState, theme};
+
+const MAX_WIDTH: u16 = 80;
+
+/// Render the popup below the cursor, or above it if there is no room. `area` is the code area.
+pub fn render_doc_popup(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let Some(entries) = &state.doc_popup else {
+        return;
+    };
+    let editor = &state.editor;
+    let cursor_y = editor
+        .screen_pos((editor.cursor_row, editor.cursor_col))
+        .map_or(area.y, |(_, y)| area.y + y as u16);
+
+    let numbered = entries.len() > 1;
+    let mut lines = Vec::new();
+    for (ind, entry) in entries.iter().enumerate() {
+        if ind > 0 {
+            lines.push(Line::raw(""));
+        }
+        let mut name = Vec::new();
+        if numbered {
+            name.push(Span::styled(
+                format!("{} ", ind + 1),
+                Style::default().fg(theme::colors::ACCENT),
+            ));
+        }
+        name.push(Span::styled(
+            entry.name.clone(),
+            Style::default()
+                .fg(theme::colors::TYPE)
+                .add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::from(name));
+        if !entry.signature.is_empty() {
+            lines.extend(highlight_code(&entry.signature));
+        }
+        if !entry.summary.is_empty() {
+            lines.push(Line::styled(
+                entry.summary.clone(),
+                Style::default().fg(theme::colors::TEXT),
+            ));
+        }
+        lines.push(Line::styled(
+            entry.location.clone(),
+            Style::default().fg(theme::colors::MUTED),
+        ));
+    }
+
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2)
+        .min(MAX_WIDTH)
+        .min(area.width);
+    let inner_width = usize::from(width.saturating_sub(2)).max(1);
+    let rows: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(inner_width).max(1))
+        .sum();
+    let height = (rows as u16 + 2).min(area.height);
+
+    let y = if cursor_y + 1 + height <= area.bottom() {
+        cursor_y + 1
+    } else if cursor_y >= area.y + height {
+        cursor_y - height
+    } else {
+        area.y
+    };
+    let x = area.right().saturating_sub(width).min(area.x + 2);
+    let popup = Rect::new(x, y, width, height).intersection(frame.area());
+
+    let keys = if numbered {
+        " o or 1-9 shows the page path · any other key closes "
+    } else {
+        " o shows the page path · any other key closes "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::colors::MUTED))
+        .title(format!(" {} std docs ", theme::icons::SOLUTION))
+        .title_bottom(Line::from(keys).right_aligned());
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .style(Style::default().bg(theme::colors::BACKGROUND)),
+        popup,
+    );
+}
@@ -1,8 +1,17 @@
-use super::highlight_rust_line;
+use std::ops::Range;
+use std::rc::Rc;
+
+use super::completion::render_completion;
+use super::doc_popup::render_doc_popup;
+use super::syntax::{highlight_line_semantic, patch_range};
+use crate::ui::buffer::{cells_before, cols_in_cells, utf16_to_byte};
+use crate::ui::lexer;
 use crate::ui::state::{EditorMode, TuiState};
 use crate::ui::theme;
 use ratatui::prelude::*;
 use ratatui::widgets::*;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub fn render_editor(frame: &mut Frame, area: Rect, state: &mut TuiState, is_active: bool) {
     let block = Block::default()
@@ -23,63 +32,81 @@ pub fn render_editor(frame: &mut Frame, area: Rect, state: &mut TuiState, is_act
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let visible_height = inner.height as usize;
-    state.editor.update_scroll(visible_height);
+    let line_count = state.editor.line_count();
+    let editor_chunks = editor_chunks(area, line_count);
+    let line_num_width = editor_chunks[0].width;
 
-    // Line number width
-    let line_count = state.editor.lines.len();
-    let line_num_width = (line_count.to_string().len() + 2) as u16;
+    let visible_height = inner.height as usize;
+    let code_width = editor_chunks[1].width as usize;
+    let wrap = state.config.soft_wrap;
+    state.editor.update_scroll(visible_height, code_width, wrap);
 
-    let editor_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(line_num_width), Constraint::Min(10)])
-        .split(inner);
+    // rows hidden in closed folds are skipped
+    let shown_rows: Vec<usize> = state.editor.shown_rows().take(visible_height).collect();
 
     // Render line numbers
-    let visible_lines: Vec<Line> = (state.editor.scroll_offset
-        ..state.editor.scroll_offset + visible_height)
-        .filter_map(|i| {
-            if i < line_count {
-                Some(Line::from(Span::styled(
-                    format!("{:>width$} ", i + 1, width = line_num_width as usize - 2),
-                    Style::default().fg(theme::colors::MUTED),
-                )))
-            } else {
-                None
-            }
+    let mut visible_lines: Vec<Line> = shown_rows
+        .iter()
+        .map(|&i| {
+            // rows with diagnostics are numbered in the color of the most severe one
+            let style = match state.diagnostics_on(i).next() {
+                Some(diagnostic) => Style::default()
+                    .fg(theme::severity_color(diagnostic.severity))
+                    .add_modifier(Modifier::BOLD),
+                None => Style::default().fg(theme::colors::MUTED),
+            };
+            Line::from(Span::styled(
+                format!("{:>width$} ", i + 1, width = line_num_width as usize - 2),
+                style,
+            ))
         })
         .collect();
 
-    let line_nums_widget = Paragraph::new(visible_lines);
-    frame.render_widget(line_nums_widget, editor_chunks[0]);
-
     // Calculate matching bracket position for highlighting
     let matching_bracket = state.editor.find_matching_bracket();
     let _cursor_pos = (state.editor.cursor_row, state.editor.cursor_col);
 
     // Render code with syntax highlighting
-    let code_lines: Vec<Line> = state
-        .editor
-        .lines
+    // block comments and strings can start above the first visible line
+    let mut lex_state =
+        lexer::state_after(state.editor.buffer.lines().take(state.editor.scroll_offset));
+    let readonly = state.editor.readonly_regions();
+    let mut lexed_row = state.editor.scroll_offset;
+    let mut code_lines: Vec<Line> = shown_rows
         .iter()
-        .skip(state.editor.scroll_offset)
-        .take(visible_height)
-        .enumerate()
-        .map(|(i, line)| {
-            let actual_row = state.editor.scroll_offset + i;
+        .map(|&actual_row| {
+            // lex the rows of a closed fold too so strings and comments stay in sync
+            for hidden_row in lexed_row..actual_row {
+                let hidden = state.editor.buffer.line(hidden_row);
+                lex_state = lexer::tokenize_line(&hidden, lex_state).1;
+            }
+            lexed_row = actual_row + 1;
+            let line = state.editor.buffer.line(actual_row);
+            let line = line.as_ref();
             let is_cursor_line = actual_row == state.editor.cursor_row;
-
-            // Check if this line is in the visual selection
-            let in_visual_mode = state.mode == EditorMode::Visual;
-            let visual_bounds = if in_visual_mode {
-                Some(get_selection_bounds(state))
-            } else {
-                None
-            };
+            // lex every line, even those rendered without highlighting, to keep the state in sync
+            let semantic = state
+                .lsp
+                .as_ref()
+                .and_then(|lsp| lsp.semantic_tokens.get(actual_row))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let mut spans = highlight_line_semantic(line, &mut lex_state, semantic);
+            for (range, color) in diagnostic_ranges(state, actual_row, line) {
+                let underline = Style::default()
+                    .add_modifier(Modifier::UNDERLINED)
+                    .underline_color(color);
+                spans = patch_range(spans, range, underline);
+            }
+            if readonly.iter().any(|rows| rows.contains(&actual_row)) {
+                let dim = Style::default().add_modifier(Modifier::DIM);
+                spans = patch_range(spans, 0..line.len(), dim);
+            }
+            let highlighted = Line::from(spans);
 
             // Visual mode rendering with selection highlighting
-            if in_visual_mode {
-                render_visual_line(line, actual_row, &visual_bounds, state, is_cursor_line)
+            if state.mode.is_visual() {
+                render_visual_line(line, actual_row, state, is_cursor_line, highlighted)
             } else if is_cursor_line
                 && (state.mode == EditorMode::Insert || state.mode == EditorMode::Normal)
             {
@@ -90,15 +117,15 @@ pub fn render_editor(frame: &mut Frame, area: Rect, state: &mut TuiState, is_act
                     theme::colors::PRIMARY // Orange for Normal
                 };
 
-                // Use char indices for proper UTF-8 handling
-                let chars: Vec<char> = line.chars().collect();
+                // Columns are graphemes so emoji and combining accents are a single cell
+                let chars: Vec<&str> = line.graphemes(true).collect();
                 let col = state.editor.cursor_col.min(chars.len());
 
                 let mut spans = Vec::new();
 
                 // Text before cursor
                 if col > 0 {
-                    let before: String = chars[..col].iter().collect();
+                    let before: String = chars[..col].concat();
                     spans.push(Span::styled(
                         before,
                         Style::default().fg(theme::colors::TEXT),
@@ -115,7 +142,7 @@ pub fn render_editor(frame: &mut Frame, area: Rect, state: &mut TuiState, is_act
                     ));
                     // Text after cursor
                     if col + 1 < chars.len() {
-                        let after: String = chars[col + 1..].iter().collect();
+                        let after: String = chars[col + 1..].concat();
                         spans.push(Span::styled(
                             after,
                             Style::default().fg(theme::colors::TEXT),
@@ -139,18 +166,119 @@ pub fn render_editor(frame: &mut Frame, area: Rect, state: &mut TuiState, is_act
                         render_line_with_bracket_highlight(line, match_col)
                     } else {
                         // Syntax highlighting
-                        highlight_rust_line(line, is_cursor_line)
+                        highlighted
                     }
                 } else {
                     // Syntax highlighting
-                    highlight_rust_line(line, is_cursor_line)
+                    highlighted
                 }
             }
         })
         .collect();
 
-    let code_widget = Paragraph::new(code_lines);
+    // closed folds end with the number of lines they hide
+    for (code_line, &row) in code_lines.iter_mut().zip(&shown_rows) {
+        let hidden = state.editor.folds.hidden_after(row).len();
+        if hidden > 0 {
+            code_line.spans.push(Span::styled(
+                format!(" ⋯ {hidden} line{} ", if hidden == 1 { "" } else { "s" }),
+                Style::default()
+                    .fg(theme::colors::MUTED)
+                    .bg(theme::colors::BG_LIGHT),
+            ));
+        }
+    }
+
+    // wrapped rows continue without a line number
+    if wrap {
+        let mut numbers = Vec::new();
+        let mut rows = Vec::new();
+        for (number, code_line) in visible_lines.into_iter().zip(code_lines) {
+            let wrapped = wrap_line(code_line, code_width);
+            numbers.push(number);
+            numbers.extend(std::iter::repeat_n(Line::raw(""), wrapped.len() - 1));
+            rows.extend(wrapped);
+        }
+        (visible_lines, code_lines) = (numbers, rows);
+    }
+
+    let line_nums_widget = Paragraph::new(visible_lines);
+    frame.render_widget(line_nums_widget, editor_chunks[0]);
+
+    let code_widget = Paragraph::new(code_lines).scroll((0, state.editor.h_scroll as u16));
     frame.render_widget(code_widget, editor_chunks[1]);
+
+    render_completion(frame, editor_chunks[1], state);
+    render_doc_popup(frame, editor_chunks[1], state);
+}
+
+/// Line number and code areas inside the border of the editor pane `area`
+pub fn editor_chunks(area: Rect, line_count: usize) -> Rc<[Rect]> {
+    let line_num_width = (line_count.to_string().len() + 2) as u16;
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(line_num_width), Constraint::Min(10)])
+        .split(area.inner(Margin::new(1, 1)))
+}
+
+/// Split a rendered line into rows of `width` cells, breaking like `buffer::wrapped_pos`
+pub fn wrap_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let mut rows = vec![Line::default()];
+    let mut x = 0;
+    for span in line.spans {
+        for grapheme in span.content.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if x > 0 && x + grapheme_width > width {
+                rows.push(Line::default());
+                x = 0;
+            }
+            x += grapheme_width;
+
+            let row = rows.last_mut().unwrap();
+            match row.spans.last_mut() {
+                Some(last) if last.style == span.style => last.content.to_mut().push_str(grapheme),
+                _ => row
+                    .spans
+                    .push(Span::styled(grapheme.to_string(), span.style)),
+            }
+        }
+    }
+    rows
+}
+
+/// Byte ranges of `line` covered by diagnostics and their colors, most severe last
+fn diagnostic_ranges(state: &TuiState, row: usize, line: &str) -> Vec<(Range<usize>, Color)> {
+    let Some(lsp) = &state.lsp else {
+        return Vec::new();
+    };
+    lsp.diagnostics
+        .iter()
+        .rev()
+        .filter(|diagnostic| (diagnostic.start.0..=diagnostic.end.0).contains(&row))
+        .map(|diagnostic| {
+            let (start, end) = (diagnostic.start, diagnostic.end);
+            let end = if end.0 == row {
+                utf16_to_byte(line, end.1)
+            } else {
+                line.len()
+            };
+            let start = if start.0 == row {
+                utf16_to_byte(line, start.1)
+            } else {
+                0
+            };
+            // empty ranges like a missing `;` underline the char before
+            let start = if start == end {
+                line[..start]
+                    .char_indices()
+                    .next_back()
+                    .map_or(start, |(i, _)| i)
+            } else {
+                start
+            };
+            (start..end, theme::severity_color(diagnostic.severity))
+        })
+        .collect()
 }
 
 pub fn get_selection_bounds(state: &TuiState) -> (usize, usize, usize, usize) {
@@ -166,62 +294,86 @@ pub fn get_selection_bounds(state: &TuiState) -> (usize, usize, usize, usize) {
     }
 }
 
+/// Left and right (inclusive) cells of a visual block selection.
+/// Display cells keep the block rectangular over tabs and wide characters.
+pub fn get_block_cols(state: &TuiState) -> (usize, usize) {
+    let corner = |row: usize, col: usize| {
+        let line = state.editor.buffer.line(row);
+        let left = cells_before(&line, col);
+        let width = line
+            .graphemes(true)
+            .nth(col)
+            .map_or(1, |g| g.width().max(1));
+        (left, left + width - 1)
+    };
+    let (a_left, a_right) = corner(state.visual_start_row, state.visual_start_col);
+    let (b_left, b_right) = corner(state.editor.cursor_row, state.editor.cursor_col);
+    (a_left.min(b_left), a_right.max(b_right))
+}
+
+/// Selected column range `[start, end)` on `row` for the current visual shape,
+/// or `None` if the row is outside of the selection
+pub fn selection_cols_for_row(
+    state: &TuiState,
+    row: usize,
+    line_len: usize,
+) -> Option<(usize, usize)> {
+    let (start_row, start_col, end_row, end_col) = get_selection_bounds(state);
+    if row < start_row || row > end_row {
+        return None;
+    }
+
+    let (sel_start, sel_end) = match state.mode {
+        EditorMode::VisualLine => (0, line_len),
+        EditorMode::VisualBlock => {
+            let (left, right) = get_block_cols(state);
+            cols_in_cells(&state.editor.buffer.line(row), left, right)
+        }
+        _ if start_row == end_row => (start_col, end_col + 1),
+        _ if row == start_row => (start_col, line_len),
+        _ if row == end_row => (0, end_col + 1),
+        _ => (0, line_len),
+    };
+
+    Some((sel_start.min(line_len), sel_end.min(line_len)))
+}
+
 /// Render a line with visual selection highlighting
 fn render_visual_line(
     line: &str,
     row: usize,
-    bounds: &Option<(usize, usize, usize, usize)>,
     state: &TuiState,
     is_cursor_line: bool,
+    highlighted: Line<'static>,
 ) -> Line<'static> {
-    let chars: Vec<char> = line.chars().collect();
+    let chars: Vec<&str> = line.graphemes(true).collect();
     let mut spans = Vec::new();
 
-    // Get selection bounds
-    let (start_row, start_col, end_row, end_col) = bounds.unwrap_or((0, 0, 0, 0));
-
-    // Check if this row is in the selection range
-    if row < start_row || row > end_row {
+    let Some((sel_start, sel_end)) = selection_cols_for_row(state, row, chars.len()) else {
         // Not in selection, render normally
-        return highlight_rust_line(line, is_cursor_line);
-    }
-
-    // Determine selection columns for this row
-    let (sel_start, sel_end) = if start_row == end_row {
-        // Single line selection
-        (start_col, end_col + 1)
-    } else if row == start_row {
-        // First line of multi-line selection
-        (start_col, chars.len())
-    } else if row == end_row {
-        // Last line of multi-line selection
-        (0, end_col + 1)
-    } else {
-        // Middle line - fully selected
-        (0, chars.len())
+        return highlighted;
     };
 
-    let sel_start = sel_start.min(chars.len());
-    let sel_end = sel_end.min(chars.len());
+    let selection_style = Style::default()
+        .fg(theme::colors::BACKGROUND)
+        .bg(theme::colors::PRIMARY); // Orange highlight for selection
 
     // Text before selection
     if sel_start > 0 {
-        let before: String = chars[..sel_start].iter().collect();
+        let before: String = chars[..sel_start].concat();
         spans.push(Span::styled(
             before,
             Style::default().fg(theme::colors::TEXT),
         ));
     }
 
-    // Selected text (highlighted with magenta background)
+    // Selected text
     if sel_start < sel_end {
-        let selected: String = chars[sel_start..sel_end].iter().collect();
-        spans.push(Span::styled(
-            selected,
-            Style::default()
-                .fg(theme::colors::BACKGROUND)
-                .bg(theme::colors::PRIMARY), // Orange highlight for selection
-        ));
+        let selected: String = chars[sel_start..sel_end].concat();
+        spans.push(Span::styled(selected, selection_style));
+    } else if chars.is_empty() && !is_cursor_line && state.mode == EditorMode::VisualLine {
+        // Keep empty lines visible as part of a linewise selection
+        spans.push(Span::styled(" ", selection_style));
     }
 
     // Cursor at current position in visual mode
@@ -229,6 +381,13 @@ fn render_visual_line(
     if is_cursor_line && cursor_col >= sel_end && cursor_col < chars.len() {
         // Show cursor after selection if visible
         let cursor_char = chars[cursor_col].to_string();
+        if cursor_col > sel_end {
+            let between: String = chars[sel_end..cursor_col].concat();
+            spans.push(Span::styled(
+                between,
+                Style::default().fg(theme::colors::TEXT),
+            ));
+        }
         spans.push(Span::styled(
             cursor_char,
             Style::default()
@@ -236,7 +395,7 @@ fn render_visual_line(
                 .bg(theme::colors::SUCCESS), // Green cursor
         ));
         if cursor_col + 1 < chars.len() {
-            let after: String = chars[cursor_col + 1..].iter().collect();
+            let after: String = chars[cursor_col + 1..].concat();
             spans.push(Span::styled(
                 after,
                 Style::default().fg(theme::colors::TEXT),
@@ -244,15 +403,15 @@ fn render_visual_line(
         }
     } else if sel_end < chars.len() {
         // Text after selection
-        let after: String = chars[sel_end..].iter().collect();
+        let after: String = chars[sel_end..].concat();
         spans.push(Span::styled(
             after,
             Style::default().fg(theme::colors::TEXT),
         ));
     }
 
-    // Handle empty line with cursor
-    if line.is_empty() && is_cursor_line {
+    // Handle empty line or cursor past the end of a short line (block mode)
+    if is_cursor_line && cursor_col >= chars.len() {
         spans.push(Span::styled(
             " ",
             Style::default()
@@ -266,7 +425,7 @@ fn render_visual_line(
 
 /// Render a line with a specific column highlighted as a matching bracket
 fn render_line_with_bracket_highlight(line: &str, highlight_col: usize) -> Line<'static> {
-    let chars: Vec<char> = line.chars().collect();
+    let chars: Vec<&str> = line.graphemes(true).collect();
     let mut spans = Vec::new();
 
     // Bracket highlight style - use a distinct color
@@ -278,7 +437,7 @@ fn render_line_with_bracket_highlight(line: &str, highlight_col: usize) -> Line<
     if highlight_col < chars.len() {
         // Text before bracket
         if highlight_col > 0 {
-            let before: String = chars[..highlight_col].iter().collect();
+            let before: String = chars[..highlight_col].concat();
             spans.push(Span::styled(
                 before,
                 Style::default().fg(theme::colors::TEXT),
@@ -293,7 +452,7 @@ fn render_line_with_bracket_highlight(line: &str, highlight_col: usize) -> Line<
 
         // Text after bracket
         if highlight_col + 1 < chars.len() {
-            let after: String = chars[highlight_col + 1..].iter().collect();
+            let after: String = chars[highlight_col + 1..].concat();
             spans.push(Span::styled(
                 after,
                 Style::default().fg(theme::colors::TEXT),
@@ -80,7 +80,7 @@ pub fn render_footer(frame: &mut Frame, area: Rect, state: &TuiState) {
     let total = state.app_state.exercises().len();
 
     // Progress percentage
-    let percent = if total > 0 { (done * 100) / total } else { 0 };
+    let percent = (done * 100).checked_div(total).unwrap_or(0);
     let percent_str = format!(" {}% ", percent);
 
     // Calculate exact width: total - leading space (1) - ball (1) - percent label
@@ -97,11 +97,7 @@ pub fn render_footer(frame: &mut Frame, area: Rect, state: &TuiState) {
     let b = (30.0 + brightness * 80.0) as u8; // 30-110
     let ball_color = Color::Rgb(r, g, b);
 
-    let filled = if total > 0 {
-        (done * progress_width) / total
-    } else {
-        0
-    };
+    let filled = (done * progress_width).checked_div(total).unwrap_or(0);
     let empty = progress_width.saturating_sub(filled);
 
     // Build the clean progress line: [orange━━━●gray━━━] XX%
@@ -152,6 +148,8 @@ pub fn render_footer(frame: &mut Frame, area: Rect, state: &TuiState) {
             theme::mode_command_style(),
         ),
         EditorMode::Visual => Span::styled(" VISUAL ", theme::mode_visual_style()),
+        EditorMode::VisualLine => Span::styled(" VISUAL LINE ", theme::mode_visual_style()),
+        EditorMode::VisualBlock => Span::styled(" VISUAL BLOCK ", theme::mode_visual_style()),
     };
 
     let keybindings = if state.mode == EditorMode::Command {
@@ -160,10 +158,27 @@ pub fn render_footer(frame: &mut Frame, area: Rect, state: &TuiState) {
         "i: edit │ :c compile │ :h hint │ s: solution │ [/]: nav │ :help │ q: quit"
     };
 
+    // rust-analyzer's message for the cursor line replaces the key hints
+    let diagnostic = (state.mode != EditorMode::Command)
+        .then(|| state.diagnostics_on(state.editor.cursor_row).next())
+        .flatten();
+    let hint_span = match diagnostic {
+        Some(diagnostic) => Span::styled(
+            diagnostic
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            Style::default().fg(theme::severity_color(diagnostic.severity)),
+        ),
+        None => Span::styled(keybindings, Style::default().fg(theme::colors::TEXT_DIM)),
+    };
+
     let status_line = Line::from(vec![
         mode_span,
         Span::styled(" ", Style::default()),
-        Span::styled(keybindings, Style::default().fg(theme::colors::TEXT_DIM)),
+        hint_span,
     ]);
     let status_bar = Paragraph::new(status_line);
     frame.render_widget(status_bar, chunks[status_idx]);
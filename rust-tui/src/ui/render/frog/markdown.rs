@@ -1,125 +1,759 @@
-//! Markdown rendering for Frog learning panel
+//! Markdown rendering for Frog learning panel.
+//! Slides are parsed with pulldown-cmark and wrapped to the panel width here,
+//! so the number of lines is the height the panel scrolls through.
 
-use crate::ui::theme;
+use std::{collections::HashMap, mem, ops::Range};
+
+use pulldown_cmark::{
+    Alignment, BlockQuoteKind, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd,
+};
 use ratatui::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
 
-use super::syntax::highlight_rust_line;
+use crate::ui::{
+    buffer::display_width,
+    lesson::{CodeBlock, CodeRun},
+    lexer::LexState,
+    render::{editor::wrap_line, syntax::highlight_line},
+    theme,
+};
 
-/// Convert markdown text to styled Lines for display
-pub fn render_markdown(text: &str) -> Vec<Line<'static>> {
-    let mut lines = Vec::new();
-    let mut in_code_block = false;
+// narrowest a table column is shrunk to before the table overflows the panel
+const MIN_COLUMN_WIDTH: usize = 3;
+const COLUMN_SEPARATOR: &str = " │ ";
 
-    for line in text.lines() {
-        let trimmed = line.trim_end();
+/// Convert markdown text to styled lines of at most `width` cells
+pub fn render_markdown(text: &str, width: usize) -> Vec<Line<'static>> {
+    render_slide(text, width, &HashMap::new(), None).lines
+}
 
-        // Toggle code block state
-        if trimmed.starts_with("```") {
-            in_code_block = !in_code_block;
-            continue;
+/// Lines of a slide and where its code blocks are
+pub struct SlideLines {
+    pub lines: Vec<Line<'static>>,
+    /// Rows of each code block in `lines`, without the output of its runs
+    pub code_blocks: Vec<Range<usize>>,
+}
+
+/// Like `render_markdown`, with the output of the runnable code blocks in `runs` below them
+/// and the code block `selected` to be copied marked.
+/// `runs` maps the index of a code block to its last run.
+pub fn render_slide(
+    text: &str,
+    width: usize,
+    runs: &HashMap<usize, CodeRun>,
+    selected: Option<usize>,
+) -> SlideLines {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_GFM;
+    let mut renderer = Renderer {
+        width: width.max(1),
+        runs: Some(runs),
+        selected,
+        ..Renderer::default()
+    };
+    for event in Parser::new_ext(text, options) {
+        renderer.event(event);
+    }
+    SlideLines {
+        lines: renderer.lines,
+        code_blocks: renderer.code_rows,
+    }
+}
+
+/// A block quote or list item the current block is nested in
+struct Container {
+    /// Prefix of the container's first line, e.g. the bullet of a list item
+    first: Vec<Span<'static>>,
+    /// Prefix of the following lines
+    rest: Vec<Span<'static>>,
+    first_used: bool,
+}
+
+struct Table {
+    alignments: Vec<Alignment>,
+    /// Rows of cells, the header first
+    rows: Vec<Vec<Vec<Span<'static>>>>,
+}
+
+#[derive(Default)]
+struct Renderer<'a> {
+    width: usize,
+    lines: Vec<Line<'static>>,
+    // text of the paragraph, heading, list item or table cell being built
+    spans: Vec<Span<'static>>,
+    // inline styles like bold or links, innermost last
+    styles: Vec<Style>,
+    containers: Vec<Container>,
+    // number of the next item of each open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    // destination of the open link and where its text starts in `spans`
+    link: Option<(String, usize)>,
+    // the open code block
+    code: Option<CodeBlock>,
+    // number of code blocks rendered so far
+    code_blocks: usize,
+    // rows of the rendered code blocks
+    code_rows: Vec<Range<usize>>,
+    runs: Option<&'a HashMap<usize, CodeRun>>,
+    selected: Option<usize>,
+    table: Option<Table>,
+    // separate the next block from the previous one by an empty line
+    needs_gap: bool,
+}
+
+impl Renderer<'_> {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code {
+                Some(block) => block.code.push_str(&text),
+                None => self
+                    .spans
+                    .push(Span::styled(text.into_string(), self.style())),
+            },
+            Event::Code(code) => self.spans.push(Span::styled(
+                code.into_string(),
+                self.style().patch(
+                    Style::default()
+                        .fg(theme::colors::ACCENT)
+                        .bg(theme::colors::BG_LIGHT),
+                ),
+            )),
+            Event::Html(html) | Event::InlineHtml(html) => self.spans.push(Span::styled(
+                html.trim_end().to_string(),
+                Style::default().fg(theme::colors::MUTED),
+            )),
+            Event::SoftBreak => self.spans.push(Span::styled(" ", self.style())),
+            Event::HardBreak => self.flush_text(),
+            Event::Rule => {
+                self.gap();
+                let (prefix, _) = self.prefixes();
+                let len = self.width.saturating_sub(spans_width(&prefix));
+                let mut spans = prefix;
+                spans.push(Span::styled(
+                    "─".repeat(len),
+                    Style::default().fg(theme::colors::BG_LIGHT),
+                ));
+                self.lines.push(Line::from(spans));
+                self.needs_gap = true;
+            }
+            _ => (),
         }
+    }
 
-        // Inside code block - render with syntax highlighting
-        if in_code_block {
-            let highlighted = highlight_rust_line(line);
-            lines.push(Line::from(highlighted));
-            continue;
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.gap(),
+            Tag::Heading { level, .. } => {
+                self.gap();
+                self.styles.push(heading_style(level));
+            }
+            Tag::BlockQuote(kind) => {
+                self.gap();
+                let color = kind.map_or(theme::colors::MUTED, callout_color);
+                let bar = vec![Span::styled("▎ ", Style::default().fg(color))];
+                self.containers.push(Container {
+                    first: bar.clone(),
+                    rest: bar,
+                    first_used: false,
+                });
+                if let Some(kind) = kind {
+                    self.spans.push(Span::styled(
+                        callout_title(kind),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ));
+                    self.flush_text();
+                }
+            }
+            Tag::CodeBlock(kind) => {
+                self.gap();
+                let block = match kind {
+                    CodeBlockKind::Fenced(info) => CodeBlock::from_info(&info),
+                    CodeBlockKind::Indented => CodeBlock::from_info(""),
+                };
+                self.code = Some(block);
+            }
+            Tag::List(start) => {
+                // the text of the item containing a nested list comes before it
+                if self.lists.is_empty() {
+                    self.gap();
+                } else {
+                    self.flush_text();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_text();
+                self.needs_gap = false;
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                let indent = " ".repeat(display_width(&marker));
+                self.containers.push(Container {
+                    first: vec![Span::styled(
+                        marker,
+                        Style::default().fg(theme::colors::INFO),
+                    )],
+                    rest: vec![Span::raw(indent)],
+                    first_used: false,
+                });
+            }
+            Tag::Table(alignments) => {
+                self.gap();
+                self.table = Some(Table {
+                    alignments,
+                    rows: Vec::new(),
+                });
+            }
+            Tag::TableHead => {
+                self.styles
+                    .push(Style::default().add_modifier(Modifier::BOLD));
+                self.start_table_row();
+            }
+            Tag::TableRow => self.start_table_row(),
+            Tag::Emphasis => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                self.styles.push(
+                    Style::default()
+                        .fg(theme::colors::INFO)
+                        .add_modifier(Modifier::UNDERLINED),
+                );
+                self.link = Some((dest_url.into_string(), self.spans.len()));
+            }
+            _ => (),
         }
+    }
 
-        // Horizontal rule
-        if trimmed.starts_with("---") && trimmed.chars().all(|c| c == '-' || c.is_whitespace()) {
-            lines.push(Line::from(vec![Span::styled(
-                "─".repeat(40),
-                Style::default().fg(theme::colors::BG_LIGHT),
-            )]));
-            continue;
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush_text();
+                self.needs_gap = true;
+            }
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.flush_text();
+                self.needs_gap = true;
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_text();
+                self.containers.pop();
+                self.needs_gap = true;
+            }
+            TagEnd::CodeBlock => {
+                if let Some(block) = self.code.take() {
+                    let start = self.lines.len();
+                    self.render_code(&block);
+                    self.code_rows.push(start..self.lines.len());
+                    if self.selected == Some(self.code_blocks) {
+                        self.render_selected_hint();
+                    }
+                    if block.runnable {
+                        self.render_run();
+                    }
+                }
+                self.code_blocks += 1;
+                self.needs_gap = true;
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                self.needs_gap = true;
+            }
+            TagEnd::Item => {
+                self.flush_text();
+                self.containers.pop();
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.render_table(table);
+                }
+                self.needs_gap = true;
+            }
+            TagEnd::TableHead => {
+                self.styles.pop();
+            }
+            TagEnd::TableCell => {
+                let cell = mem::take(&mut self.spans);
+                if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+                    row.push(cell);
+                }
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link | TagEnd::Image => {
+                self.styles.pop();
+                // the destination is shown unless the text already is the URL
+                if let Some((url, start)) = self.link.take() {
+                    let text: String = self.spans[start..]
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect();
+                    if !url.is_empty() && text != url {
+                        self.spans.push(Span::styled(
+                            format!(" ({url})"),
+                            Style::default().fg(theme::colors::MUTED),
+                        ));
+                    }
+                }
+            }
+            _ => (),
         }
+    }
 
-        // H1 header
-        if let Some(header_text) = trimmed.strip_prefix("# ") {
-            lines.push(Line::from(""));
-            lines.push(Line::from(vec![Span::styled(
-                header_text.to_string(),
-                Style::default()
-                    .fg(theme::colors::ACCENT)
-                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            )]));
-            lines.push(Line::from(""));
-            continue;
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |style, inner| style.patch(*inner))
+    }
+
+    /// Prefixes of the first line of the next block and of the lines after it
+    fn prefixes(&mut self) -> (Vec<Span<'static>>, Vec<Span<'static>>) {
+        let mut first = Vec::new();
+        let mut rest = Vec::new();
+        for container in &mut self.containers {
+            if container.first_used {
+                first.extend(container.rest.iter().cloned());
+            } else {
+                first.extend(container.first.iter().cloned());
+                container.first_used = true;
+            }
+            rest.extend(container.rest.iter().cloned());
         }
+        (first, rest)
+    }
 
-        // H2 header
-        if let Some(header_text) = trimmed.strip_prefix("## ") {
-            lines.push(Line::from(vec![Span::styled(
-                header_text.to_string(),
-                Style::default()
-                    .fg(theme::colors::PRIMARY)
-                    .add_modifier(Modifier::BOLD),
-            )]));
-            lines.push(Line::from(""));
-            continue;
+    // an empty line between blocks, but not right after the bullet of a list item
+    fn gap(&mut self) {
+        let after_marker = self
+            .containers
+            .last()
+            .is_some_and(|container| !container.first_used);
+        if mem::take(&mut self.needs_gap) && !self.lines.is_empty() && !after_marker {
+            let (_, rest) = self.prefixes();
+            self.lines.push(Line::from(rest));
         }
+    }
 
-        // H3 header
-        if let Some(header_text) = trimmed.strip_prefix("### ") {
-            lines.push(Line::from(vec![Span::styled(
-                header_text.to_string(),
-                Style::default()
-                    .fg(theme::colors::INFO)
-                    .add_modifier(Modifier::BOLD),
-            )]));
-            continue;
+    // wrap the collected text into lines
+    fn flush_text(&mut self) {
+        if self.spans.is_empty() {
+            return;
         }
+        let spans = mem::take(&mut self.spans);
+        let (first, rest) = self.prefixes();
+        self.lines
+            .extend(wrap_words(spans, first, rest, self.width));
+    }
 
-        // Table row (starts with |)
-        if trimmed.starts_with('|') {
-            if trimmed.contains("---") {
-                lines.push(Line::from(vec![Span::styled(
-                    trimmed.replace("-", "─").replace("|", "│"),
-                    Style::default().fg(theme::colors::BG_LIGHT),
-                )]));
+    fn render_code(&mut self, block: &CodeBlock) {
+        let (first, rest) = self.prefixes();
+        let width = self.width.saturating_sub(spans_width(&rest) + 2).max(1);
+        // only Rust is highlighted
+        let highlight = block.is_rust();
+        let code = &block.code;
+        let mut state = LexState::default();
+        // the selected block has a bar on its left
+        let gutter = if self.selected == Some(self.code_blocks) {
+            Span::styled("▌ ", Style::default().fg(theme::colors::ACCENT))
+        } else {
+            Span::raw("  ")
+        };
+        // drawings indented to be centered in a wide panel would overflow a narrow one
+        let indent = code
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .min()
+            .unwrap_or(0);
+
+        for (ind, line) in code.lines().enumerate() {
+            let line = line.get(indent..).unwrap_or("");
+            let spans = if highlight {
+                highlight_line(line, &mut state)
             } else {
-                lines.push(Line::from(vec![Span::styled(
-                    trimmed.replace("|", "│"),
-                    Style::default().fg(theme::colors::TEXT),
-                )]));
+                vec![Span::styled(
+                    line.to_string(),
+                    Style::default().fg(theme::colors::TEXT_DIM),
+                )]
+            };
+            // long lines are broken anywhere to keep drawings intact
+            for (row, wrapped) in wrap_line(Line::from(spans), width).into_iter().enumerate() {
+                let prefix = if ind == 0 && row == 0 { &first } else { &rest };
+                let mut spans = prefix.clone();
+                spans.push(gutter.clone());
+                spans.extend(wrapped.spans);
+                self.lines.push(Line::from(spans));
+            }
+        }
+    }
+
+    // how to copy the selected code block
+    fn render_selected_hint(&mut self) {
+        let (_, mut spans) = self.prefixes();
+        spans.push(Span::styled(
+            "▌ Shift+Y yank · Shift+P put in the editor",
+            Style::default().fg(theme::colors::ACCENT),
+        ));
+        self.lines.push(Line::from(spans));
+    }
+
+    // the output of the last run below a runnable code block, or how to run it
+    fn render_run(&mut self) {
+        let (_, rest) = self.prefixes();
+        let run = self.runs.and_then(|runs| runs.get(&self.code_blocks));
+        let Some(run) = run else {
+            let mut spans = rest;
+            spans.push(Span::styled(
+                "  ▶ Shift+R to run",
+                Style::default().fg(theme::colors::MUTED),
+            ));
+            self.lines.push(Line::from(spans));
+            return;
+        };
+
+        let (title, color) = if run.success {
+            ("  ▶ Output", theme::colors::SUCCESS)
+        } else {
+            ("  ✗ Failed", theme::colors::ERROR)
+        };
+        let mut spans = rest.clone();
+        spans.push(Span::styled(
+            title,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+        self.lines.push(Line::from(spans));
+
+        let width = self.width.saturating_sub(spans_width(&rest) + 4).max(1);
+        let output = if run.output.is_empty() {
+            "(no output)"
+        } else {
+            run.output.as_str()
+        };
+        for line in output.lines() {
+            let line = Line::styled(
+                line.to_string(),
+                Style::default().fg(theme::colors::TEXT_DIM),
+            );
+            for wrapped in wrap_line(line, width) {
+                let mut spans = rest.clone();
+                spans.push(Span::styled("  │ ", Style::default().fg(color)));
+                spans.extend(wrapped.spans);
+                self.lines.push(Line::from(spans));
             }
-            continue;
         }
+    }
 
-        // Bullet list
-        if let Some(list_text) = trimmed.strip_prefix("- ") {
-            lines.push(Line::from(vec![
-                Span::styled("  • ", Style::default().fg(theme::colors::INFO)),
-                Span::raw(render_inline_formatting(list_text)),
-            ]));
-            continue;
+    fn start_table_row(&mut self) {
+        if let Some(table) = &mut self.table {
+            table.rows.push(Vec::new());
         }
+    }
 
-        // Box drawing characters
-        if trimmed.contains('┌')
-            || trimmed.contains('│')
-            || trimmed.contains('└')
-            || trimmed.contains('├')
-            || trimmed.contains('╭')
-            || trimmed.contains('╰')
-        {
-            lines.push(Line::from(vec![Span::styled(
-                trimmed.to_string(),
-                Style::default().fg(theme::colors::INFO),
-            )]));
-            continue;
+    /// Columns as wide as their widest cell, shrunk to the panel width by wrapping the cells
+    fn render_table(&mut self, table: Table) {
+        let (first, rest) = self.prefixes();
+        let available = self.width.saturating_sub(spans_width(&rest));
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        let mut widths: Vec<usize> = (0..columns)
+            .map(|col| {
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(col))
+                    .map(|cell| spans_width(cell))
+                    .max()
+                    .unwrap_or(0)
+                    .max(1)
+            })
+            .collect();
+        let separators = display_width(COLUMN_SEPARATOR) * (columns - 1);
+        while widths.iter().sum::<usize>() + separators > available {
+            let (widest, &width) = widths
+                .iter()
+                .enumerate()
+                .max_by_key(|&(_, width)| *width)
+                .unwrap();
+            if width <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            widths[widest] -= 1;
         }
 
-        // Regular text with inline formatting
-        let formatted = render_inline_formatting(trimmed);
-        lines.push(Line::from(formatted));
+        let border = Style::default().fg(theme::colors::BG_LIGHT);
+        for (ind, row) in table.rows.into_iter().enumerate() {
+            let cells: Vec<Vec<Line>> = widths
+                .iter()
+                .enumerate()
+                .map(|(col, &width)| {
+                    let cell = row.get(col).cloned().unwrap_or_default();
+                    wrap_words(cell, Vec::new(), Vec::new(), width)
+                })
+                .collect();
+            let height = cells.iter().map(Vec::len).max().unwrap_or(1).max(1);
+
+            for row_line in 0..height {
+                let prefix = if ind == 0 && row_line == 0 {
+                    &first
+                } else {
+                    &rest
+                };
+                let mut spans = prefix.clone();
+                for (col, cell) in cells.iter().enumerate() {
+                    if col > 0 {
+                        spans.push(Span::styled(COLUMN_SEPARATOR, border));
+                    }
+                    let line = cell.get(row_line).cloned().unwrap_or_default();
+                    let padding = widths[col].saturating_sub(spans_width(&line.spans));
+                    let left = match table.alignments.get(col) {
+                        Some(Alignment::Right) => padding,
+                        Some(Alignment::Center) => padding / 2,
+                        _ => 0,
+                    };
+                    spans.push(Span::raw(" ".repeat(left)));
+                    spans.extend(line.spans);
+                    spans.push(Span::raw(" ".repeat(padding - left)));
+                }
+                self.lines.push(Line::from(spans));
+            }
+
+            // rule under the header
+            if ind == 0 {
+                let mut spans = rest.clone();
+                let rule: Vec<String> = widths.iter().map(|width| "─".repeat(*width)).collect();
+                spans.push(Span::styled(rule.join("─┼─"), border));
+                self.lines.push(Line::from(spans));
+            }
+        }
+    }
+}
+
+fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(|span| display_width(&span.content)).sum()
+}
+
+fn heading_style(level: HeadingLevel) -> Style {
+    match level {
+        HeadingLevel::H1 => Style::default()
+            .fg(theme::colors::ACCENT)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        HeadingLevel::H2 => Style::default()
+            .fg(theme::colors::PRIMARY)
+            .add_modifier(Modifier::BOLD),
+        _ => Style::default()
+            .fg(theme::colors::INFO)
+            .add_modifier(Modifier::BOLD),
+    }
+}
+
+fn callout_color(kind: BlockQuoteKind) -> Color {
+    match kind {
+        BlockQuoteKind::Note => theme::colors::INFO,
+        BlockQuoteKind::Tip => theme::colors::SUCCESS,
+        BlockQuoteKind::Important => theme::colors::ACCENT,
+        BlockQuoteKind::Warning => theme::colors::WARNING,
+        BlockQuoteKind::Caution => theme::colors::ERROR,
+    }
+}
+
+fn callout_title(kind: BlockQuoteKind) -> String {
+    match kind {
+        BlockQuoteKind::Note => format!("{} Note", theme::icons::INFO),
+        BlockQuoteKind::Tip => format!("{} Tip", theme::icons::HINT),
+        BlockQuoteKind::Important => "❗ Important".to_string(),
+        BlockQuoteKind::Warning => "⚠ Warning".to_string(),
+        BlockQuoteKind::Caution => format!("{} Caution", theme::icons::ERROR),
+    }
+}
+
+/// Break styled text into lines of `width` cells at whitespace.
+/// Words longer than a line are broken anywhere.
+fn wrap_words(
+    spans: Vec<Span<'static>>,
+    first: Vec<Span<'static>>,
+    rest: Vec<Span<'static>>,
+    width: usize,
+) -> Vec<Line<'static>> {
+    // a word can be made of several spans like in `**bold**,`, `None` stands for whitespace
+    let mut words: Vec<Option<Vec<Span>>> = Vec::new();
+    for span in &spans {
+        let mut start = 0;
+        let content = span.content.as_ref();
+        while start < content.len() {
+            let is_space = content[start..].starts_with(char::is_whitespace);
+            let len = content[start..]
+                .find(|c: char| c.is_whitespace() != is_space)
+                .unwrap_or(content.len() - start);
+            let piece = Span::styled(&content[start..start + len], span.style);
+            match words.last_mut() {
+                _ if is_space => words.push(None),
+                Some(Some(word)) => word.push(piece),
+                _ => words.push(Some(vec![piece])),
+            }
+            start += len;
+        }
     }
 
+    let indent = spans_width(&rest);
+    let mut lines = Vec::new();
+    let mut x = spans_width(&first);
+    let mut line_start = x;
+    let mut line = first;
+    // a space is only added in front of the next word on the same line
+    let mut space = false;
+    let mut new_line = |line: &mut Vec<Span<'static>>, x: &mut usize, line_start: &mut usize| {
+        lines.push(Line::from(mem::replace(line, rest.clone())));
+        *x = indent;
+        *line_start = indent;
+    };
+
+    for word in words {
+        let Some(word) = word else {
+            space = x > line_start;
+            continue;
+        };
+
+        let word_width = spans_width(&word);
+        if mem::take(&mut space) {
+            if x + 1 + word_width > width {
+                new_line(&mut line, &mut x, &mut line_start);
+            } else {
+                line.push(Span::raw(" "));
+                x += 1;
+            }
+        } else if x > line_start && x + word_width > width {
+            new_line(&mut line, &mut x, &mut line_start);
+        }
+        if x + word_width <= width {
+            line.extend(
+                word.into_iter()
+                    .map(|span| Span::styled(span.content.into_owned(), span.style)),
+            );
+            x += word_width;
+            continue;
+        }
+
+        for span in word {
+            for grapheme in span.content.graphemes(true) {
+                let grapheme_width = display_width(grapheme);
+                if x > line_start && x + grapheme_width > width {
+                    new_line(&mut line, &mut x, &mut line_start);
+                }
+                line.push(Span::styled(grapheme.to_string(), span.style));
+                x += grapheme_width;
+            }
+        }
+    }
+    lines.push(Line::from(line));
     lines
 }
 
-/// Strip inline markdown markers (bold, code)
-fn render_inline_formatting(text: &str) -> String {
-    text.replace("**", "").to_string()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_quotes_and_wrapping() {
+        let lines = render_markdown(
+            "Some **bold** and `code` text.\n\n1. first\n2. second item\n   - nested\n\n> quoted",
+            14,
+        );
+        assert_eq!(
+            text(&lines),
+            [
+                "Some bold and",
+                "code text.",
+                "",
+                "1. first",
+                "2. second item",
+                "   • nested",
+                "",
+                "▎ quoted",
+            ],
+        );
+        assert!(
+            lines[0].spans[2]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+    }
+
+    #[test]
+    fn tables_fit_the_width() {
+        let lines = render_markdown(
+            "| Type | Meaning |\n| :--- | ---: |\n| `i32` | a signed integer |",
+            20,
+        );
+        assert_eq!(
+            text(&lines),
+            [
+                "Type │       Meaning",
+                "─────┼──────────────",
+                "i32  │      a signed",
+                "     │       integer",
+            ],
+        );
+        assert!(lines.iter().all(|line| spans_width(&line.spans) <= 20));
+    }
+
+    #[test]
+    fn code_block_rows() {
+        let runs = HashMap::from([(
+            1,
+            CodeRun {
+                success: true,
+                output: "3".to_string(),
+            },
+        )]);
+        let slide = render_slide(
+            "Intro\n\n```\na\nb\n```\n\n```rust,run\nprintln!(\"3\");\n```",
+            40,
+            &runs,
+            Some(0),
+        );
+        assert_eq!(
+            text(&slide.lines),
+            [
+                "Intro",
+                "",
+                "▌ a",
+                "▌ b",
+                "▌ Shift+Y yank · Shift+P put in the editor",
+                "",
+                "  println!(\"3\");",
+                "  ▶ Output",
+                "  │ 3",
+            ]
+        );
+        assert_eq!(slide.code_blocks, [2..4, 6..7]);
+    }
 }
@@ -1,18 +1,63 @@
 //! Frog Learning Panel rendering
 
 mod markdown;
-pub mod syntax;
+mod quiz;
 
-use crate::ui::state::TuiState;
-use crate::ui::theme;
+use crate::{
+    book,
+    ui::{lesson::Slide, state::TuiState, theme},
+};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
-use markdown::render_markdown;
+use markdown::{SlideLines, render_slide};
+
+// cells before the first step number
+const STEP_BAR_INDENT: u16 = 2;
+// the step numbers and the border below them
+const STEP_BAR_HEIGHT: u16 = 3;
+
+// step numbers are separated by a space
+fn step_label(step: usize) -> String {
+    format!(" {} ", step + 1)
+}
+
+/// Step whose number is at `(x, y)` in the Frog panel drawn in `area`
+pub fn step_at(area: Rect, state: &TuiState, x: u16, y: u16) -> Option<usize> {
+    let inner = area.inner(Margin::new(1, 1));
+    if y != inner.y {
+        return None;
+    }
+
+    let mut left = inner.x + STEP_BAR_INDENT;
+    for step in 0..state.current_frog_steps.len() {
+        let width = step_label(step).len() as u16;
+        if (left..left + width).contains(&x) {
+            return Some(step);
+        }
+        left += width + 1;
+    }
+    None
+}
+
+/// Code block of the current slide at `(x, y)` in the Frog panel drawn in `area`
+pub fn code_block_at(area: Rect, state: &TuiState, x: u16, y: u16) -> Option<usize> {
+    let inner = area.inner(Margin::new(1, 1));
+    let top = inner.y + STEP_BAR_HEIGHT;
+    if !(inner.left()..inner.right()).contains(&x) || !(top..inner.bottom()).contains(&y) {
+        return None;
+    }
+
+    let row = (y - top) as usize + state.frog_scroll;
+    state
+        .frog_block_rows
+        .iter()
+        .position(|rows| rows.contains(&row))
+}
 
 /// Render the Frog learning panel
 pub fn render_frog_panel(frame: &mut Frame, area: Rect, state: &mut TuiState) {
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(theme::colors::SUCCESS))
@@ -22,6 +67,13 @@ pub fn render_frog_panel(frame: &mut Frame, area: Rect, state: &mut TuiState) {
                 .fg(theme::colors::SUCCESS)
                 .add_modifier(Modifier::BOLD),
         ));
+    // the first chapter of the Book explaining the exercise, `rustlings docs` prints all
+    if let Some(page) = state.app_state.current_exercise().book.first() {
+        block = block.title_bottom(Span::styled(
+            format!(" {} Book {} ", theme::icons::SOLUTION, book::label(page)),
+            Style::default().fg(theme::colors::TEXT_DIM),
+        ));
+    }
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -41,22 +93,22 @@ pub fn render_frog_panel(frame: &mut Frame, area: Rect, state: &mut TuiState) {
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .constraints([Constraint::Length(STEP_BAR_HEIGHT), Constraint::Min(0)])
         .split(inner);
 
     // Step Bar
     render_step_bar(frame, chunks[0], state);
 
     // Content - clone to avoid borrow conflict
-    if let Some(content_str) = state.current_frog_steps.get(state.frog_step).cloned() {
-        render_content(frame, chunks[1], &content_str, state);
+    if let Some(slide) = state.current_frog_steps.get(state.frog_step).cloned() {
+        render_content(frame, chunks[1], &slide, state);
     }
 }
 
 fn render_step_bar(frame: &mut Frame, area: Rect, state: &TuiState) {
     let total_steps = state.current_frog_steps.len();
     let mut step_spans = Vec::new();
-    step_spans.push(Span::raw("  "));
+    step_spans.push(Span::raw(" ".repeat(STEP_BAR_INDENT as usize)));
 
     for i in 0..total_steps {
         let is_current = i == state.frog_step;
@@ -69,7 +121,7 @@ fn render_step_bar(frame: &mut Frame, area: Rect, state: &TuiState) {
             Style::default().fg(theme::colors::TEXT_DIM)
         };
 
-        step_spans.push(Span::styled(format!(" {} ", i + 1), style));
+        step_spans.push(Span::styled(step_label(i), style));
         step_spans.push(Span::raw(" "));
     }
 
@@ -81,10 +133,42 @@ fn render_step_bar(frame: &mut Frame, area: Rect, state: &TuiState) {
     frame.render_widget(step_bar, area);
 }
 
-fn render_content(frame: &mut Frame, area: Rect, content_str: &str, state: &mut TuiState) {
-    let content_lines = render_markdown(content_str);
-    let total_lines = content_lines.len();
+// lines of a slide, with the output of its code blocks and the choices and feedback of its quiz
+fn slide_lines(slide: &Slide, state: &TuiState, width: usize) -> SlideLines {
+    let SlideLines { lines, code_blocks } =
+        render_slide(&slide.body, width, &state.frog_runs, state.frog_block);
+    let Some(quiz) = &slide.quiz else {
+        return SlideLines { lines, code_blocks };
+    };
+
+    let chosen = state
+        .app_state
+        .quiz_answer(state.frog_step)
+        .map(|answer| answer.choice);
+    // the question is above the body
+    let shift = quiz::QUESTION_LINES;
+    SlideLines {
+        lines: quiz::render_quiz(quiz, lines, chosen, width),
+        code_blocks: code_blocks
+            .into_iter()
+            .map(|rows| rows.start + shift..rows.end + shift)
+            .collect(),
+    }
+}
+
+fn render_content(frame: &mut Frame, area: Rect, slide: &Slide, state: &mut TuiState) {
+    // make room for the scrollbar if the slide doesn't fit
     let visible_height = area.height as usize;
+    let mut slide_lines = self::slide_lines(slide, state, area.width as usize);
+    if slide_lines.lines.len() > visible_height {
+        slide_lines = self::slide_lines(slide, state, area.width.saturating_sub(2) as usize);
+    }
+    let SlideLines {
+        lines: content_lines,
+        code_blocks,
+    } = slide_lines;
+    state.frog_block_rows = code_blocks;
+    let total_lines = content_lines.len();
 
     let max_scroll = total_lines.saturating_sub(visible_height);
     if state.frog_scroll > max_scroll {
@@ -96,7 +180,6 @@ fn render_content(frame: &mut Frame, area: Rect, content_str: &str, state: &mut
 
     let content = Paragraph::new(content_lines)
         .style(Style::default().fg(theme::colors::TEXT))
-        .wrap(Wrap { trim: false })
         .scroll((state.frog_scroll as u16, 0));
 
     let content_area = if total_lines > visible_height {
@@ -0,0 +1,104 @@
+//! Quiz slides: the question above the slide, the numbered choices below it
+//! and the feedback once a choice is picked
+
+use ratatui::prelude::*;
+
+use crate::ui::{lesson::Quiz, theme};
+
+use super::markdown::render_markdown;
+
+// cells of a choice label like ` 1 ` and the space after it
+const LABEL_WIDTH: usize = 4;
+/// Lines above the body of the slide: the question and an empty line
+pub const QUESTION_LINES: usize = 2;
+
+/// Lines of a quiz slide around the rendered `body`, `chosen` being the answered choice
+pub fn render_quiz(
+    quiz: &Quiz,
+    body: Vec<Line<'static>>,
+    chosen: Option<usize>,
+    width: usize,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("❓ {}", quiz.kind.title()),
+            Style::default()
+                .fg(theme::colors::ACCENT)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(body);
+    lines.push(Line::from(""));
+
+    for (ind, choice) in quiz.choices.iter().enumerate() {
+        // the right choice and a wrong pick are marked once answered, the others fade
+        let (color, mark) = match chosen {
+            None => (Some(theme::colors::ACCENT), ""),
+            Some(_) if ind == quiz.answer => (Some(theme::colors::SUCCESS), theme::icons::DONE),
+            Some(chosen) if ind == chosen => (Some(theme::colors::ERROR), theme::icons::ERROR),
+            Some(_) => (None, ""),
+        };
+        let label_style = match color {
+            Some(color) => Style::default().fg(theme::colors::BG_DARK).bg(color),
+            None => Style::default().fg(theme::colors::MUTED),
+        };
+
+        let choice_lines = render_markdown(choice, width.saturating_sub(LABEL_WIDTH));
+        for (line_ind, line) in choice_lines.into_iter().enumerate() {
+            let mut spans = if line_ind == 0 {
+                vec![
+                    Span::styled(
+                        format!(" {} ", ind + 1),
+                        label_style.add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" "),
+                ]
+            } else {
+                vec![Span::raw(" ".repeat(LABEL_WIDTH))]
+            };
+            spans.extend(line.spans);
+            if line_ind == 0 && !mark.is_empty() {
+                spans.push(Span::styled(
+                    format!(" {mark}"),
+                    Style::default().fg(color.unwrap_or(theme::colors::TEXT)),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+    lines.push(Line::from(""));
+
+    let Some(chosen) = chosen else {
+        lines.push(Line::from(Span::styled(
+            format!("Press 1-{} to answer", quiz.choices.len()),
+            Style::default().fg(theme::colors::TEXT_DIM),
+        )));
+        return lines;
+    };
+
+    let feedback = if chosen == quiz.answer {
+        Span::styled(
+            format!("{} Correct!", theme::icons::DONE),
+            Style::default()
+                .fg(theme::colors::SUCCESS)
+                .add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::styled(
+            format!(
+                "{} Not quite, the answer is {}.",
+                theme::icons::ERROR,
+                quiz.answer + 1
+            ),
+            Style::default()
+                .fg(theme::colors::ERROR)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    lines.push(Line::from(feedback));
+    if let Some(explanation) = &quiz.explanation {
+        lines.extend(render_markdown(explanation, width));
+    }
+    lines
+}
@@ -21,7 +21,7 @@ pub fn render_header(frame: &mut Frame, area: Rect, state: &TuiState) {
             .add_modifier(Modifier::BOLD)
     };
 
-    let header_line = Line::from(vec![
+    let mut header_spans = vec![
         Span::styled(
             format!(" {} ", theme::icons::CRAB),
             Style::default()
@@ -51,8 +51,21 @@ pub fn render_header(frame: &mut Frame, area: Rect, state: &TuiState) {
                 .fg(theme::colors::SUCCESS)
                 .add_modifier(Modifier::BOLD),
         ),
-    ]);
+    ];
 
-    let header = Paragraph::new(header_line);
+    // last so that a narrow terminal cuts them off first
+    let concepts = &state.app_state.current_exercise().concepts;
+    if !concepts.is_empty() {
+        header_spans.push(Span::styled(
+            " │ ",
+            Style::default().fg(theme::colors::MUTED),
+        ));
+        header_spans.push(Span::styled(
+            concepts.join(" · "),
+            Style::default().fg(theme::colors::ACCENT),
+        ));
+    }
+
+    let header = Paragraph::new(Line::from(header_spans));
     frame.render_widget(header, area);
 }
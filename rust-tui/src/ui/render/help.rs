@@ -24,15 +24,6 @@ fn key(binding: &str, desc: &str) -> Line<'static> {
 }
 
 pub fn render_help_modal(frame: &mut Frame, area: Rect) {
-    // Calculate centered modal area (60% width, 70% height)
-    let modal_width = (area.width * 60 / 100).clamp(50, 80);
-    let modal_height = (area.height * 70 / 100).clamp(20, 35);
-    let modal_x = area.x + (area.width.saturating_sub(modal_width)) / 2;
-    let modal_y = area.y + (area.height.saturating_sub(modal_height)) / 2;
-    let modal_area = Rect::new(modal_x, modal_y, modal_width, modal_height);
-
-    frame.render_widget(Clear, modal_area);
-
     let help_text = vec![
         Line::from(vec![Span::styled(
             "   Rustlings TUI Help   ",
@@ -44,21 +35,49 @@ pub fn render_help_modal(frame: &mut Frame, area: Rect) {
         header("NAVIGATION"),
         key("]", "Next exercise"),
         key("[", "Previous exercise"),
-        key("Shift+J/K", "Scroll output down/up"),
+        key("Ctrl+D/U", "Scroll output down/up"),
         key("PgDn/PgUp", "Fast scroll output"),
         Line::from(""),
         header("EDITING"),
         key("i", "Enter Insert mode"),
         key("Esc", "Return to Normal mode"),
         key("h/j/k/l", "Vim cursor movement"),
+        key("v/V/Ctrl+V", "Visual char/line/block"),
+        key(">/< gc", "Indent/outdent, comment (visual)"),
+        key("I/A", "Insert/append on block (visual)"),
+        key("== gg=G", "Reindent line / whole file"),
+        key("u g- g+", "Undo, older/newer state (all branches)"),
+        key("za zc zo", "Toggle/close/open fold"),
+        key("zM zR", "Close/open all folds"),
+        key("K / gd", "Type info / go to definition"),
+        key("gK / :doc", "Offline std docs, o shows the page path"),
+        key("Ctrl+Space", "Complete (insert mode)"),
+        key("Ctrl+N/P", "Complete words, std items (insert)"),
+        key("fn<Tab>", "Expand snippet, Tab/S-Tab to jump"),
+        key("Mouse", "Click to place cursor, drag to select"),
         Line::from(""),
         header("COMMANDS"),
         key(":w", "Save file"),
         key(":c", "Compile/check"),
+        key(":fmt", "Format with rustfmt"),
+        key(":fmtonsave", "Toggle format on save"),
+        key(":lsp", "Toggle rust-analyzer"),
+        key(":wrap", "Toggle soft wrap of long lines"),
+        key(":e <path>", "Open a file (Tab completes)"),
+        key(":ls :b N :bd", "List, switch, close files"),
+        key(":grep <term>", "Search lessons, hints, exercises"),
+        key(":cc N :cn :cp", "Jump to a search match"),
+        key(":concept <name>", "Explain a concept, list exercises"),
+        key(":earlier 2m", "Go back in time (:later forward)"),
         key(":hint / h", "Show hint"),
         key(":sol / s", "Toggle solution view"),
         key("Shift+F", "Toggle 🐸 Frog panel"),
+        key("1-9", "Answer a Frog quiz"),
+        key("Shift+R", "Run the code on a Frog slide"),
+        key("Tab Shift+Y/P", "Pick Frog code, yank/put it"),
         key("Ctrl+O", "Expand output panel"),
+        key("Ctrl+W < > + -", "Resize editor (or drag borders)"),
+        key("Ctrl+W x s v =", "Swap, stack, side by side, reset"),
         key(":auto", "Toggle auto-advance"),
         key(":watch", "Toggle auto-compile"),
         key(":reset", "Reset exercise"),
@@ -70,6 +89,15 @@ pub fn render_help_modal(frame: &mut Frame, area: Rect) {
         )]),
     ];
 
+    // Calculate centered modal area (60% width, tall enough for all bindings)
+    let modal_width = (area.width * 60 / 100).clamp(50, 80);
+    let modal_height = (help_text.len() as u16 + 2).min(area.height);
+    let modal_x = area.x + (area.width.saturating_sub(modal_width)) / 2;
+    let modal_y = area.y + (area.height.saturating_sub(modal_height)) / 2;
+    let modal_area = Rect::new(modal_x, modal_y, modal_width, modal_height);
+
+    frame.render_widget(Clear, modal_area);
+
     let help_block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
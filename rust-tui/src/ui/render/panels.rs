@@ -1,4 +1,4 @@
-use super::{highlight_rust_line, strip_ansi_codes};
+use super::{strip_ansi_codes, syntax::highlight_code};
 use crate::ui::state::TuiState;
 use crate::ui::theme;
 use ratatui::prelude::*;
@@ -20,11 +20,8 @@ pub fn render_solution(frame: &mut Frame, area: Rect, state: &TuiState) {
     frame.render_widget(block, area);
 
     if let Some(ref content) = state.solution_content {
-        let lines: Vec<Line> = content
-            .lines()
-            .map(|line| highlight_rust_line(line, false))
-            .collect();
-        let solution_widget = Paragraph::new(lines);
+        let solution_widget =
+            Paragraph::new(highlight_code(content)).scroll((state.solution_scroll as u16, 0));
         frame.render_widget(solution_widget, inner);
     }
 }
@@ -0,0 +1,94 @@
+//! Rust syntax highlighting shared by the editor, solution and Frog panels
+
+use std::ops::Range;
+
+use ratatui::prelude::*;
+
+use crate::ui::{
+    buffer::utf16_to_byte,
+    lexer::{self, LexState, Token, TokenKind},
+    lsp::SemanticToken,
+    theme,
+};
+
+fn spans(line: &str, tokens: &[Token]) -> Vec<Span<'static>> {
+    tokens
+        .iter()
+        .map(|token| {
+            Span::styled(
+                line[token.range.clone()].to_string(),
+                theme::token_style(token.kind),
+            )
+        })
+        .collect()
+}
+
+/// Highlight one line starting in `state` which is advanced to the next line's state
+pub fn highlight_line(line: &str, state: &mut LexState) -> Vec<Span<'static>> {
+    highlight_line_semantic(line, state, &[])
+}
+
+/// Like `highlight_line` but identifiers are colored by rust-analyzer's semantic tokens
+/// where the lexer could only guess (e.g. functions or lowercase type aliases)
+pub fn highlight_line_semantic(
+    line: &str,
+    state: &mut LexState,
+    semantic: &[SemanticToken],
+) -> Vec<Span<'static>> {
+    let (tokens, next) = lexer::tokenize_line(line, *state);
+    *state = next;
+
+    let mut spans = spans(line, &tokens);
+    for token in semantic {
+        let start = utf16_to_byte(line, token.start);
+        let range = start..utf16_to_byte(line, token.start + token.len);
+        let guessed = tokens
+            .iter()
+            .filter(|t| t.range.start < range.end && range.start < t.range.end)
+            .all(|t| matches!(t.kind, TokenKind::Plain | TokenKind::Type));
+        if guessed {
+            spans = patch_range(spans, range, theme::token_style(token.kind));
+        }
+    }
+    spans
+}
+
+/// Patch the style of a byte range of a highlighted line, splitting the spans at its ends
+pub fn patch_range(
+    spans: Vec<Span<'static>>,
+    range: Range<usize>,
+    patch: Style,
+) -> Vec<Span<'static>> {
+    let mut patched = Vec::with_capacity(spans.len() + 2);
+    let mut offset = 0;
+    for span in spans {
+        let (start, end) = (offset, offset + span.content.len());
+        offset = end;
+        let (from, to) = (range.start.clamp(start, end), range.end.clamp(start, end));
+        if from == to {
+            patched.push(span);
+            continue;
+        }
+
+        let parts = [
+            (start..from, span.style),
+            (from..to, span.style.patch(patch)),
+            (to..end, span.style),
+        ];
+        for (part, style) in parts {
+            if !part.is_empty() {
+                let text = &span.content[part.start - start..part.end - start];
+                patched.push(Span::styled(text.to_string(), style));
+            }
+        }
+    }
+    patched
+}
+
+/// Highlight a whole snippet of code
+pub fn highlight_code(code: &str) -> Vec<Line<'static>> {
+    let mut state = LexState::default();
+    code.lines()
+        .map(|line| Line::from(highlight_line(line, &mut state)))
+        .collect()
+}
@@ -0,0 +1,279 @@
+//! `:grep`: search the Frog lessons, the hints, the chapter READMEs and the exercise files.
+//! The matches are listed in the output panel grouped by exercise. Like Vim's quickfix list,
+//! `:cc N` jumps to a match and `:cn`/`:cp` to the next or previous one.
+
+use std::{collections::HashSet, fmt::Write, fs};
+
+use anyhow::Result;
+
+use crate::ui::{buffers::EXERCISE_BUFFER, lesson::SLIDE_SEPARATOR, state::TuiState, theme};
+
+/// More matches aren't listed, the term should be more specific
+const MAX_MATCHES: usize = 200;
+// longer lines are cut in the list
+const MAX_LINE_CHARS: usize = 100;
+
+/// Where a match was found
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MatchSource {
+    /// Slide of the exercise's Frog lesson
+    Lesson {
+        slide: usize,
+    },
+    Hint,
+    /// README of the exercise's chapter, listed under its first exercise
+    Readme {
+        path: String,
+        row: usize,
+    },
+    Exercise {
+        row: usize,
+    },
+}
+
+pub struct SearchMatch {
+    pub exercise_ind: usize,
+    pub source: MatchSource,
+    /// The matching line without its indentation
+    pub line: String,
+}
+
+/// Smart case like Vim: the search ignores case unless the term contains an uppercase letter
+pub fn matches(line: &str, term: &str) -> bool {
+    if term.chars().any(char::is_uppercase) {
+        line.contains(term)
+    } else {
+        line.to_lowercase().contains(term)
+    }
+}
+
+/// Rows and trimmed lines of `text` that match `term`
+fn matching_lines<'a>(text: &'a str, term: &'a str) -> impl Iterator<Item = (usize, String)> + 'a {
+    text.lines()
+        .enumerate()
+        .filter(move |(_, line)| matches(line, term))
+        .map(|(row, line)| (row, line.trim().to_string()))
+}
+
+impl TuiState<'_> {
+    /// `:grep <term>`: list the matches in the output panel
+    pub fn grep(&mut self, term: &str) {
+        let term = term.trim();
+        if term.is_empty() {
+            self.output = format!("{} Usage: :grep <term>", theme::icons::ERROR);
+            return;
+        }
+
+        let mut found = Vec::new();
+        let mut searched_dirs = HashSet::new();
+        for (ind, exercise) in self.app_state.exercises().iter().enumerate() {
+            let mut push = |source, line| {
+                found.push(SearchMatch {
+                    exercise_ind: ind,
+                    source,
+                    line,
+                })
+            };
+
+            if let Ok(Some(lesson)) = self.app_state.lesson(ind) {
+                for (slide, text) in lesson.split(SLIDE_SEPARATOR).enumerate() {
+                    for (_, line) in matching_lines(text, term) {
+                        push(MatchSource::Lesson { slide }, line);
+                    }
+                }
+            }
+            for (_, line) in matching_lines(exercise.hint, term) {
+                push(MatchSource::Hint, line);
+            }
+            if let Some(dir) = exercise.dir
+                && searched_dirs.insert(dir)
+            {
+                let path = format!("exercises/{dir}/README.md");
+                if let Ok(readme) = fs::read_to_string(&path) {
+                    for (row, line) in matching_lines(&readme, term) {
+                        let path = path.clone();
+                        push(MatchSource::Readme { path, row }, line);
+                    }
+                }
+            }
+            if let Ok(source) = fs::read_to_string(exercise.path) {
+                for (row, line) in matching_lines(&source, term) {
+                    push(MatchSource::Exercise { row }, line);
+                }
+            }
+        }
+
+        self.grep_matches = found;
+        self.grep_match = None;
+        self.output = self.match_list(term);
+        self.output_scroll = 0;
+    }
+
+    fn match_list(&self, term: &str) -> String {
+        if self.grep_matches.is_empty() {
+            return format!("{} No matches for `{term}`", theme::icons::INFO);
+        }
+
+        let mut list = format!(
+            "{} {} matches for `{term}` (:cc N jumps to one, :cn/:cp to the next/previous)",
+            theme::icons::SEARCH,
+            self.grep_matches.len(),
+        );
+        let mut exercise_ind = None;
+        for (ind, found) in self.grep_matches.iter().enumerate().take(MAX_MATCHES) {
+            if exercise_ind != Some(found.exercise_ind) {
+                exercise_ind = Some(found.exercise_ind);
+                let exercise = &self.app_state.exercises()[found.exercise_ind];
+                let _ = write!(list, "\n\n{}", exercise.name);
+            }
+            let line: String = found.line.chars().take(MAX_LINE_CHARS).collect();
+            let _ = write!(
+                list,
+                "\n{:>4}  {}  {line}",
+                ind + 1,
+                self.match_location(found)
+            );
+        }
+        if self.grep_matches.len() > MAX_MATCHES {
+            let _ = write!(
+                list,
+                "\n\n{} Only the first {MAX_MATCHES} matches are listed",
+                theme::icons::INFO
+            );
+        }
+        list
+    }
+
+    fn match_location(&self, found: &SearchMatch) -> String {
+        match &found.source {
+            MatchSource::Lesson { slide } => format!("🐸 slide {}", slide + 1),
+            MatchSource::Hint => format!("{} hint", theme::icons::HINT),
+            MatchSource::Readme { path, row } => format!("{path}:{}", row + 1),
+            MatchSource::Exercise { row } => {
+                let exercise = &self.app_state.exercises()[found.exercise_ind];
+                format!("{}:{}", exercise.path, row + 1)
+            }
+        }
+    }
+
+    /// `:cc N`: go to the match numbered `num` in the list, the current one without a number
+    pub fn cmd_jump_to_match(&mut self, num: &str) -> Result<()> {
+        let num = num.trim();
+        if num.is_empty() {
+            return self.jump_to_match(self.grep_match.unwrap_or(0));
+        }
+        match num.parse::<usize>() {
+            Ok(num) if num >= 1 => self.jump_to_match(num - 1),
+            _ => {
+                self.output = format!("{} Invalid match number: {num}", theme::icons::ERROR);
+                Ok(())
+            }
+        }
+    }
+
+    /// `:cn`/`:cp`: go to the next or previous match
+    pub fn jump_to_next_match(&mut self, forward: bool) -> Result<()> {
+        let ind = match (self.grep_match, forward) {
+            (None, _) => 0,
+            (Some(ind), true) => ind + 1,
+            (Some(ind), false) => ind.saturating_sub(1),
+        };
+        self.jump_to_match(ind.min(self.grep_matches.len().saturating_sub(1)))
+    }
+
+    /// Show the match `ind`, switching to its exercise
+    fn jump_to_match(&mut self, ind: usize) -> Result<()> {
+        let Some(found) = self.grep_matches.get(ind) else {
+            self.output = if self.grep_matches.is_empty() {
+                format!(
+                    "{} No matches, search with :grep <term>",
+                    theme::icons::ERROR
+                )
+            } else {
+                format!(
+                    "{} There are only {} matches",
+                    theme::icons::ERROR,
+                    self.grep_matches.len()
+                )
+            };
+            return Ok(());
+        };
+        let exercise_ind = found.exercise_ind;
+        let source = found.source.clone();
+        let location = format!(
+            "[{}/{}] {}  {}",
+            ind + 1,
+            self.grep_matches.len(),
+            self.app_state.exercises()[exercise_ind].name,
+            self.match_location(found),
+        );
+
+        if exercise_ind != self.app_state.current_exercise_ind() {
+            // switching exercises reloads the exercise buffer from the disk
+            let exercise_modified = if self.active_buffer == EXERCISE_BUFFER {
+                self.modified
+            } else {
+                self.buffers[EXERCISE_BUFFER].modified
+            };
+            if exercise_modified {
+                self.output = format!(
+                    "{} Unsaved changes in the exercise! Use :w before jumping to another one",
+                    theme::icons::ERROR
+                );
+                return Ok(());
+            }
+            self.app_state.set_current_exercise_ind(exercise_ind)?;
+            self.reload_exercise()?;
+        }
+        self.grep_match = Some(ind);
+        self.output = format!("{} {location}", theme::icons::SEARCH);
+
+        match source {
+            MatchSource::Lesson { slide } => {
+                self.show_frog = true;
+                let last = self.current_frog_steps.len().saturating_sub(1);
+                self.set_frog_step(slide.min(last));
+            }
+            MatchSource::Hint => {
+                let hint = self.app_state.current_exercise().hint;
+                self.output = format!("{location}\n\n{} {hint}", theme::icons::HINT);
+            }
+            MatchSource::Readme { path, row } => {
+                self.open_file(&path);
+                if self.file_path == path {
+                    self.goto_row(row);
+                }
+            }
+            MatchSource::Exercise { row } => {
+                self.switch_buffer(EXERCISE_BUFFER);
+                self.goto_row(row);
+            }
+        }
+        Ok(())
+    }
+
+    fn goto_row(&mut self, row: usize) {
+        self.editor.cursor_row = row.min(self.editor.line_count() - 1);
+        self.editor.move_to_first_non_whitespace();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_case() {
+        assert!(matches("Use the `?` operator", "?"));
+        assert!(matches("impl Trait for T", "impl trait"));
+        assert!(matches("impl Trait for T", "Trait"));
+        assert!(!matches("trait objects", "Trait"));
+
+        let text = "fn main() {\n    let x = 5;\n    let y = x;\n}";
+        let found: Vec<_> = matching_lines(text, "let").collect();
+        assert_eq!(
+            found,
+            [(1, "let x = 5;".to_string()), (2, "let y = x;".to_string())]
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Snippet expansion with tab-stops in insert mode.
+//! Bodies use the LSP syntax: `$1`, `${1:placeholder}` and `$0` for the final cursor position.
+
+use std::{ops::Range, path::Path};
+
+use crate::ui::{lexer, state::TuiState};
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("fn", "fn ${1:name}(${2}) {\n    $0\n}"),
+    ("impl", "impl ${1:Type} {\n    $0\n}"),
+    (
+        "struct",
+        "struct ${1:Name} {\n    ${2:field}: ${3:Type},\n}",
+    ),
+    ("enum", "enum ${1:Name} {\n    ${2:Variant},\n}"),
+    (
+        "match",
+        "match ${1:value} {\n    ${2:pattern} => ${3:todo!()},\n}",
+    ),
+    ("if", "if ${1:condition} {\n    $0\n}"),
+    ("for", "for ${1:item} in ${2:iter} {\n    $0\n}"),
+    ("while", "while ${1:condition} {\n    $0\n}"),
+    ("test", "#[test]\nfn ${1:name}() {\n    $0\n}"),
+    (
+        "tests",
+        "#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn ${1:name}() {\n        $0\n    }\n}",
+    ),
+];
+
+// snippets for the topic of a chapter, keyed by its directory in `exercises/`
+const CHAPTERS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "05_vecs",
+        &[("vecnew", "let mut ${1:v}: Vec<${2:i32}> = Vec::new();")],
+    ),
+    (
+        "11_hashmaps",
+        &[
+            (
+                "hashmap",
+                "let mut ${1:map}: HashMap<${2:String}, ${3:u32}> = HashMap::new();",
+            ),
+            (
+                "entry",
+                "*${1:map}.entry(${2:key}).or_insert(${3:0}) += ${4:1};",
+            ),
+        ],
+    ),
+    (
+        "12_options",
+        &[(
+            "iflet",
+            "if let Some(${1:value}) = ${2:option} {\n    $0\n}",
+        )],
+    ),
+    (
+        "13_error_handling",
+        &[(
+            "fnres",
+            "fn ${1:name}(${2}) -> Result<${3:()}, ${4:Box<dyn Error>}> {\n    $0\n}",
+        )],
+    ),
+    (
+        "15_traits",
+        &[
+            (
+                "trait",
+                "trait ${1:Name} {\n    fn ${2:method}(&self)$0;\n}",
+            ),
+            ("implfor", "impl ${1:Trait} for ${2:Type} {\n    $0\n}"),
+        ],
+    ),
+    (
+        "16_lifetimes",
+        &[(
+            "fnlt",
+            "fn ${1:name}<'a>(${2:x}: &'a ${3:str}) -> &'a ${4:str} {\n    $0\n}",
+        )],
+    ),
+    (
+        "18_iterators",
+        &[(
+            "itermap",
+            "iter().map(|${1:x}| ${2}).collect::<${3:Vec<_>}>()$0",
+        )],
+    ),
+    (
+        "20_threads",
+        &[(
+            "spawn",
+            "let ${1:handle} = thread::spawn(move || {\n    $0\n});",
+        )],
+    ),
+    (
+        "21_macros",
+        &[(
+            "macro",
+            "macro_rules! ${1:name} {\n    (${2}) => {\n        $0\n    };\n}",
+        )],
+    ),
+];
+
+/// Body of the snippet triggered by `trigger`.
+/// User snippets take precedence over the chapter's and the built-in ones.
+fn find_body<'a>(state: &'a TuiState, trigger: &str) -> Option<&'a str> {
+    if let Some(body) = state.config.snippets.get(trigger) {
+        return Some(body);
+    }
+
+    let chapter = Path::new(&state.file_path)
+        .parent()
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str());
+    CHAPTERS
+        .iter()
+        .filter(|(name, _)| Some(*name) == chapter)
+        .flat_map(|(_, snippets)| snippets.iter())
+        .chain(BUILTIN)
+        .find(|(name, _)| *name == trigger)
+        .map(|(_, body)| *body)
+}
+
+/// Text of an expanded snippet and the char ranges of its tab-stops in jump order
+struct Expansion {
+    text: String,
+    stops: Vec<Range<usize>>,
+}
+
+/// Expand `body` with `indent` added to every non-empty line after the first.
+/// The final stop is `$0`, or the end of the text without one.
+fn expand(body: &str, indent: &str) -> Expansion {
+    let mut text = String::new();
+    let mut len = 0;
+    let mut stops: Vec<(usize, Range<usize>)> = Vec::new();
+    let mut line_start = false;
+
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            text.push('\n');
+            len += 1;
+            line_start = true;
+            continue;
+        }
+        if line_start {
+            text.push_str(indent);
+            len += indent.chars().count();
+            line_start = false;
+        }
+
+        let braced = c == '$' && chars.peek() == Some(&'{');
+        let numbered = c == '$' && chars.peek().is_some_and(char::is_ascii_digit);
+        if !(braced || numbered) {
+            text.push(c);
+            len += 1;
+            continue;
+        }
+
+        if braced {
+            chars.next();
+        }
+        let mut number = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            number.push(digit);
+        }
+        let mut placeholder = String::new();
+        if braced {
+            if chars.next_if_eq(&':').is_some() {
+                placeholder.extend(chars.by_ref().take_while(|&c| c != '}'));
+            } else {
+                chars.next_if_eq(&'}');
+            }
+        }
+
+        let start = len;
+        text.push_str(&placeholder);
+        len += placeholder.chars().count();
+        // `$0` jumps last, mirrored stops only count once
+        let index = number.parse().unwrap_or(0);
+        let order = if index == 0 { usize::MAX } else { index };
+        if stops.iter().all(|(other, _)| *other != order) {
+            stops.push((order, start..len));
+        }
+    }
+
+    stops.sort_by_key(|(order, _)| *order);
+    if stops.last().is_none_or(|(order, _)| *order != usize::MAX) {
+        stops.push((usize::MAX, len..len));
+    }
+    Expansion {
+        text,
+        stops: stops.into_iter().map(|(_, range)| range).collect(),
+    }
+}
+
+/// Tab-stops of the snippet being filled in, as rope char ranges
+pub struct SnippetSession {
+    stops: Vec<Range<usize>>,
+    current: usize,
+    /// The placeholder of the current stop is untouched and replaced by typing
+    pub pristine: bool,
+}
+
+impl SnippetSession {
+    /// Range of the current placeholder if it wasn't edited yet
+    pub fn pristine_range(&self) -> Option<Range<usize>> {
+        let range = &self.stops[self.current];
+        (self.pristine && !range.is_empty()).then(|| range.clone())
+    }
+}
+
+impl TuiState<'_> {
+    /// Expand the snippet named by the word before the cursor.
+    /// Returns `false` if there is no such snippet.
+    pub fn expand_snippet(&mut self) -> bool {
+        let row = self.editor.cursor_row;
+        let start_col = self.editor.word_start_before_cursor(lexer::is_ident_char);
+        let trigger = self
+            .editor
+            .buffer
+            .slice((row, start_col), (row, self.editor.cursor_col));
+        if trigger.is_empty() {
+            return false;
+        }
+        let Some(body) = find_body(self, &trigger) else {
+            return false;
+        };
+
+        let line = self.editor.buffer.line(row);
+        let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+        let expansion = expand(body, &indent);
+
+        self.modified = true;
+        self.completion = None;
+        self.editor.save_snapshot();
+        let buffer = &mut self.editor.buffer;
+        buffer.remove((row, start_col), (row, self.editor.cursor_col));
+        let offset = buffer.char_idx((row, start_col));
+        buffer.insert((row, start_col), &expansion.text);
+
+        let stops = expansion.stops.into_iter();
+        self.snippet = Some(SnippetSession {
+            stops: stops.map(|r| r.start + offset..r.end + offset).collect(),
+            current: 0,
+            pristine: false,
+        });
+        self.enter_snippet_stop();
+        true
+    }
+
+    /// Tab/Shift+Tab: jump to the next or previous tab-stop
+    pub fn jump_snippet_stop(&mut self, forward: bool) {
+        let Some(session) = &mut self.snippet else {
+            return;
+        };
+        session.current = if forward {
+            session.current + 1
+        } else {
+            session.current.saturating_sub(1)
+        };
+        self.enter_snippet_stop();
+    }
+
+    // put the cursor on the current stop, the session ends on the final one
+    fn enter_snippet_stop(&mut self) {
+        let Some(session) = &mut self.snippet else {
+            return;
+        };
+        let current = session.current.min(session.stops.len() - 1);
+        let range = session.stops[current].clone();
+        session.current = current;
+        session.pristine = true;
+
+        (self.editor.cursor_row, self.editor.cursor_col) = self.editor.buffer.pos_at(range.start);
+        if current == session.stops.len() - 1 {
+            self.snippet = None;
+        }
+    }
+
+    /// Remove the untouched placeholder of the current stop before it's typed over
+    pub fn clear_snippet_placeholder(&mut self) {
+        let Some(session) = &mut self.snippet else {
+            return;
+        };
+        let Some(range) = session.pristine_range() else {
+            return;
+        };
+        session.pristine = false;
+
+        self.modified = true;
+        self.editor.save_snapshot();
+        let buffer = &mut self.editor.buffer;
+        let (start, end) = (buffer.pos_at(range.start), buffer.pos_at(range.end));
+        buffer.remove(start, end);
+        (self.editor.cursor_row, self.editor.cursor_col) = start;
+    }
+
+    /// Shift the tab-stops after an edit in insert mode.
+    /// `len_before` and `cursor_before` are the char length and cursor index before the key.
+    /// Editing outside of the current placeholder ends the snippet.
+    pub fn track_snippet_edit(&mut self, len_before: usize, cursor_before: usize) {
+        let Some(session) = &mut self.snippet else {
+            return;
+        };
+        let buffer = &self.editor.buffer;
+        let delta = buffer.rope().len_chars() as isize - len_before as isize;
+        if delta == 0 {
+            return;
+        }
+
+        let cursor = buffer.char_idx((self.editor.cursor_row, self.editor.cursor_col));
+        let edit_pos = cursor_before.min(cursor);
+        let current = &session.stops[session.current];
+        if !(current.start..=current.end).contains(&edit_pos) {
+            self.snippet = None;
+            return;
+        }
+
+        let current = session.current;
+        for (ind, stop) in session.stops.iter_mut().enumerate() {
+            if ind == current {
+                stop.end = stop.end.saturating_add_signed(delta).max(stop.start);
+            } else if stop.start >= edit_pos {
+                stop.start = stop.start.saturating_add_signed(delta);
+                stop.end = stop.end.saturating_add_signed(delta);
+            }
+        }
+        session.pristine = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_with_indent_and_stops() {
+        let expansion = expand("fn ${1:name}($2) {\n\n    $0\n}", "  ");
+        assert_eq!(expansion.text, "fn name() {\n\n      \n  }");
+        // `name`, the empty parameter list, then `$0`
+        assert_eq!(expansion.stops, [3..7, 8..8, 19..19]);
+
+        // without `$0` the snippet ends after its text
+        let expansion = expand("${1:a} + ${1:b}", "");
+        assert_eq!(expansion.text, "a + b");
+        assert_eq!(expansion.stops, [0..1, 5..5]);
+    }
+}
@@ -1,8 +1,29 @@
 //! TUI state definitions
 
-use std::time::{Instant, SystemTime};
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::mpsc::Receiver,
+    time::{Instant, SystemTime},
+};
 
-use crate::ui::editor::TextEditor;
+use anyhow::Result;
+
+use crate::{
+    glossary::Glossary,
+    ui::{
+        buffers::OpenFile,
+        completion::CompletionMenu,
+        config::TuiConfig,
+        editor::TextEditor,
+        layout::Divider,
+        lesson::{CodeRun, Slide},
+        lsp::LspClient,
+        search::SearchMatch,
+        snippets::SnippetSession,
+        std_docs::{DocEntry, StdDocs},
+    },
+};
 
 /// View mode for layout switching
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -20,19 +41,54 @@ pub enum EditorMode {
     Insert,
     Command,
     Visual,
+    VisualLine,
+    VisualBlock,
+}
+
+impl EditorMode {
+    /// Any of the three visual selection shapes
+    pub fn is_visual(self) -> bool {
+        matches!(self, Self::Visual | Self::VisualLine | Self::VisualBlock)
+    }
+}
+
+/// Pending block insert started with `I`/`A` in visual block mode.
+/// The text typed on `start_row` is replicated on the other rows on Esc.
+#[derive(Clone, Copy)]
+pub struct BlockInsert {
+    pub start_row: usize,
+    pub end_row: usize,
+    /// Column of the insert on the first row
+    pub col: usize,
+    /// Display cell of the insert on the other rows
+    pub cell: usize,
+    pub line_len: usize,
+    /// `A` pads short rows, `I` skips them
+    pub append: bool,
+}
+
+/// Code blocks of a Frog slide being run on another thread
+pub struct FrogRun {
+    pub exercise_ind: usize,
+    pub step: usize,
+    /// Result of each block by its index, the channel closes when all ran
+    pub results: Receiver<(usize, Result<CodeRun>)>,
 }
 
 /// Main TUI state container
 pub struct TuiState<'a> {
     pub app_state: &'a mut crate::app_state::AppState,
+    pub config: TuiConfig,
     pub editor: TextEditor,
     pub solution_content: Option<String>,
+    pub solution_scroll: usize,
     pub output: String,
     pub output_buffer: Vec<u8>,
     pub mode: EditorMode,
     pub view_mode: ViewMode,
     pub command_buffer: String,
     pub modified: bool,
+    /// File of the shown buffer
     pub file_path: String,
     pub output_scroll: u16,
     pub start_time: Instant,
@@ -43,10 +99,38 @@ pub struct TuiState<'a> {
     pub pending_keys: Vec<char>,
     pub visual_start_row: usize,
     pub visual_start_col: usize,
+    pub block_insert: Option<BlockInsert>,
     pub show_frog: bool,
     pub frog_step: usize,
-    pub current_frog_steps: Vec<String>,
+    pub current_frog_steps: Vec<Slide>,
+    /// Runs of the code blocks on the current slide by their index
+    pub frog_runs: HashMap<usize, CodeRun>,
+    /// Running code blocks, `poll_frog_run` moves their results to `frog_runs`
+    pub frog_run: Option<FrogRun>,
+    /// Code block of the current slide selected to be copied
+    pub frog_block: Option<usize>,
+    /// Content rows of the code blocks on the current slide, updated when rendering
+    pub frog_block_rows: Vec<Range<usize>>,
     pub frog_scroll: usize,
     pub frog_content_height: usize,
     pub frog_visible_height: usize,
+    /// `None` if rust-analyzer is disabled or couldn't be started
+    pub lsp: Option<LspClient>,
+    pub completion: Option<CompletionMenu>,
+    pub snippet: Option<SnippetSession>,
+    /// Open files, the shown one's content is in `editor`, `file_path`, `modified`
+    /// and `last_file_modified`
+    pub buffers: Vec<OpenFile>,
+    pub active_buffer: usize,
+    /// Border being dragged with the mouse
+    pub resizing: Option<Divider>,
+    /// Matches of the last `:grep`
+    pub grep_matches: Vec<SearchMatch>,
+    /// Match shown by `:cc`, `:cn` or `:cp`
+    pub grep_match: Option<usize>,
+    pub glossary: Glossary,
+    /// `None` until the first lookup in the offline std docs
+    pub std_docs: Option<StdDocs>,
+    /// Results of the last std docs lookup, shown in a popup until the next key
+    pub doc_popup: Option<Vec<DocEntry>>,
 }
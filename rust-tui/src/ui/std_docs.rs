@@ -0,0 +1,516 @@
+//! Offline lookup of std items and methods in the docs installed by rustup: `gK`, `:doc`
+//! and `K` while rust-analyzer isn't running. The format of rustdoc's search index changes
+//! between releases, so the list of all items in `std/all.html` and the item pages are read.
+//! Methods are found with an index of the pages that document them, built once on a
+//! background thread. The results are shown in a popup next to the cursor, `o` shows the
+//! path of a page.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    book,
+    ui::{lexer, state::TuiState, theme},
+};
+
+/// More results aren't shown, the name should be more specific
+const MAX_RESULTS: usize = 5;
+// pages read for a method, `clone` is documented on hundreds of them
+const MAX_METHOD_PAGES: usize = 20;
+// long declarations like the one of a trait are cut
+const MAX_SIGNATURE_LINES: usize = 6;
+// rustdoc's note after the declaration of a nightly-only item or method
+const UNSTABLE: &str = "<span class=\"item-info\"><div class=\"stab unstable\">";
+
+// pages searched first for a method, the types and traits used by the exercises
+const COMMON_PAGES: [&str; 22] = [
+    "option/enum.Option.html",
+    "result/enum.Result.html",
+    "vec/struct.Vec.html",
+    "primitive.slice.html",
+    "string/struct.String.html",
+    "primitive.str.html",
+    "collections/struct.HashMap.html",
+    "collections/hash_map/enum.Entry.html",
+    "iter/trait.Iterator.html",
+    "iter/trait.IntoIterator.html",
+    "primitive.char.html",
+    "primitive.i32.html",
+    "primitive.u32.html",
+    "primitive.f64.html",
+    "clone/trait.Clone.html",
+    "convert/trait.From.html",
+    "convert/trait.Into.html",
+    "string/trait.ToString.html",
+    "boxed/struct.Box.html",
+    "rc/struct.Rc.html",
+    "sync/struct.Arc.html",
+    "sync/struct.Mutex.html",
+];
+
+/// An item listed in `std/all.html`
+#[derive(PartialEq, Eq, Debug)]
+struct Item {
+    /// Like `collections::HashMap`, just `bool` for a primitive type
+    path: String,
+    /// Relative to the `std` directory like `collections/struct.HashMap.html`
+    page: String,
+}
+
+impl Item {
+    fn name(&self) -> &str {
+        self.path.rsplit("::").next().unwrap_or(&self.path)
+    }
+
+    fn is_primitive(&self) -> bool {
+        self.page.starts_with("primitive.")
+    }
+}
+
+/// Type or trait documented on a page like `option/enum.Option.html`,
+/// `None` for pages without methods like the ones of functions and macros
+fn page_type(page: &str) -> Option<&str> {
+    let file_name = page.rsplit('/').next()?;
+    let (kind, rest) = file_name.split_once('.')?;
+    if !matches!(kind, "struct" | "enum" | "trait" | "union" | "primitive") {
+        return None;
+    }
+    rest.strip_suffix(".html")
+}
+
+/// The items listed in `std/all.html`
+fn all_items(html: &str) -> Vec<Item> {
+    html.split("<li><a href=\"")
+        .skip(1)
+        .filter_map(|rest| {
+            let (page, rest) = rest.split_once("\">")?;
+            let (path, _) = rest.split_once("</a>")?;
+            page.ends_with(".html").then(|| Item {
+                path: text(path),
+                page: page.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Text of an HTML snippet without its tags
+fn text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = rest[start..].split_once('>').map_or("", |(_, after)| after);
+    }
+    text.push_str(rest);
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+// the HTML between `start` and `end`
+fn between<'a>(html: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = html.split_once(start)?;
+    rest.split_once(end).map(|(inner, _)| inner)
+}
+
+// the first paragraph of a docblock as a single line
+fn summary(docblock: &str) -> String {
+    between(docblock, "<p>", "</p>")
+        .map(|paragraph| {
+            text(paragraph)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+fn signature(html: &str) -> String {
+    let text = text(html);
+    let mut lines: Vec<&str> = text.lines().take(MAX_SIGNATURE_LINES + 1).collect();
+    if lines.len() > MAX_SIGNATURE_LINES {
+        lines[MAX_SIGNATURE_LINES] = "…";
+    }
+    lines.join("\n")
+}
+
+/// Declaration and summary of the item documented on a page, `None` if it's nightly-only.
+/// The declaration is empty for primitive types.
+fn item_doc(page: &str) -> Option<(String, String)> {
+    let mut signature_text = String::new();
+    if let Some((_, rest)) = page.split_once("<pre class=\"rust item-decl\"><code>") {
+        let (decl, after) = rest.split_once("</code></pre>")?;
+        if after.starts_with(UNSTABLE) {
+            return None;
+        }
+        signature_text = signature(decl);
+    }
+    let summary = page
+        .split_once("<details class=\"toggle top-doc\"")
+        .map(|(_, doc)| summary(doc))
+        .unwrap_or_default();
+    Some((signature_text, summary))
+}
+
+/// Names of the methods documented on a type's page, required methods of traits included
+fn method_names(html: &str) -> impl Iterator<Item = &str> {
+    html.split("<section id=\"").skip(1).filter_map(|rest| {
+        let (id, _) = rest.split_once('"')?;
+        id.strip_prefix("method.")
+            .or_else(|| id.strip_prefix("tymethod."))
+    })
+}
+
+/// Method name to the indices of the pages that document it, in the order of the pages
+type MethodIndex = HashMap<String, Vec<usize>>;
+
+// reads every page, 100+ MB, so it only runs on a background thread
+fn method_index(dir: &Path, pages: &[String]) -> MethodIndex {
+    let mut index = MethodIndex::new();
+    for (ind, page) in pages.iter().enumerate() {
+        let Ok(html) = fs::read_to_string(dir.join(page)) else {
+            continue;
+        };
+        for name in method_names(&html) {
+            let pages = index.entry(name.to_string()).or_default();
+            if pages.last() != Some(&ind) {
+                pages.push(ind);
+            }
+        }
+    }
+    index
+}
+
+enum Methods {
+    Indexing(Receiver<MethodIndex>),
+    Ready(MethodIndex),
+}
+
+/// Anchor, signature and summary of a stable method documented on a type's page,
+/// required methods of traits included
+fn method_doc(page: &str, name: &str) -> Option<(String, String, String)> {
+    ["method", "tymethod"].into_iter().find_map(|kind| {
+        let anchor = format!("{kind}.{name}");
+        let (_, section) = page.split_once(&format!("<section id=\"{anchor}\""))?;
+        // without the tooltip of the notable traits of the return type
+        let signature = between(section, "<h4 class=\"code-header\">", "</h4>")
+            .map(|header| text(header).replace(" ⓘ", ""))?;
+        let (_, after) = section.split_once("</section>")?;
+        let after = after.strip_prefix("</summary>").unwrap_or(after);
+        if after.starts_with(UNSTABLE) {
+            return None;
+        }
+        // undocumented methods have no docblock, the next one belongs to another method
+        let summary = after
+            .strip_prefix("<div class=\"docblock\">")
+            .map(summary)
+            .unwrap_or_default();
+        Some((anchor, signature, summary))
+    })
+}
+
+/// Result of a lookup
+pub struct DocEntry {
+    /// Like `std::collections::HashMap` or `Option::unwrap_or`
+    pub name: String,
+    pub signature: String,
+    pub summary: String,
+    /// Short form of the URL like `std/option/enum.Option.html#method.unwrap_or`
+    pub location: String,
+    pub page: PathBuf,
+    /// Like `method.unwrap_or`
+    pub anchor: Option<String>,
+}
+
+impl DocEntry {
+    /// The page with the anchor as a `file://` URL
+    pub fn url(&self) -> String {
+        let path = self.page.to_string_lossy().replace('\\', "/");
+        let prefix = if path.starts_with('/') {
+            "file://"
+        } else {
+            "file:///"
+        };
+        match &self.anchor {
+            Some(anchor) => format!("{prefix}{path}#{anchor}"),
+            None => format!("{prefix}{path}"),
+        }
+    }
+}
+
+/// The offline std docs, loaded on the first lookup
+pub struct StdDocs {
+    dir: PathBuf,
+    items: Vec<Item>,
+    /// Pages of the types and traits, the common ones first
+    pages: Vec<String>,
+    methods: Methods,
+}
+
+impl StdDocs {
+    fn load() -> Result<Self> {
+        let Some(dir) = book::local_dir("--std") else {
+            bail!(
+                "The offline std docs aren't installed. Install them with `rustup component add rust-docs`"
+            );
+        };
+        let all_path = dir.join("all.html");
+        let all = fs::read_to_string(&all_path)
+            .with_context(|| format!("Failed to read {}", all_path.display()))?;
+        let items = all_items(&all);
+
+        let mut pages: Vec<String> = COMMON_PAGES.map(String::from).to_vec();
+        pages.extend(
+            items
+                .iter()
+                .map(|item| &item.page)
+                .filter(|page| page_type(page).is_some() && !COMMON_PAGES.contains(&page.as_str()))
+                .cloned(),
+        );
+        let (sender, receiver) = mpsc::channel();
+        let (index_dir, index_pages) = (dir.clone(), pages.clone());
+        thread::spawn(move || {
+            let _ = sender.send(method_index(&index_dir, &index_pages));
+        });
+
+        Ok(Self {
+            dir,
+            items,
+            pages,
+            methods: Methods::Indexing(receiver),
+        })
+    }
+
+    /// Items with the name like `HashMap` or `println`, otherwise methods like `unwrap_or`.
+    /// `Type::method` only searches the methods of `Type`.
+    /// `None` while the methods are still being indexed.
+    fn lookup(&mut self, name: &str) -> Option<Vec<DocEntry>> {
+        let (type_name, name) = match name.rsplit_once("::") {
+            Some((path, name)) => (path.rsplit("::").next(), name),
+            None => (None, name),
+        };
+
+        if type_name.is_none() {
+            let items = distinct(
+                self.items
+                    .iter()
+                    .filter(|item| item.name() == name)
+                    .filter_map(|item| {
+                        let page = self.dir.join(&item.page);
+                        let (signature, summary) = item_doc(&fs::read_to_string(&page).ok()?)?;
+                        Some(DocEntry {
+                            name: if item.is_primitive() {
+                                item.path.clone()
+                            } else {
+                                format!("std::{}", item.path)
+                            },
+                            signature,
+                            summary,
+                            location: format!("std/{}", item.page),
+                            page,
+                            anchor: None,
+                        })
+                    }),
+            );
+            if !items.is_empty() {
+                return Some(items);
+            }
+        }
+
+        if let Methods::Indexing(receiver) = &self.methods {
+            self.methods = match receiver.try_recv() {
+                Ok(index) => Methods::Ready(index),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => Methods::Ready(MethodIndex::new()),
+            };
+        }
+        let Methods::Ready(index) = &self.methods else {
+            return None;
+        };
+
+        let methods = index
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|&ind| self.pages[ind].as_str())
+            .filter(|page| {
+                type_name
+                    .is_none_or(|name| page_type(page).is_some_and(|page_type| name == page_type))
+            })
+            .take(MAX_METHOD_PAGES)
+            .filter_map(|page| {
+                let path = self.dir.join(page);
+                let html = fs::read_to_string(&path).ok()?;
+                let (anchor, signature, summary) = method_doc(&html, name)?;
+                Some(DocEntry {
+                    name: format!("{}::{name}", page_type(page)?),
+                    signature,
+                    summary,
+                    location: format!("std/{page}#{anchor}"),
+                    page: path,
+                    anchor: Some(anchor),
+                })
+            });
+        Some(distinct(methods))
+    }
+}
+
+// the first results without duplicates, like re-exported items or the methods of slices
+// that `Vec` documents again
+fn distinct(entries: impl Iterator<Item = DocEntry>) -> Vec<DocEntry> {
+    let mut distinct: Vec<DocEntry> = Vec::new();
+    for entry in entries {
+        let duplicate = distinct
+            .iter()
+            .any(|other| other.signature == entry.signature && other.summary == entry.summary);
+        if !duplicate {
+            distinct.push(entry);
+            if distinct.len() == MAX_RESULTS {
+                break;
+            }
+        }
+    }
+    distinct
+}
+
+impl TuiState<'_> {
+    /// `gK`, or `K` without rust-analyzer: look up the identifier under the cursor
+    pub fn std_doc_under_cursor(&mut self) {
+        match self.editor.word_at_cursor(lexer::is_ident_char) {
+            Some(word) => self.show_std_doc(&word),
+            None => {
+                self.output = format!("{} No identifier under the cursor", theme::icons::INFO);
+            }
+        }
+    }
+
+    /// `:doc <name>`: show the std docs of an item or method in the popup
+    pub fn show_std_doc(&mut self, name: &str) {
+        let name = name.trim().trim_end_matches(['!', '(', ')']);
+        if name.is_empty() {
+            self.output = format!("{} Usage: :doc <name>", theme::icons::ERROR);
+            return;
+        }
+
+        if self.std_docs.is_none() {
+            match StdDocs::load() {
+                Ok(docs) => self.std_docs = Some(docs),
+                Err(e) => {
+                    self.output = format!("{} {e:#}", theme::icons::ERROR);
+                    return;
+                }
+            }
+        }
+        let Some(docs) = &mut self.std_docs else {
+            return;
+        };
+
+        match docs.lookup(name) {
+            None => {
+                self.output = format!(
+                    "{} Indexing the std docs, try again in a moment",
+                    theme::icons::INFO
+                );
+            }
+            Some(entries) if entries.is_empty() => {
+                self.output = format!("{} `{name}` isn't in the std docs", theme::icons::INFO);
+            }
+            Some(entries) => self.doc_popup = Some(entries),
+        }
+    }
+
+    /// `o` or the number of a result in the docs popup: show the path of its page
+    pub fn std_doc_path(&mut self, ind: usize) {
+        let Some(entry) = self
+            .doc_popup
+            .take()
+            .and_then(|entries| entries.into_iter().nth(ind))
+        else {
+            return;
+        };
+        self.output = format!("{} {}: {}", theme::icons::SOLUTION, entry.name, entry.url());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_html() {
+        let html = r##"<ul class="all-items"><li><a href="collections/struct.HashMap.html">collections::HashMap</a></li><li><a href="primitive.bool.html">bool</a></li></ul><li><a href="#structs">Structs</a></li>"##;
+        let items = all_items(html);
+        assert_eq!(
+            items,
+            [
+                Item {
+                    path: "collections::HashMap".to_string(),
+                    page: "collections/struct.HashMap.html".to_string(),
+                },
+                Item {
+                    path: "bool".to_string(),
+                    page: "primitive.bool.html".to_string(),
+                },
+            ]
+        );
+        assert_eq!(items[0].name(), "HashMap");
+        assert_eq!(page_type(&items[0].page), Some("HashMap"));
+        assert_eq!(page_type("macro.println.html"), None);
+    }
+
+    #[test]
+    fn item_and_method_docs() {
+        let page = r##"<pre class="rust item-decl"><code>pub enum Option&lt;T&gt; {
+    None,
+}</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>The <code>Option</code>
+type.</p></div></details>
+<section id="method.unwrap_or" class="method"><h4 class="code-header">pub fn <a href="#method.unwrap_or" class="fn">unwrap_or</a>(self, default: T) -&gt; T <a href="#" class="tooltip" data-notable-ty="T">ⓘ</a></h4></section></summary><div class="docblock"><p>Returns the contained <a href="x">value</a> or a default.</p></div>
+<section id="method.undocumented" class="method"><h4 class="code-header">pub fn undocumented(&amp;self)</h4></section><section id="method.nightly" class="method"><h4 class="code-header">pub fn nightly(&amp;self)</h4></section><span class="item-info"><div class="stab unstable">Nightly</div></span><section id="tymethod.next" class="method"><h4 class="code-header">fn next(&amp;mut self)</h4></section></summary><div class="docblock"><p>Advances.</p></div>"##;
+
+        assert_eq!(
+            item_doc(page),
+            Some((
+                "pub enum Option<T> {\n    None,\n}".to_string(),
+                "The Option type.".to_string()
+            ))
+        );
+        let unstable =
+            format!("<pre class=\"rust item-decl\"><code>pub macro iter</code></pre>{UNSTABLE}");
+        assert_eq!(item_doc(&unstable), None);
+        assert_eq!(
+            method_doc(page, "unwrap_or"),
+            Some((
+                "method.unwrap_or".to_string(),
+                "pub fn unwrap_or(self, default: T) -> T".to_string(),
+                "Returns the contained value or a default.".to_string()
+            ))
+        );
+        assert_eq!(
+            method_doc(page, "undocumented").map(|(_, _, summary)| summary),
+            Some(String::new())
+        );
+        assert_eq!(
+            method_doc(page, "next")
+                .map(|(anchor, _, _)| anchor)
+                .as_deref(),
+            Some("tymethod.next")
+        );
+        assert_eq!(method_doc(page, "nightly"), None);
+        assert_eq!(method_doc(page, "unwrap"), None);
+        assert_eq!(
+            method_names(page).collect::<Vec<_>>(),
+            ["unwrap_or", "undocumented", "nightly", "next"]
+        );
+    }
+}
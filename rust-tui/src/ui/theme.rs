@@ -1,6 +1,8 @@
 //! Theme and color definitions for the Rustlings TUI
 
-use ratatui::style::{Modifier, Style};
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::ui::{lexer::TokenKind, lsp::Severity};
 
 /// Dracula-based color palette
 pub mod colors {
@@ -26,6 +28,13 @@ pub mod colors {
     pub const STRING: Color = Color::Rgb(241, 250, 140); // Yellow
     pub const COMMENT: Color = Color::Rgb(98, 114, 164); // Gray
     pub const NUMBER: Color = Color::Rgb(189, 147, 249); // Purple
+    pub const TYPE: Color = Color::Rgb(139, 233, 253); // Cyan
+    pub const MACRO: Color = Color::Rgb(80, 250, 123); // Green
+    pub const FUNCTION: Color = Color::Rgb(166, 226, 146); // Light green
+    pub const LIFETIME: Color = Color::Rgb(255, 184, 108); // Amber
+    pub const ATTRIBUTE: Color = Color::Rgb(189, 147, 249); // Purple
+    pub const DOC_COMMENT: Color = Color::Rgb(130, 170, 120); // Muted green
+    pub const ESCAPE: Color = Color::Rgb(255, 121, 63); // Rust orange
 
     // Text
     pub const TEXT: Color = Color::Rgb(248, 248, 242);
@@ -42,6 +51,35 @@ pub mod icons {
     pub const SOLUTION: &str = "📖";
     pub const CRAB: &str = "🦀";
     pub const INFO: &str = "ℹ";
+    pub const SEARCH: &str = "🔍";
+}
+
+/// Syntax highlighting style of a token class
+pub fn token_style(kind: TokenKind) -> Style {
+    let style = Style::default();
+    match kind {
+        TokenKind::Plain | TokenKind::Punct => style.fg(colors::TEXT),
+        TokenKind::Keyword => style.fg(colors::KEYWORD).add_modifier(Modifier::BOLD),
+        TokenKind::Type => style.fg(colors::TYPE),
+        TokenKind::Macro => style.fg(colors::MACRO),
+        TokenKind::Function => style.fg(colors::FUNCTION),
+        TokenKind::Lifetime => style.fg(colors::LIFETIME).add_modifier(Modifier::ITALIC),
+        TokenKind::Attribute => style.fg(colors::ATTRIBUTE),
+        TokenKind::Comment => style.fg(colors::COMMENT),
+        TokenKind::DocComment => style.fg(colors::DOC_COMMENT).add_modifier(Modifier::ITALIC),
+        TokenKind::String | TokenKind::Char => style.fg(colors::STRING),
+        TokenKind::Escape => style.fg(colors::ESCAPE),
+        TokenKind::Number => style.fg(colors::NUMBER),
+    }
+}
+
+/// Color of diagnostics from rust-analyzer
+pub fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Error => colors::ERROR,
+        Severity::Warning => colors::WARNING,
+        Severity::Info => colors::INFO,
+    }
 }
 
 /// Mode indicator styles
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io::stdout;
 use std::time::{Duration, Instant};
@@ -13,13 +14,22 @@ use ratatui::prelude::*;
 
 use crate::app_state::AppState;
 use crate::exercise::OUTPUT_CAPACITY;
+use crate::glossary::Glossary;
 use crate::ui::{
+    buffer::Buffer,
+    buffers::{EXERCISE_BUFFER, OpenFile},
+    config::TuiConfig,
     editor::TextEditor,
-    handlers, layout, render,
+    handlers, layout,
+    lsp::LspClient,
+    render,
     state::{EditorMode, TuiState, ViewMode},
+    theme,
 };
 
 const FILE_WATCH_POLL_MS: u64 = 500;
+// while waiting for rust-analyzer's answers or the output of Frog code
+const LSP_POLL_MS: u64 = 50;
 
 impl<'a> TuiState<'a> {
     pub fn new(app_state: &'a mut AppState) -> Result<Self> {
@@ -27,14 +37,17 @@ impl<'a> TuiState<'a> {
         let file_path = exercise.path.to_string();
         let exercise_name = exercise.name.to_string();
         let content = fs::read_to_string(&file_path)?;
-        let editor = TextEditor::new(&content);
+        let mut editor = TextEditor::new(&content);
+        editor.load_history(&exercise_name);
         let last_file_modified = Self::get_file_modified_time(&file_path);
-        let frog_steps = Self::load_frog_content(&file_path, &exercise_name);
+        let frog_steps = Self::load_frog_content(app_state);
 
-        Ok(Self {
+        let mut state = Self {
             app_state,
+            config: TuiConfig::load(),
             editor,
             solution_content: None,
+            solution_scroll: 0,
             output: String::new(),
             output_buffer: Vec::with_capacity(OUTPUT_CAPACITY),
             mode: EditorMode::Normal,
@@ -51,65 +64,107 @@ impl<'a> TuiState<'a> {
             pending_keys: Vec::new(),
             visual_start_row: 0,
             visual_start_col: 0,
+            block_insert: None,
             show_frog: true,
             frog_step: 0,
             current_frog_steps: frog_steps,
+            frog_runs: HashMap::new(),
+            frog_run: None,
+            frog_block: None,
+            frog_block_rows: Vec::new(),
             frog_scroll: 0,
             frog_content_height: 0,
             frog_visible_height: 0,
-        })
+            lsp: None,
+            completion: None,
+            snippet: None,
+            buffers: vec![OpenFile::shown()],
+            active_buffer: EXERCISE_BUFFER,
+            resizing: None,
+            grep_matches: Vec::new(),
+            grep_match: None,
+            glossary: Glossary::parse()?,
+            std_docs: None,
+            doc_popup: None,
+        };
+        state.start_lsp();
+        Ok(state)
     }
 }
 
 fn render(frame: &mut Frame, state: &mut TuiState) {
-    let (header, main, footer) = if state.view_mode == ViewMode::ExpandedOutput {
-        layout::expanded_layout(frame.area())
-    } else {
-        layout::main_layout(frame.area())
-    };
+    let panes = layout::panes(
+        frame.area(),
+        state.view_mode,
+        state.show_frog,
+        &state.config.layout,
+    );
 
-    render::render_header(frame, header, state);
+    render::render_header(frame, panes.header, state);
 
-    match state.view_mode {
-        ViewMode::EditorOnly => {
-            if state.show_frog {
-                let (editor_area, right_panel) = layout::split_editors_layout(main);
-                render::render_editor(frame, editor_area, state, true);
-                render::render_frog_panel(frame, right_panel, state);
-            } else {
-                render::render_editor(frame, main, state, true);
-            }
-        }
-        ViewMode::WithSolution => {
-            let (editor_area, right_panel) = layout::split_editors_layout(main);
-            render::render_editor(frame, editor_area, state, true);
+    if let Some(editor_area) = panes.editor {
+        render::render_editor(frame, editor_area, state, true);
+    }
+    if let Some(right_panel) = panes.side {
+        if state.view_mode == ViewMode::WithSolution {
             render::render_solution(frame, right_panel, state);
+        } else {
+            render::render_frog_panel(frame, right_panel, state);
         }
-        ViewMode::ExpandedOutput => {
-            render::render_expanded_output(frame, main, state);
-        }
-        ViewMode::HelpModal => {
-            if state.show_frog {
-                let (editor_area, right_panel) = layout::split_editors_layout(main);
-                render::render_editor(frame, editor_area, state, true);
-                render::render_frog_panel(frame, right_panel, state);
-            } else {
-                render::render_editor(frame, main, state, true);
-            }
-            render::render_help_modal(frame, main);
-        }
     }
 
-    render::render_footer(frame, footer, state);
+    match state.view_mode {
+        ViewMode::ExpandedOutput => render::render_expanded_output(frame, panes.main, state),
+        ViewMode::HelpModal => render::render_help_modal(frame, panes.main),
+        ViewMode::EditorOnly | ViewMode::WithSolution => (),
+    }
+
+    render::render_footer(frame, panes.footer, state);
 }
 
 fn handle_key(key: event::KeyEvent, state: &mut TuiState) -> Result<Option<bool>> {
-    match state.mode {
-        EditorMode::Normal => handlers::handle_normal_mode(key, state),
-        EditorMode::Insert => handlers::handle_insert_mode(key, state),
+    let file_path = state.file_path.clone();
+    let before = state.editor.buffer.rope().clone();
+    let cursor = (state.editor.cursor_row, state.editor.cursor_col);
+    let modified = state.modified;
+    let mode = state.mode;
+
+    let result = match state.mode {
+        // `:reset` and `:r` load a new editor with its own snapshot
         EditorMode::Command => handlers::handle_command_mode(key, state),
-        EditorMode::Visual => handlers::handle_visual_mode(key, state),
+        EditorMode::Insert => handlers::handle_insert_mode(key, state),
+        EditorMode::Visual | EditorMode::VisualLine | EditorMode::VisualBlock => {
+            handlers::handle_visual_mode(key, state)
+        }
+        _ => handlers::handle_normal_mode(key, state),
+    };
+
+    // undo edits touching a read-only region of the same exercise, including the ones of
+    // commands like `:earlier`
+    let edited = state.file_path == file_path && *state.editor.buffer.rope() != before;
+    if edited {
+        let readonly = state.editor.readonly_text();
+        let snapshot = &state.editor.readonly_snapshot;
+        if snapshot.is_some() && readonly != *snapshot {
+            state.editor.buffer = Buffer::from_rope(before);
+            (state.editor.cursor_row, state.editor.cursor_col) = cursor;
+            state.modified = modified;
+            // e.g. `cc` doesn't leave the user in insert mode, commands return to normal mode
+            if mode != EditorMode::Command {
+                state.mode = mode;
+            }
+            state.completion = None;
+            state.output = format!(
+                "{} This part of the exercise is read-only",
+                theme::icons::ERROR
+            );
+        } else {
+            state.editor.readonly_snapshot = readonly;
+            state.editor.mark_edited();
+        }
     }
+
+    result
 }
 
 pub fn run_tui(app_state: &mut AppState) -> Result<()> {
@@ -122,9 +177,17 @@ pub fn run_tui(app_state: &mut AppState) -> Result<()> {
     let mut state = TuiState::new(app_state)?;
 
     loop {
+        state.poll_lsp();
+        state.poll_frog_run();
         terminal.draw(|frame| render(frame, &mut state))?;
 
-        if !event::poll(Duration::from_millis(FILE_WATCH_POLL_MS))? {
+        let busy = state.lsp.as_ref().is_some_and(LspClient::is_busy) || state.frog_run.is_some();
+        let poll_ms = if busy {
+            LSP_POLL_MS
+        } else {
+            FILE_WATCH_POLL_MS
+        };
+        if !event::poll(Duration::from_millis(poll_ms))? {
             state.check_external_file_change()?;
             continue;
         }
@@ -151,21 +214,9 @@ fn handle_event(event: Event, state: &mut TuiState) -> Result<bool> {
             Ok(handle_key(key, state)?.unwrap_or(false))
         }
         Event::Mouse(mouse) => {
-            handle_mouse(mouse, state);
+            handlers::handle_mouse(mouse, state)?;
             Ok(false)
         }
         _ => Ok(false),
     }
 }
-
-fn handle_mouse(mouse: event::MouseEvent, state: &mut TuiState) {
-    match mouse.kind {
-        event::MouseEventKind::ScrollDown => {
-            state.output_scroll = state.output_scroll.saturating_add(2);
-        }
-        event::MouseEventKind::ScrollUp => {
-            state.output_scroll = state.output_scroll.saturating_sub(2);
-        }
-        _ => {}
-    }
-}
@@ -0,0 +1,544 @@
+//! Diff-based undo tree with time travel and on-disk persistence
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+const HISTORY_DIR: &str = ".rustlings-undo";
+
+// oldest linear history is merged into the root beyond this many changes
+const MAX_UNDO_HISTORY: usize = 1000;
+
+/// Replacement of the `removed` text at char index `start` by `inserted`
+#[derive(Clone, Serialize, Deserialize)]
+struct Change {
+    start: usize,
+    removed: String,
+    inserted: String,
+}
+
+impl Change {
+    /// Smallest change of whole lines turning `old` into `new` (common lines are skipped).
+    /// Ropes compare chunk by chunk so this is cheap even for large buffers.
+    fn between(old: &Rope, new: &Rope) -> Option<Self> {
+        let (old_len, new_len) = (old.len_lines(), new.len_lines());
+        let prefix = old
+            .lines()
+            .zip(new.lines())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix == old_len && prefix == new_len {
+            return None;
+        }
+
+        let max_suffix = old_len.min(new_len) - prefix;
+        let suffix = (1..=max_suffix)
+            .take_while(|i| old.line(old_len - i) == new.line(new_len - i))
+            .count();
+
+        let start = old.line_to_char(prefix);
+        let old_end = old.line_to_char(old_len - suffix);
+        let new_end = new.line_to_char(new_len - suffix);
+        Some(Self {
+            start,
+            removed: old.slice(start..old_end).to_string(),
+            inserted: new.slice(start..new_end).to_string(),
+        })
+    }
+
+    // whether `text` contains `from` at `start`, replacing it can't panic then
+    fn matches(text: &Rope, start: usize, from: &str) -> bool {
+        let end = start.saturating_add(from.chars().count());
+        end <= text.len_chars() && text.slice(start..end) == from
+    }
+
+    fn replace(text: &mut Rope, start: usize, from: &str, to: &str) {
+        text.remove(start..start + from.chars().count());
+        text.insert(start, to);
+    }
+
+    fn apply(&self, text: &mut Rope) {
+        Self::replace(text, self.start, &self.removed, &self.inserted);
+    }
+
+    fn revert(&self, text: &mut Rope) {
+        Self::replace(text, self.start, &self.inserted, &self.removed);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct UndoNode {
+    parent: usize,
+    // `None` only for the root
+    change: Option<Change>,
+    // cursor before the change, restored when undoing it
+    cursor: (usize, usize),
+    // seconds since the UNIX epoch
+    time: u64,
+    // child followed by redo (the most recently visited branch)
+    last_child: Option<usize>,
+}
+
+/// Every edit becomes a node. Undoing and then editing starts a new branch
+/// instead of discarding the undone changes.
+/// Node indices are in chronological order which `g-`/`g+` and `:earlier` rely on.
+#[derive(Serialize, Deserialize)]
+pub struct UndoTree {
+    nodes: Vec<UndoNode>,
+    current: usize,
+}
+
+/// Seconds since the UNIX epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+impl Default for UndoTree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![UndoNode {
+                parent: 0,
+                change: None,
+                cursor: (0, 0),
+                time: now(),
+                last_child: None,
+            }],
+            current: 0,
+        }
+    }
+}
+
+impl UndoTree {
+    /// Record the change from `old` to `new` made at `time` as a child of the current node
+    pub fn commit(&mut self, old: &Rope, new: &Rope, cursor: (usize, usize), time: u64) {
+        let Some(change) = Change::between(old, new) else {
+            return;
+        };
+
+        self.nodes.push(UndoNode {
+            parent: self.current,
+            change: Some(change),
+            cursor,
+            time,
+            last_child: None,
+        });
+        let ind = self.nodes.len() - 1;
+        self.nodes[self.current].last_child = Some(ind);
+        self.current = ind;
+
+        self.prune(new);
+    }
+
+    /// Whether a tree read from disk fits `text`, the buffer at its current node.
+    /// Parents must come before their children, otherwise walking to the root never ends,
+    /// and every change must apply to the text of its parent.
+    fn is_valid(&self, text: &Rope) -> bool {
+        let nodes = &self.nodes;
+        let shape = self.current < nodes.len()
+            && nodes
+                .first()
+                .is_some_and(|root| root.parent == 0 && root.change.is_none())
+            && nodes.iter().enumerate().all(|(ind, node)| {
+                (ind == 0 || (node.parent < ind && node.change.is_some()))
+                    && node
+                        .last_child
+                        .is_none_or(|child| child < nodes.len() && nodes[child].parent == ind)
+            });
+        if !shape {
+            return false;
+        }
+
+        let mut root = text.clone();
+        let mut ind = self.current;
+        while let Some(change) = &nodes[ind].change {
+            if !Change::matches(&root, change.start, &change.inserted) {
+                return false;
+            }
+            change.revert(&mut root);
+            ind = nodes[ind].parent;
+        }
+
+        // the parent's text is always known since it comes first
+        let mut texts = vec![root];
+        for node in &nodes[1..] {
+            let mut text = texts[node.parent].clone();
+            let Some(change) = &node.change else {
+                return false;
+            };
+            if !Change::matches(&text, change.start, &change.removed) {
+                return false;
+            }
+            change.apply(&mut text);
+            texts.push(text);
+        }
+        true
+    }
+
+    /// Revert the current change. Returns the cursor to restore.
+    pub fn undo(&mut self, text: &mut Rope) -> Option<(usize, usize)> {
+        let node = &self.nodes[self.current];
+        let change = node.change.as_ref()?;
+        change.revert(text);
+
+        let cursor = node.cursor;
+        let parent = node.parent;
+        self.nodes[parent].last_child = Some(self.current);
+        self.current = parent;
+        Some(cursor)
+    }
+
+    /// Reapply the most recently undone change. Returns the cursor to restore.
+    pub fn redo(&mut self, text: &mut Rope) -> Option<(usize, usize)> {
+        let child = self.nodes[self.current].last_child?;
+        let change = self.nodes[child].change.as_ref()?;
+        change.apply(text);
+
+        self.current = child;
+        Some((text.char_to_line(change.start), 0))
+    }
+
+    /// Move to any node: undo up to the common ancestor, then redo down to `target`
+    fn goto(&mut self, target: usize, text: &mut Rope) -> Option<(usize, usize)> {
+        if target == self.current || target >= self.nodes.len() {
+            return None;
+        }
+
+        let ancestors = |mut ind: usize| {
+            let mut path = vec![ind];
+            while ind != 0 {
+                ind = self.nodes[ind].parent;
+                path.push(ind);
+            }
+            path
+        };
+        let target_path = ancestors(target);
+
+        let mut cursor = None;
+        while !target_path.contains(&self.current) {
+            cursor = self.undo(text);
+        }
+
+        let common = target_path
+            .iter()
+            .position(|&ind| ind == self.current)
+            .unwrap_or(0);
+        for &ind in target_path[..common].iter().rev() {
+            self.nodes[self.current].last_child = Some(ind);
+            cursor = self.redo(text);
+        }
+
+        cursor
+    }
+
+    /// `g-`/`g+`: move `steps` changes back or forward in time regardless of branches
+    pub fn step_chronological(&mut self, steps: isize, text: &mut Rope) -> Option<(usize, usize)> {
+        let target = self
+            .current
+            .saturating_add_signed(steps)
+            .min(self.nodes.len() - 1);
+        self.goto(target, text)
+    }
+
+    /// `:earlier`/`:later` with a duration: go to the last state at `current time + offset`
+    pub fn step_seconds(&mut self, offset: i64, text: &mut Rope) -> Option<(usize, usize)> {
+        let time = self.nodes[self.current].time.saturating_add_signed(offset);
+        let target = self
+            .nodes
+            .iter()
+            .rposition(|node| node.time <= time)
+            .unwrap_or(0);
+        self.goto(target, text)
+    }
+
+    // merge the oldest change into the root beyond `MAX_UNDO_HISTORY` changes.
+    // It's always a child of the root, the root's other children are diffed against the merged
+    // text again. `text` is the buffer at the current node.
+    fn prune(&mut self, text: &Rope) {
+        if self.nodes.len() <= MAX_UNDO_HISTORY {
+            return;
+        }
+
+        let mut root_text = text.clone();
+        let mut ind = self.current;
+        while let Some(change) = &self.nodes[ind].change {
+            change.revert(&mut root_text);
+            ind = self.nodes[ind].parent;
+        }
+
+        // merging into the current node would change the buffer
+        while self.nodes.len() > MAX_UNDO_HISTORY && self.current != 0 {
+            let first = 1;
+            let mut merged_text = root_text.clone();
+            if let Some(change) = &self.nodes[first].change {
+                change.apply(&mut merged_text);
+            }
+            for ind in first + 1..self.nodes.len() {
+                if self.nodes[ind].parent != 0 {
+                    continue;
+                }
+                let mut sibling_text = root_text.clone();
+                if let Some(change) = &self.nodes[ind].change {
+                    change.apply(&mut sibling_text);
+                }
+                // an empty change keeps a sibling with the same text
+                self.nodes[ind].change = Some(
+                    Change::between(&merged_text, &sibling_text).unwrap_or(Change {
+                        start: 0,
+                        removed: String::new(),
+                        inserted: String::new(),
+                    }),
+                );
+            }
+
+            let merged = self.nodes.remove(first);
+            self.nodes[0].time = merged.time;
+            if self.nodes[0].last_child == Some(first) {
+                self.nodes[0].last_child = merged.last_child;
+            }
+
+            let shift = |ind: usize| match ind.cmp(&first) {
+                std::cmp::Ordering::Less => ind,
+                std::cmp::Ordering::Equal => 0,
+                std::cmp::Ordering::Greater => ind - 1,
+            };
+            for node in &mut self.nodes {
+                node.parent = shift(node.parent);
+                node.last_child = node.last_child.map(shift);
+            }
+            self.nodes[0].last_child = self.nodes[0].last_child.filter(|&ind| ind != 0);
+            self.current = shift(self.current);
+            root_text = merged_text;
+        }
+    }
+}
+
+// The history is written together with the buffer it leads to.
+// The buffer is needed to apply the history on a file changed in the meantime.
+#[derive(Serialize)]
+struct HistoryFileRef<'a> {
+    tree: &'a UndoTree,
+    text: &'a str,
+}
+
+#[derive(Deserialize)]
+struct HistoryFile {
+    tree: UndoTree,
+    text: String,
+}
+
+fn history_path(exercise_name: &str) -> PathBuf {
+    PathBuf::from(HISTORY_DIR).join(format!("{exercise_name}.json"))
+}
+
+/// The saved history of an exercise and the text of its current state
+/// if there is a readable history file
+pub fn load_history(exercise_name: &str) -> Option<(UndoTree, Rope)> {
+    let content = fs::read(history_path(exercise_name)).ok()?;
+    let file: HistoryFile = serde_json::de::from_slice(&content).ok()?;
+    let text = Rope::from_str(&file.text);
+
+    // reject corrupted or edited files instead of hanging or panicking later
+    file.tree.is_valid(&text).then_some((file.tree, text))
+}
+
+pub fn write_history(exercise_name: &str, tree: &UndoTree, text: &Rope) -> Result<()> {
+    fs::create_dir_all(HISTORY_DIR)
+        .with_context(|| format!("Failed to create the directory {HISTORY_DIR}"))?;
+
+    let path = history_path(exercise_name);
+    let content = serde_json::ser::to_vec(&HistoryFileRef {
+        tree,
+        text: &text.to_string(),
+    })
+    .context("Failed to serialize the undo history")?;
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write the file {}", path.display()))
+}
+
+/// Distance of `:earlier`/`:later`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeTravel {
+    /// A count of changes
+    Steps(usize),
+    Seconds(u64),
+}
+
+/// Parse the argument of `:earlier`/`:later`: a count of changes or a duration like `2m`.
+/// `None` if it's invalid or too large.
+pub fn parse_time_travel(arg: &str) -> Option<TimeTravel> {
+    let arg = arg.trim();
+    if arg.is_empty() {
+        return Some(TimeTravel::Steps(1));
+    }
+
+    let unit_start = arg.find(|c: char| !c.is_ascii_digit()).unwrap_or(arg.len());
+    let value: u64 = arg[..unit_start].parse().ok()?;
+    let multiplier = match &arg[unit_start..] {
+        "" => return usize::try_from(value).ok().map(TimeTravel::Steps),
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+
+    value.checked_mul(multiplier).map(TimeTravel::Seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Rope {
+        Rope::from_str(&s.replace(',', "\n"))
+    }
+
+    // commit `new` on top of the tree's current `buf`
+    fn edit(tree: &mut UndoTree, buf: &mut Rope, new: &str) {
+        let new = lines(new);
+        tree.commit(buf, &new, (0, 0), now());
+        *buf = new;
+    }
+
+    #[test]
+    fn change_roundtrip() {
+        let old = lines("a,b,c,d");
+        let new = lines("a,x,y,d");
+        let change = Change::between(&old, &new).unwrap();
+        assert_eq!(change.start, 2);
+        assert_eq!(change.removed, "b\nc\n");
+
+        let mut buf = old.clone();
+        change.apply(&mut buf);
+        assert_eq!(buf, new);
+        change.revert(&mut buf);
+        assert_eq!(buf, old);
+
+        assert!(Change::between(&old, &old).is_none());
+    }
+
+    #[test]
+    fn branches_are_kept() {
+        let mut tree = UndoTree::default();
+        let mut buf = lines("a");
+        edit(&mut tree, &mut buf, "a,b");
+        edit(&mut tree, &mut buf, "a,b,c");
+
+        tree.undo(&mut buf);
+        assert_eq!(buf, lines("a,b"));
+
+        // new branch
+        edit(&mut tree, &mut buf, "a,b,x");
+
+        // back in time to the undone branch
+        tree.step_chronological(-1, &mut buf);
+        assert_eq!(buf, lines("a,b,c"));
+        tree.step_chronological(-1, &mut buf);
+        assert_eq!(buf, lines("a,b"));
+        tree.step_chronological(2, &mut buf);
+        assert_eq!(buf, lines("a,b,x"));
+
+        tree.undo(&mut buf);
+        tree.undo(&mut buf);
+        assert_eq!(buf, lines("a"));
+        assert!(tree.undo(&mut buf).is_none());
+
+        // redo follows the most recent branch
+        tree.redo(&mut buf);
+        tree.redo(&mut buf);
+        assert_eq!(buf, lines("a,b,x"));
+    }
+
+    #[test]
+    fn edit_time_is_kept_until_commit() {
+        let mut tree = UndoTree::default();
+        let mut buf = lines("a");
+        let start = tree.nodes[0].time;
+
+        // edited 10 minutes ago but only committed with the next edit
+        let new = lines("a,b");
+        tree.commit(&buf, &new, (0, 0), start + 60);
+        buf = new;
+        let new = lines("a,b,c");
+        tree.commit(&buf, &new, (0, 0), start + 11 * 60);
+        buf = new;
+
+        tree.step_seconds(-2 * 60, &mut buf);
+        assert_eq!(buf, lines("a,b"));
+        tree.step_seconds(10 * 60, &mut buf);
+        assert_eq!(buf, lines("a,b,c"));
+    }
+
+    #[test]
+    fn prune_branched_history() {
+        let mut tree = UndoTree::default();
+        let mut buf = lines("a");
+        edit(&mut tree, &mut buf, "a,b");
+        tree.undo(&mut buf);
+        // the root has two children now
+        edit(&mut tree, &mut buf, "a,x");
+        for i in 0..MAX_UNDO_HISTORY {
+            edit(&mut tree, &mut buf, &format!("a,x,{i}"));
+        }
+        assert_eq!(tree.nodes.len(), MAX_UNDO_HISTORY);
+        assert!(tree.is_valid(&buf));
+
+        // the oldest changes are merged into the root
+        while tree.undo(&mut buf).is_some() {}
+        assert_eq!(buf, lines("a,x,0"));
+        tree.step_chronological(isize::MAX, &mut buf);
+        assert_eq!(buf, lines(&format!("a,x,{}", MAX_UNDO_HISTORY - 1)));
+    }
+
+    #[test]
+    fn corrupted_history() {
+        let tree = |nodes: &str, current: usize| -> UndoTree {
+            serde_json::from_str(&format!(r#"{{"nodes":[{nodes}],"current":{current}}}"#)).unwrap()
+        };
+        let root = r#"{"parent":0,"change":null,"cursor":[0,0],"time":0,"last_child":1}"#;
+        let change = |parent: usize, start: usize, removed: &str| {
+            format!(
+                r#"{{"parent":{parent},"change":{{"start":{start},"removed":"{removed}","inserted":"b\n"}},"cursor":[0,0],"time":0,"last_child":null}}"#
+            )
+        };
+        let text = lines("a,b,");
+
+        let mut valid = tree(&format!("{root},{}", change(0, 2, "")), 1);
+        assert!(valid.is_valid(&text));
+        let mut buf = text.clone();
+        valid.undo(&mut buf);
+        assert_eq!(buf, lines("a,"));
+
+        // parent cycle
+        let cycle = tree(
+            &format!("{root},{},{}", change(2, 2, ""), change(1, 2, "")),
+            2,
+        );
+        assert!(!cycle.is_valid(&text));
+        // the inserted text isn't in the saved text
+        assert!(!tree(&format!("{root},{}", change(0, 9, "")), 1).is_valid(&text));
+        // the removed text of another branch isn't in the parent's text
+        let branch = format!("{root},{},{}", change(0, 2, ""), change(0, 2, "x"));
+        assert!(!tree(&branch, 1).is_valid(&text));
+        assert!(!tree(root, 1).is_valid(&text));
+    }
+
+    #[test]
+    fn time_travel_args() {
+        assert_eq!(parse_time_travel(""), Some(TimeTravel::Steps(1)));
+        assert_eq!(parse_time_travel("3"), Some(TimeTravel::Steps(3)));
+        assert_eq!(parse_time_travel("2m"), Some(TimeTravel::Seconds(120)));
+        assert_eq!(parse_time_travel("1h"), Some(TimeTravel::Seconds(3600)));
+        assert_eq!(parse_time_travel("2x"), None);
+        assert_eq!(parse_time_travel("999999999999999999h"), None);
+        assert_eq!(parse_time_travel("99999999999999999999"), None);
+    }
+}
@@ -12,13 +12,16 @@ struct OtherSoftware {
     version_number: String,
 }
 
+// rustlings:readonly-begin
 impl Licensed for SomeSoftware {}
 impl Licensed for OtherSoftware {}
+// rustlings:readonly-end
 
 fn main() {
     // You can optionally experiment here.
 }
 
+// rustlings:readonly-begin
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,3 +37,4 @@ mod tests {
         assert_eq!(other_software.licensing_info(), licensing_info);
     }
 }
+// rustlings:readonly-end